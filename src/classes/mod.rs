@@ -0,0 +1,248 @@
+/// Builds a space-separated class list, Astro `class:list` style.
+///
+/// Items are separated by commas and may be:
+/// - `(class, condition)`: included only when `condition` is `true`.
+/// - `Option<T: Display>`: skipped when `None`.
+/// - A cloneable iterator of `Display` items: each item is included.
+/// - Anything else implementing `Display` (e.g. `&str`, `String`).
+///
+/// Empty items are dropped. `rsx!` uses this for any attribute written with a
+/// bracketed value.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::{rsx, class_list};
+///
+/// let is_active = true;
+/// let is_disabled = false;
+/// let extra_classes = Some("shadow");
+/// let theme: Option<&str> = None;
+///
+/// let html = rsx!(lined, div {
+///     class: ["card", ("card--active", is_active), ("card--disabled", is_disabled), extra_classes, theme],
+///     "..."
+/// });
+/// assert_eq!(html, r#"<div class="card card--active shadow">...</div>"#);
+///
+/// let tags = ["a", "b"];
+/// assert_eq!(class_list!["x", tags.iter().map(|t| format!("tag-{}", t))], "x tag-a tag-b");
+/// ```
+#[cfg(feature = "macros")]
+#[macro_export]
+macro_rules! class_list {
+    (@acc [$($parts:expr),*] ($class:expr, $cond:expr) $(, $($rest:tt)*)?) => {
+        forge_rsx::class_list!(@acc [$($parts,)* if $cond { ::std::string::ToString::to_string(&$class) } else { String::new() }] $($($rest)*)?)
+    };
+    (@acc [$($parts:expr),*] $item:expr $(, $($rest:tt)*)?) => {
+        forge_rsx::class_list!(@acc [$($parts,)* {
+            #[allow(unused_imports)]
+            use forge_rsx::rules::{RawChild as _, RawIterChild as _, OptionChild as _, IterChild as _, DisplayChild as _};
+            (&&&forge_rsx::rules::ChildExpr(&$item)).render_child("", " ", false, 0, 0)
+        }] $($($rest)*)?)
+    };
+    (@acc []) => {
+        String::new()
+    };
+    (@acc [$($parts:expr),*]) => {
+        forge_rsx::classes::join([$($parts),*])
+    };
+    ($($items:tt)*) => {
+        forge_rsx::class_list!(@acc [] $($items)*)
+    };
+}
+
+/// Joins class lists with single spaces, skipping empty entries.
+pub fn join<I, S>(parts: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    parts
+        .into_iter()
+        .filter_map(|p| {
+            let p = p.as_ref().trim().to_string();
+            (!p.is_empty()).then_some(p)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Merges Tailwind CSS class lists, resolving conflicting utilities so the
+/// last one wins.
+///
+/// Each argument is a whitespace-separated class list and anything that
+/// implements `Display`. Classes are grouped by the CSS property they set
+/// (padding, font size, text color, display, ...), taking variants such as
+/// `hover:`/`md:` and the `!` important modifier into account; when two classes
+/// fall in the same group only the later one is kept. Shorthands override their
+/// longhands (`p-4` drops an earlier `px-2`), but not the other way around.
+/// Unknown classes never conflict and are only deduplicated.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::{rsx, tw_merge, Html};
+///
+/// fn button(extra: &str) -> Html {
+///     rsx!(lined, button { class: tw_merge!("px-4 py-2 text-sm bg-blue-500 rounded", extra), "Save" })
+/// }
+///
+/// assert_eq!(
+///     button("py-3 text-lg bg-red-600 hover:bg-red-700"),
+///     r#"<button class="px-4 rounded py-3 text-lg bg-red-600 hover:bg-red-700">Save</button>"#
+/// );
+/// assert_eq!(tw_merge!("px-2 text-red-500", "p-4 text-center"), "text-red-500 p-4 text-center");
+/// ```
+#[cfg(feature = "macros")]
+#[macro_export]
+macro_rules! tw_merge {
+    ($($classes:expr),+ $(,)?) => {
+        forge_rsx::classes::tw_merge([$(::std::string::ToString::to_string(&$classes)),+])
+    };
+}
+
+/// Function form of [`tw_merge!`](crate::tw_merge!).
+pub fn tw_merge<I, S>(lists: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let lists: Vec<S> = lists.into_iter().collect();
+    let classes: Vec<&str> = lists.iter().flat_map(|l| l.as_ref().split_whitespace()).collect();
+
+    // Walk backwards so the last class of each group claims it first.
+    let mut claimed: Vec<String> = Vec::new();
+    let mut kept: Vec<&str> = Vec::new();
+    for class in classes.into_iter().rev() {
+        if kept.contains(&class) {
+            continue;
+        }
+        let (modifiers, utility) = split_modifiers(class);
+        let Some(group) = utility_group(utility) else {
+            kept.push(class);
+            continue;
+        };
+        let key = format!("{}{}", modifiers, group);
+        if claimed.contains(&key) {
+            continue;
+        }
+        claimed.push(key);
+        for overridden in overridden_groups(group) {
+            claimed.push(format!("{}{}", modifiers, overridden));
+        }
+        kept.push(class);
+    }
+    kept.reverse();
+    kept.join(" ")
+}
+
+/// Splits `md:hover:!p-4` into its modifiers (`md:hover:!`) and utility (`p-4`).
+fn split_modifiers(class: &str) -> (&str, &str) {
+    let variant_end = class.rfind(':').map_or(0, |i| i + 1);
+    let important_end = variant_end + class[variant_end..].len()
+        - class[variant_end..].trim_start_matches('!').len();
+    let utility = &class[important_end..];
+    // Negative values (`-mt-2`) share the group of their positive form.
+    (&class[..important_end], utility.strip_prefix('-').unwrap_or(utility))
+}
+
+const FONT_SIZES: [&str; 13] = ["xs", "sm", "base", "lg", "xl", "2xl", "3xl", "4xl", "5xl", "6xl", "7xl", "8xl", "9xl"];
+const TEXT_ALIGNS: [&str; 6] = ["left", "center", "right", "justify", "start", "end"];
+const FONT_WEIGHTS: [&str; 9] = ["thin", "extralight", "light", "normal", "medium", "semibold", "bold", "extrabold", "black"];
+const SIZES: [&str; 8] = ["none", "sm", "md", "lg", "xl", "2xl", "3xl", "full"];
+const DISPLAYS: [&str; 12] = [
+    "block", "inline-block", "inline", "flex", "inline-flex", "grid", "inline-grid",
+    "hidden", "contents", "table", "flow-root", "list-item",
+];
+const POSITIONS: [&str; 5] = ["static", "fixed", "absolute", "relative", "sticky"];
+
+/// Utilities whose group is the prefix before the value (`gap-x-4` -> `gap-x`),
+/// longest prefixes first.
+const PREFIX_GROUPS: [&str; 43] = [
+    "min-w", "max-w", "min-h", "max-h", "gap-x", "gap-y", "inset-x", "inset-y",
+    "grid-cols", "grid-rows", "col-span", "row-span", "overflow-x", "overflow-y",
+    "px", "py", "pt", "pr", "pb", "pl", "ps", "pe", "p",
+    "mx", "my", "mt", "mr", "mb", "ml", "ms", "me", "m",
+    "w", "h", "size", "gap", "inset", "top", "right", "bottom", "left", "z", "opacity",
+];
+
+/// Returns the conflict group of a utility, or `None` when it is not recognised.
+fn utility_group(utility: &str) -> Option<&'static str> {
+    if DISPLAYS.contains(&utility) {
+        return Some("display");
+    }
+    if POSITIONS.contains(&utility) {
+        return Some("position");
+    }
+    if let Some(value) = utility.strip_prefix("text-") {
+        return Some(if FONT_SIZES.contains(&value) {
+            "font-size"
+        } else if TEXT_ALIGNS.contains(&value) {
+            "text-align"
+        } else {
+            "text-color"
+        });
+    }
+    if let Some(value) = utility.strip_prefix("font-") {
+        return Some(if FONT_WEIGHTS.contains(&value) { "font-weight" } else { "font-family" });
+    }
+    if utility == "rounded" || utility.strip_prefix("rounded-").is_some_and(|v| SIZES.contains(&v)) {
+        return Some("rounded");
+    }
+    if utility == "shadow" || utility.strip_prefix("shadow-").is_some_and(|v| SIZES.contains(&v) || v == "inner") {
+        return Some("shadow");
+    }
+    if utility == "border" || utility.strip_prefix("border-").is_some_and(|v| v.parse::<u8>().is_ok()) {
+        return Some("border-width");
+    }
+    if utility.starts_with("border-") && !utility.starts_with("border-x") && !utility.starts_with("border-y") {
+        let side = utility.trim_start_matches("border-");
+        if !["t", "r", "b", "l"].iter().any(|s| side == *s || side.starts_with(&format!("{}-", s))) {
+            return Some("border-color");
+        }
+    }
+    if utility.starts_with("bg-") {
+        return Some("bg-color");
+    }
+    if ["flex-row", "flex-row-reverse", "flex-col", "flex-col-reverse"].contains(&utility) {
+        return Some("flex-direction");
+    }
+    for (prefix, group) in [
+        ("justify-", "justify-content"),
+        ("items-", "align-items"),
+        ("leading-", "line-height"),
+        ("tracking-", "letter-spacing"),
+        ("cursor-", "cursor"),
+    ] {
+        if utility.starts_with(prefix) {
+            return Some(group);
+        }
+    }
+    if let Some(group) = ["overflow-x", "overflow-y"].into_iter().find(|g| utility.starts_with(&format!("{}-", g))) {
+        return Some(group);
+    }
+    if utility.starts_with("overflow-") {
+        return Some("overflow");
+    }
+    PREFIX_GROUPS.into_iter().find(|p| {
+        utility.strip_prefix(p).is_some_and(|rest| rest.starts_with('-'))
+    })
+}
+
+/// Groups that a (shorthand) group overrides when it comes later.
+fn overridden_groups(group: &str) -> &'static [&'static str] {
+    match group {
+        "p" => &["px", "py", "pt", "pr", "pb", "pl", "ps", "pe"],
+        "px" => &["pr", "pl", "ps", "pe"],
+        "py" => &["pt", "pb"],
+        "m" => &["mx", "my", "mt", "mr", "mb", "ml", "ms", "me"],
+        "mx" => &["mr", "ml", "ms", "me"],
+        "my" => &["mt", "mb"],
+        "gap" => &["gap-x", "gap-y"],
+        "inset" => &["inset-x", "inset-y", "top", "right", "bottom", "left"],
+        "inset-x" => &["right", "left"],
+        "inset-y" => &["top", "bottom"],
+        "size" => &["w", "h"],
+        "overflow" => &["overflow-x", "overflow-y"],
+        _ => &[],
+    }
+}
@@ -0,0 +1,336 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use crate::theme::Theme;
+
+/// Per-thread state shared between components during a render.
+///
+/// Components register data here while they are rendered, and the
+/// [`Document`](crate::Document) renderer consumes it when assembling the
+/// page. Rendering is synchronous, so one thread renders one page at a time;
+/// call [`clear`] before starting a page if a previous render may have been
+/// abandoned halfway.
+#[derive(Debug, Default)]
+struct Context {
+    title: Option<String>,
+    head: Vec<String>,
+    styles: Vec<(String, String)>,
+    ids: IdGen,
+    theme: Option<Theme>,
+    color_theme: Option<String>,
+    consent: Vec<String>,
+    #[cfg(feature = "i18n")]
+    locale: Option<String>,
+}
+
+thread_local! {
+    static CONTEXT: RefCell<Context> = RefCell::new(Context::default());
+}
+
+/// Head items hoisted from components, as taken by [`take_head`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Head {
+    /// Page-specific title; the last call to [`set_title`] wins.
+    pub title: Option<String>,
+    /// Rendered head elements in registration order, without duplicates.
+    pub items: Vec<String>,
+    /// Stylesheets registered with [`add_style`], in registration order.
+    pub styles: Vec<String>,
+}
+
+/// Registers a rendered element (e.g. a `meta` or `link` tag) for `<head>`.
+///
+/// Identical items are only kept once, so every instance of a component can
+/// register what it needs.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::{rsx, context, Html};
+///
+/// fn video_card(src: &str) -> Html {
+///     context::add_head(rsx!(lined, link { rel: "preload", href: "/player.js", as: "script" }));
+///     rsx!(lined, video { src: src })
+/// }
+///
+/// context::clear();
+/// let cards = format!("{}{}", video_card("/a.mp4"), video_card("/b.mp4"));
+/// context::set_title("Videos");
+///
+/// let head = context::take_head();
+/// assert_eq!(head.title.as_deref(), Some("Videos"));
+/// assert_eq!(head.items, vec![r#"<link rel="preload" href="/player.js" as="script">"#]);
+/// assert!(context::take_head().items.is_empty());
+/// # let _ = cards;
+/// ```
+pub fn add_head(item: impl std::fmt::Display) {
+    let item = item.to_string();
+    CONTEXT.with(|c| {
+        let mut c = c.borrow_mut();
+        if !c.head.contains(&item) {
+            c.head.push(item);
+        }
+    });
+}
+
+/// Registers a stylesheet under `key`, to be emitted in the document's single
+/// `<style>` element. Later registrations with the same key are ignored.
+pub fn add_style(key: &str, css: &str) {
+    CONTEXT.with(|c| {
+        let mut c = c.borrow_mut();
+        if !c.styles.iter().any(|(k, _)| k == key) {
+            c.styles.push((key.to_string(), css.to_string()));
+        }
+    });
+}
+
+/// Sets a page-specific title, overriding the one given to the document.
+pub fn set_title(title: impl Into<String>) {
+    let title = title.into();
+    CONTEXT.with(|c| c.borrow_mut().title = Some(title));
+}
+
+/// Takes the hoisted head items, leaving them empty for the next page.
+///
+/// This ends the render: consents granted with [`grant_consent`] are
+/// withdrawn too, the color theme set with [`set_color_theme`] is removed and
+/// the [`unique_id`] generator starts over, so none of them carries over to
+/// the next page rendered on the same thread.
+pub fn take_head() -> Head {
+    CONTEXT.with(|c| {
+        let mut c = c.borrow_mut();
+        c.consent.clear();
+        c.ids = IdGen::default();
+        c.color_theme = None;
+        Head {
+            title: c.title.take(),
+            items: std::mem::take(&mut c.head),
+            styles: std::mem::take(&mut c.styles).into_iter().map(|(_, css)| css).collect(),
+        }
+    })
+}
+
+/// Head items registered while running a closure, see [`record`].
+#[cfg(feature = "macros")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Recorded {
+    title: Option<String>,
+    head: Vec<String>,
+    styles: Vec<(String, String)>,
+}
+
+/// Runs `f` and returns what it registered with [`set_title`], [`add_head`]
+/// and [`add_style`], leaving those registrations in place.
+#[cfg(feature = "macros")]
+pub(crate) fn record<R>(f: impl FnOnce() -> R) -> (R, Recorded) {
+    let saved = CONTEXT.with(|c| {
+        let mut c = c.borrow_mut();
+        Recorded {
+            title: c.title.take(),
+            head: std::mem::take(&mut c.head),
+            styles: std::mem::take(&mut c.styles),
+        }
+    });
+    let result = f();
+    let recorded = CONTEXT.with(|c| {
+        let mut c = c.borrow_mut();
+        Recorded {
+            title: std::mem::replace(&mut c.title, saved.title),
+            head: std::mem::replace(&mut c.head, saved.head),
+            styles: std::mem::replace(&mut c.styles, saved.styles),
+        }
+    });
+    replay(&recorded);
+    (result, recorded)
+}
+
+/// Registers the items of a [`record`] again, e.g. for a cached component.
+#[cfg(feature = "macros")]
+pub(crate) fn replay(recorded: &Recorded) {
+    if let Some(title) = &recorded.title {
+        set_title(title.clone());
+    }
+    for item in &recorded.head {
+        add_head(item);
+    }
+    for (key, css) in &recorded.styles {
+        add_style(key, css);
+    }
+}
+
+/// A generator of unique, reproducible element ids, for wiring
+/// `label[for]`/`input[id]` or `aria-controls` pairs inside components that
+/// appear several times on a page.
+///
+/// Ids are `<seed>-<base>-<n>`, numbered per base in the order they are
+/// requested, so rendering the same page twice gives the same ids. Components
+/// usually go through [`unique_id`], which uses the generator of the current
+/// render; [`take_head`] resets it when the page is done.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::context::IdGen;
+///
+/// let mut ids = IdGen::new("f");
+/// assert_eq!(ids.next("email"), "f-email-1");
+/// assert_eq!(ids.next("email"), "f-email-2");
+/// assert_eq!(ids.next("name"), "f-name-1");
+/// assert_eq!(IdGen::default().next("email"), "email-1");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IdGen {
+    seed: String,
+    counts: HashMap<String, u32>,
+}
+
+impl IdGen {
+    /// Creates a generator whose ids start with `seed` (no prefix when empty).
+    pub fn new(seed: impl Into<String>) -> Self {
+        IdGen { seed: seed.into(), counts: HashMap::new() }
+    }
+
+    /// Returns the next id for `base`.
+    pub fn next(&mut self, base: &str) -> String {
+        let n = self.counts.entry(base.to_string()).or_insert(0);
+        *n += 1;
+        if self.seed.is_empty() {
+            format!("{}-{}", base, n)
+        } else {
+            format!("{}-{}-{}", self.seed, base, n)
+        }
+    }
+}
+
+/// Starts a fresh [`IdGen`] with `seed` for the current render, e.g. a
+/// per-page or per-fragment prefix that keeps ids apart when separately
+/// rendered fragments end up on the same page. [`take_head`] and [`clear`]
+/// reset it to an unseeded generator.
+pub fn set_id_seed(seed: impl Into<String>) {
+    let ids = IdGen::new(seed);
+    CONTEXT.with(|c| c.borrow_mut().ids = ids);
+}
+
+/// Returns the next id for `base` from the current render's [`IdGen`].
+///
+/// ```rust
+/// use forge_rsx::{rsx, context, Html};
+///
+/// fn field(label: &str, kind: &str) -> Html {
+///     let id = context::unique_id(kind);
+///     rsx!(lined, div {
+///         label { for: &id, {label} }
+///         input { id: &id, type: kind }
+///     })
+/// }
+///
+/// context::clear();
+/// let form = format!("{}{}", field("Work", "email"), field("Home", "email"));
+/// assert_eq!(form, concat!(
+///     r#"<div><label for="email-1">Work</label><input id="email-1" type="email"></div>"#,
+///     r#"<div><label for="email-2">Home</label><input id="email-2" type="email"></div>"#,
+/// ));
+///
+/// // The next page starts numbering again.
+/// let _ = context::take_head();
+/// assert_eq!(format!("{}{}", field("Work", "email"), field("Home", "email")), form);
+/// ```
+pub fn unique_id(base: &str) -> String {
+    CONTEXT.with(|c| c.borrow_mut().ids.next(base))
+}
+
+/// Sets the design-system [`Theme`] used by [`theme::theme`](crate::theme::theme)
+/// and [`theme::theme_attr`](crate::theme::theme_attr) on the current thread,
+/// e.g. per tenant or per request. Themes are cheap to clone. [`clear`]
+/// removes it.
+pub fn set_theme(theme: Theme) {
+    CONTEXT.with(|c| c.borrow_mut().theme = Some(theme));
+}
+
+/// Calls `f` with the current theme, if one is set.
+pub(crate) fn with_theme<R>(f: impl FnOnce(Option<&Theme>) -> R) -> R {
+    CONTEXT.with(|c| f(c.borrow().theme.as_ref()))
+}
+
+/// Sets the locale of the current thread, see
+/// [`i18n::set_locale`](crate::i18n::set_locale).
+#[cfg(feature = "i18n")]
+pub(crate) fn set_locale(locale: String) {
+    CONTEXT.with(|c| c.borrow_mut().locale = Some(locale));
+}
+
+/// Returns the locale set with [`set_locale`].
+#[cfg(feature = "i18n")]
+pub(crate) fn locale() -> Option<String> {
+    CONTEXT.with(|c| c.borrow().locale.clone())
+}
+
+/// Sets the color theme (e.g. `dark`) of the current render, overriding the
+/// one given to the [`Document`](crate::Document) (see
+/// [`ColorTheme`](crate::theme::ColorTheme)). It applies until the page is
+/// rendered: [`take_head`] and [`clear`] remove it.
+pub fn set_color_theme(name: impl Into<String>) {
+    let name = name.into();
+    CONTEXT.with(|c| c.borrow_mut().color_theme = Some(name));
+}
+
+/// Returns the color theme set with [`set_color_theme`].
+pub(crate) fn color_theme() -> Option<String> {
+    CONTEXT.with(|c| c.borrow().color_theme.clone())
+}
+
+/// Records that the visitor consented to the cookie/tracking `category`
+/// (e.g. `"analytics"`) for the current render, as read by
+/// [`scripts::consent_gate`](crate::scripts::consent_gate).
+///
+/// Consent only lasts until the end of the render: [`take_head`] (called
+/// when a [`Document`](crate::Document) is rendered) and [`clear`] withdraw
+/// every consent, so a visitor's choice never leaks into another visitor's
+/// page rendered later on the same thread.
+///
+/// ```rust
+/// use forge_rsx::context;
+///
+/// context::grant_consent("analytics");
+/// assert!(context::has_consent("analytics"));
+///
+/// let _ = context::take_head(); // the page is rendered
+/// assert!(!context::has_consent("analytics"));
+/// ```
+pub fn grant_consent(category: &str) {
+    CONTEXT.with(|c| {
+        let mut c = c.borrow_mut();
+        if !c.consent.iter().any(|k| k == category) {
+            c.consent.push(category.to_string());
+        }
+    });
+}
+
+/// Whether consent was granted for `category` with [`grant_consent`].
+pub fn has_consent(category: &str) -> bool {
+    CONTEXT.with(|c| c.borrow().consent.iter().any(|k| k == category))
+}
+
+/// Discards the per-page state of the current thread, everything
+/// [`take_head`] resets, while keeping the theme set with [`set_theme`] and
+/// the locale. Called between the pages of a batch render.
+///
+/// ```rust,standalone_crate
+/// use forge_rsx::context::{self, set_theme, set_title, unique_id};
+/// use forge_rsx::theme::Theme;
+///
+/// set_theme(Theme::new().class("btn", "btn-x"));
+/// set_title("Page 1");
+/// unique_id("tab");
+/// context::reset_page();
+/// assert!(context::take_head().title.is_none());
+/// assert_eq!(unique_id("tab"), "tab-1");
+/// assert_eq!(forge_rsx::theme::theme("btn"), "btn-x");
+/// ```
+pub fn reset_page() {
+    take_head();
+}
+
+/// Discards everything registered on the current thread, including the
+/// theme and locale; see [`reset_page`] to keep them.
+pub fn clear() {
+    CONTEXT.with(|c| *c.borrow_mut() = Context::default());
+}
@@ -0,0 +1,613 @@
+use std::io::{self, BufRead};
+
+use crate::error::Result;
+
+use crate::filters::escape;
+
+/// How [`table_from_csv`] decides whether the first row is a header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CsvHeader {
+    /// The first row is a header when none of its fields is empty or numeric.
+    #[default]
+    Auto,
+    /// The first row is a header.
+    Yes,
+    /// Every row is data.
+    No,
+}
+
+/// Options of [`table_from_csv`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvOptions {
+    /// The field delimiter, `,` by default.
+    pub delimiter: char,
+    /// Header detection.
+    pub header: CsvHeader,
+    /// The maximum number of data rows; reading stops there.
+    pub limit: Option<usize>,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions { delimiter: ',', header: CsvHeader::Auto, limit: None }
+    }
+}
+
+impl CsvOptions {
+    /// Options for tab-separated values.
+    pub fn tsv() -> Self {
+        CsvOptions { delimiter: '\t', ..Self::default() }
+    }
+}
+
+/// Streams delimited data (CSV, TSV, ..) into a rendered `table`.
+///
+/// Fields follow RFC 4180: they may be quoted with `"`, quoted fields can
+/// contain delimiters and line breaks, and `""` stands for a quote. Values
+/// are escaped. Header cells become `th scope="col"` in a `thead`, data rows
+/// go to a `tbody`. Rows are read one at a time, so with a
+/// [`limit`](CsvOptions::limit) only the needed part of the input is read.
+/// Read failures (including invalid UTF-8) are returned as
+/// [`Error::Io`](crate::Error::Io).
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::convert::{table_from_csv, CsvOptions};
+///
+/// let csv = "name,role\nAnn,\"Admin, <root>\"\nBob,User\nCid,User\n";
+/// let options = CsvOptions { limit: Some(2), ..CsvOptions::default() };
+/// assert_eq!(table_from_csv(csv.as_bytes(), &options).unwrap(), concat!(
+///     r#"<table><thead><tr><th scope="col">name</th><th scope="col">role</th></tr></thead>"#,
+///     "<tbody><tr><td>Ann</td><td>Admin, &lt;root&gt;</td></tr><tr><td>Bob</td><td>User</td></tr></tbody></table>",
+/// ));
+///
+/// // A numeric first row is data.
+/// let tsv = "1\t2\n3\t4";
+/// assert_eq!(
+///     table_from_csv(tsv.as_bytes(), &CsvOptions::tsv()).unwrap(),
+///     "<table><tbody><tr><td>1</td><td>2</td></tr><tr><td>3</td><td>4</td></tr></tbody></table>"
+/// );
+/// ```
+pub fn table_from_csv(reader: impl BufRead, options: &CsvOptions) -> Result<String> {
+    let mut records = Records { reader, delimiter: options.delimiter };
+    let mut out = String::from("<table>");
+    let mut first = records.next_record()?;
+    if let Some(row) = &first {
+        let is_header = match options.header {
+            CsvHeader::Yes => true,
+            CsvHeader::No => false,
+            CsvHeader::Auto => row.iter().all(|f| !f.trim().is_empty() && f.trim().parse::<f64>().is_err()),
+        };
+        if is_header {
+            out.push_str("<thead>");
+            push_row(&mut out, row, r#"th scope="col""#, "th");
+            out.push_str("</thead>");
+            first = None;
+        }
+    }
+
+    let mut rows = 0;
+    let mut next = match first {
+        Some(row) => Some(row),
+        None => records.next_record()?,
+    };
+    if next.is_some() && options.limit != Some(0) {
+        out.push_str("<tbody>");
+        while let Some(row) = next {
+            push_row(&mut out, &row, "td", "td");
+            rows += 1;
+            if options.limit.is_some_and(|limit| rows >= limit) {
+                break;
+            }
+            next = records.next_record()?;
+        }
+        out.push_str("</tbody>");
+    }
+    out.push_str("</table>");
+    Ok(out)
+}
+
+fn push_row(out: &mut String, fields: &[String], open: &str, close: &str) {
+    out.push_str("<tr>");
+    for field in fields {
+        out.push('<');
+        out.push_str(open);
+        out.push('>');
+        out.push_str(&escape(field));
+        out.push_str("</");
+        out.push_str(close);
+        out.push('>');
+    }
+    out.push_str("</tr>");
+}
+
+/// Reads delimited records line by line.
+struct Records<R> {
+    reader: R,
+    delimiter: char,
+}
+
+impl<R: BufRead> Records<R> {
+    /// Returns the next record, reading more lines while a quoted field is
+    /// open. Blank lines are skipped.
+    fn next_record(&mut self) -> io::Result<Option<Vec<String>>> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            if !line.trim_end_matches(['\r', '\n']).is_empty() {
+                break;
+            }
+        }
+
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut quoted = false;
+        loop {
+            let mut chars = line.chars().peekable();
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' if quoted && chars.peek() == Some(&'"') => {
+                        chars.next();
+                        field.push('"');
+                    }
+                    '"' if quoted => quoted = false,
+                    '"' if field.is_empty() => quoted = true,
+                    c if c == self.delimiter && !quoted => fields.push(std::mem::take(&mut field)),
+                    '\r' | '\n' if !quoted => {}
+                    c => field.push(c),
+                }
+            }
+            if !quoted {
+                break;
+            }
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 {
+                break;
+            }
+        }
+        fields.push(field);
+        Ok(Some(fields))
+    }
+}
+
+/// Renders a JSON value as a collapsible tree, for debug and API-explorer
+/// pages.
+///
+/// Objects and arrays become `details` elements (open) whose `summary`
+/// shows their size, holding a `ul` with one `li` per member; scalars become
+/// `span`s classed `json-string`, `json-number`, `json-bool` or `json-null`.
+/// Keys (in the map's order) and strings are escaped. Empty objects and
+/// arrays are rendered inline.
+///
+/// Requires the `json` feature.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::convert::render_json_value;
+///
+/// let value = serde_json::json!({ "meta": {}, "name": "<Ann>", "tags": [1, true] });
+/// assert_eq!(render_json_value(&value), concat!(
+///     r#"<details class="json-object" open><summary>{3 keys}</summary><ul>"#,
+///     r#"<li><span class="json-key">meta</span>: <span class="json-object">{}</span></li>"#,
+///     r#"<li><span class="json-key">name</span>: <span class="json-string">"&lt;Ann&gt;"</span></li>"#,
+///     r#"<li><span class="json-key">tags</span>: <details class="json-array" open><summary>[2 items]</summary><ul>"#,
+///     r#"<li><span class="json-number">1</span></li><li><span class="json-bool">true</span></li></ul></details></li></ul></details>"#,
+/// ));
+/// ```
+#[cfg(feature = "json")]
+pub fn render_json_value(value: &serde_json::Value) -> String {
+    let mut out = String::new();
+    push_json(&mut out, value);
+    out
+}
+
+#[cfg(feature = "json")]
+fn push_json(out: &mut String, value: &serde_json::Value) {
+    use serde_json::Value;
+
+    let plural = |n: usize, noun: &str| format!("{} {}{}", n, noun, if n == 1 { "" } else { "s" });
+    match value {
+        Value::Null => out.push_str(r#"<span class="json-null">null</span>"#),
+        Value::Bool(b) => out.push_str(&format!(r#"<span class="json-bool">{}</span>"#, b)),
+        Value::Number(n) => out.push_str(&format!(r#"<span class="json-number">{}</span>"#, n)),
+        Value::String(s) => out.push_str(&format!(r#"<span class="json-string">"{}"</span>"#, escape(s))),
+        Value::Array(items) if items.is_empty() => out.push_str(r#"<span class="json-array">[]</span>"#),
+        Value::Object(map) if map.is_empty() => out.push_str(r#"<span class="json-object">{}</span>"#),
+        Value::Array(items) => {
+            out.push_str(&format!(r#"<details class="json-array" open><summary>[{}]</summary><ul>"#, plural(items.len(), "item")));
+            for item in items {
+                out.push_str("<li>");
+                push_json(out, item);
+                out.push_str("</li>");
+            }
+            out.push_str("</ul></details>");
+        }
+        Value::Object(map) => {
+            out.push_str(&format!(r#"<details class="json-object" open><summary>{{{}}}</summary><ul>"#, plural(map.len(), "key")));
+            for (key, item) in map {
+                out.push_str(&format!(r#"<li><span class="json-key">{}</span>: "#, escape(key)));
+                push_json(out, item);
+                out.push_str("</li>");
+            }
+            out.push_str("</ul></details>");
+        }
+    }
+}
+
+/// The layout of [`render_diff`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DiffLayout {
+    /// One column of lines, removed lines before added ones.
+    #[default]
+    Unified,
+    /// Old and new lines next to each other.
+    SideBySide,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DiffOp {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Computes a line diff from the longest common subsequence.
+///
+/// The subsequence is found with Hirschberg's divide and conquer, which
+/// keeps two rows of lengths instead of the whole table, so memory stays
+/// linear in the input even for large unrelated texts.
+fn diff_lines(old: &[&str], new: &[&str]) -> Vec<DiffOp> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..].iter().rev().zip(new[prefix..].iter().rev()).take_while(|(a, b)| a == b).count();
+    let (a, b) = (&old[prefix..old.len() - suffix], &new[prefix..new.len() - suffix]);
+
+    let mut ops: Vec<DiffOp> = (0..prefix).map(|i| DiffOp::Equal(i, i)).collect();
+    diff_middle(a, b, prefix, prefix, &mut ops);
+    ops.extend((0..suffix).map(|k| DiffOp::Equal(old.len() - suffix + k, new.len() - suffix + k)));
+    ops
+}
+
+/// Appends the diff of `a` and `b`, whose first lines are `a_start` and
+/// `b_start` in the full texts.
+fn diff_middle(a: &[&str], b: &[&str], a_start: usize, b_start: usize, ops: &mut Vec<DiffOp>) {
+    if a.is_empty() || b.is_empty() {
+        ops.extend((0..a.len()).map(|i| DiffOp::Delete(a_start + i)));
+        ops.extend((0..b.len()).map(|j| DiffOp::Insert(b_start + j)));
+        return;
+    }
+    if a.len() == 1 {
+        match b.iter().position(|line| *line == a[0]) {
+            Some(j) => {
+                ops.extend((0..j).map(|k| DiffOp::Insert(b_start + k)));
+                ops.push(DiffOp::Equal(a_start, b_start + j));
+                ops.extend((j + 1..b.len()).map(|k| DiffOp::Insert(b_start + k)));
+            }
+            None => {
+                ops.push(DiffOp::Delete(a_start));
+                ops.extend((0..b.len()).map(|k| DiffOp::Insert(b_start + k)));
+            }
+        }
+        return;
+    }
+    // Split `a` in half and `b` where the LCS lengths of the two halves add
+    // up to the most.
+    let mid = a.len() / 2;
+    let head = lcs_lengths(a[..mid].iter(), b.iter());
+    let tail = lcs_lengths(a[mid..].iter().rev(), b.iter().rev());
+    let split = (0..=b.len()).max_by_key(|&j| (head[j] + tail[b.len() - j], std::cmp::Reverse(j))).unwrap_or(0);
+    diff_middle(&a[..mid], &b[..split], a_start, b_start, ops);
+    diff_middle(&a[mid..], &b[split..], a_start + mid, b_start + split, ops);
+}
+
+/// Returns, for every prefix length `j` of `b`, the length of the LCS of
+/// `a` and `b[..j]`.
+fn lcs_lengths<'s>(
+    a: impl Iterator<Item = &'s &'s str>,
+    b: impl Iterator<Item = &'s &'s str> + Clone,
+) -> Vec<usize> {
+    let mut row = vec![0; b.clone().count() + 1];
+    let mut prev = row.clone();
+    for x in a {
+        std::mem::swap(&mut row, &mut prev);
+        for (j, y) in b.clone().enumerate() {
+            row[j + 1] = if x == y { prev[j] + 1 } else { prev[j + 1].max(row[j]) };
+        }
+    }
+    row
+}
+
+/// Renders a line diff of two texts as a `table`, for audit logs and
+/// review pages.
+///
+/// Rows are classed `diff-equal`, `diff-delete`, `diff-insert` (or
+/// `diff-change` for paired lines side by side); removed lines are wrapped
+/// in `del`, added ones in `ins`, and line numbers sit in `td.diff-line`
+/// cells. Text is escaped.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::convert::{render_diff, DiffLayout};
+///
+/// let old = "a\nb\nc";
+/// let new = "a\nB\nc";
+/// assert_eq!(render_diff(old, new, DiffLayout::Unified), concat!(
+///     r#"<table class="diff diff-unified"><tbody>"#,
+///     r#"<tr class="diff-equal"><td class="diff-line">1</td><td class="diff-line">1</td><td>a</td></tr>"#,
+///     r#"<tr class="diff-delete"><td class="diff-line">2</td><td class="diff-line"></td><td><del>b</del></td></tr>"#,
+///     r#"<tr class="diff-insert"><td class="diff-line"></td><td class="diff-line">2</td><td><ins>B</ins></td></tr>"#,
+///     r#"<tr class="diff-equal"><td class="diff-line">3</td><td class="diff-line">3</td><td>c</td></tr>"#,
+///     "</tbody></table>",
+/// ));
+///
+/// let html = render_diff(old, new, DiffLayout::SideBySide);
+/// assert!(html.contains(concat!(
+///     r#"<tr class="diff-change"><td class="diff-line">2</td><td><del>b</del></td>"#,
+///     r#"<td class="diff-line">2</td><td><ins>B</ins></td></tr>"#,
+/// )));
+/// ```
+pub fn render_diff(old: &str, new: &str, layout: DiffLayout) -> String {
+    let (old, new): (Vec<&str>, Vec<&str>) = (old.lines().collect(), new.lines().collect());
+    let ops = diff_lines(&old, &new);
+    let number = |n: Option<usize>| n.map_or_else(String::new, |n| (n + 1).to_string());
+    let row = |class: &str, cells: &[(Option<usize>, String)]| {
+        let mut out = format!(r#"<tr class="{}">"#, class);
+        for (n, text) in cells {
+            out.push_str(&format!(r#"<td class="diff-line">{}</td><td>{}</td>"#, number(*n), text));
+        }
+        out.push_str("</tr>");
+        out
+    };
+    let equal = |line: &str| escape(line);
+    let del = |line: &str| format!("<del>{}</del>", escape(line));
+    let ins = |line: &str| format!("<ins>{}</ins>", escape(line));
+
+    let mut rows = String::new();
+    match layout {
+        DiffLayout::Unified => {
+            for op in &ops {
+                let (class, a, b, text) = match *op {
+                    DiffOp::Equal(i, j) => ("diff-equal", Some(i), Some(j), equal(old[i])),
+                    DiffOp::Delete(i) => ("diff-delete", Some(i), None, del(old[i])),
+                    DiffOp::Insert(j) => ("diff-insert", None, Some(j), ins(new[j])),
+                };
+                rows.push_str(&format!(
+                    r#"<tr class="{}"><td class="diff-line">{}</td><td class="diff-line">{}</td><td>{}</td></tr>"#,
+                    class,
+                    number(a),
+                    number(b),
+                    text
+                ));
+            }
+        }
+        DiffLayout::SideBySide => {
+            let mut k = 0;
+            while k < ops.len() {
+                if let DiffOp::Equal(i, j) = ops[k] {
+                    rows.push_str(&row("diff-equal", &[(Some(i), equal(old[i])), (Some(j), equal(new[j]))]));
+                    k += 1;
+                    continue;
+                }
+                // Pair the deletions and insertions of a change block.
+                let end = ops[k..].iter().position(|op| matches!(op, DiffOp::Equal(..))).map_or(ops.len(), |p| k + p);
+                let (mut deleted, mut inserted) = (Vec::new(), Vec::new());
+                for op in &ops[k..end] {
+                    match *op {
+                        DiffOp::Delete(i) => deleted.push(i),
+                        DiffOp::Insert(j) => inserted.push(j),
+                        DiffOp::Equal(..) => {}
+                    }
+                }
+                for n in 0..deleted.len().max(inserted.len()) {
+                    let left = deleted.get(n).map_or((None, String::new()), |&i| (Some(i), del(old[i])));
+                    let right = inserted.get(n).map_or((None, String::new()), |&j| (Some(j), ins(new[j])));
+                    let class = match (left.0, right.0) {
+                        (Some(_), Some(_)) => "diff-change",
+                        (Some(_), None) => "diff-delete",
+                        _ => "diff-insert",
+                    };
+                    rows.push_str(&row(class, &[left, right]));
+                }
+                k = end;
+            }
+        }
+    }
+    let class = match layout {
+        DiffLayout::Unified => "diff diff-unified",
+        DiffLayout::SideBySide => "diff diff-side-by-side",
+    };
+    format!(r#"<table class="{}"><tbody>{}</tbody></table>"#, class, rows)
+}
+
+const ANSI_COLORS: [&str; 8] = ["black", "red", "green", "yellow", "blue", "magenta", "cyan", "white"];
+
+/// A color set by an SGR sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AnsiColor {
+    /// One of the 16 basic colors (8 and up are the bright ones) or an index
+    /// of the 256-color palette.
+    Indexed(u8),
+    Rgb(u8, u8, u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct AnsiStyle {
+    bold: bool,
+    dim: bool,
+    italic: bool,
+    underline: bool,
+    fg: Option<AnsiColor>,
+    bg: Option<AnsiColor>,
+}
+
+impl AnsiStyle {
+    /// Applies the parameters of an SGR (`ESC [ .. m`) sequence; `None`
+    /// stands for a parameter that isn't a number that fits, and is ignored.
+    fn apply(&mut self, params: &[Option<u16>]) {
+        let mut params = params.iter().copied();
+        while let Some(code) = params.next() {
+            let Some(code) = code else { continue };
+            match code {
+                0 => *self = AnsiStyle::default(),
+                1 => self.bold = true,
+                2 => self.dim = true,
+                3 => self.italic = true,
+                4 => self.underline = true,
+                22 => (self.bold, self.dim) = (false, false),
+                23 => self.italic = false,
+                24 => self.underline = false,
+                30..=37 => self.fg = Some(AnsiColor::Indexed(code as u8 - 30)),
+                90..=97 => self.fg = Some(AnsiColor::Indexed(code as u8 - 90 + 8)),
+                40..=47 => self.bg = Some(AnsiColor::Indexed(code as u8 - 40)),
+                100..=107 => self.bg = Some(AnsiColor::Indexed(code as u8 - 100 + 8)),
+                39 => self.fg = None,
+                49 => self.bg = None,
+                38 | 48 => {
+                    // A missing or oversized color parameter clears the color.
+                    let mut params = params.by_ref().map_while(|p| p);
+                    let color = match params.next() {
+                        Some(5) => params.next().map(|n| AnsiColor::Indexed(n.min(255) as u8)),
+                        Some(2) => match (params.next(), params.next(), params.next()) {
+                            (Some(r), Some(g), Some(b)) => Some(AnsiColor::Rgb(r.min(255) as u8, g.min(255) as u8, b.min(255) as u8)),
+                            _ => None,
+                        },
+                        _ => None,
+                    };
+                    if code == 38 {
+                        self.fg = color;
+                    } else {
+                        self.bg = color;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Renders the opening `span` tag, or nothing for the default style.
+    fn open_tag(&self) -> Option<String> {
+        let mut classes = Vec::new();
+        let mut styles = Vec::new();
+        for (on, class) in [(self.bold, "ansi-bold"), (self.dim, "ansi-dim"), (self.italic, "ansi-italic"), (self.underline, "ansi-underline")] {
+            if on {
+                classes.push(class.to_string());
+            }
+        }
+        for (color, prefix, property) in [(self.fg, "ansi-", "color"), (self.bg, "ansi-bg-", "background-color")] {
+            match color {
+                Some(AnsiColor::Indexed(n)) if n < 8 => classes.push(format!("{}{}", prefix, ANSI_COLORS[n as usize])),
+                Some(AnsiColor::Indexed(n)) if n < 16 => classes.push(format!("{}bright-{}", prefix, ANSI_COLORS[n as usize - 8])),
+                Some(AnsiColor::Indexed(n)) => classes.push(format!("{}{}", prefix, n)),
+                Some(AnsiColor::Rgb(r, g, b)) => styles.push(format!("{}:#{:02x}{:02x}{:02x}", property, r, g, b)),
+                None => {}
+            }
+        }
+        if classes.is_empty() && styles.is_empty() {
+            return None;
+        }
+        let mut tag = String::from("<span");
+        if !classes.is_empty() {
+            tag.push_str(&format!(r#" class="{}""#, classes.join(" ")));
+        }
+        if !styles.is_empty() {
+            tag.push_str(&format!(r#" style="{}""#, styles.join(";")));
+        }
+        tag.push('>');
+        Some(tag)
+    }
+}
+
+/// Converts terminal output with ANSI escape codes (e.g. a CI or build log)
+/// into a `pre class="ansi"` element.
+///
+/// SGR sequences become `span`s with classes: `ansi-bold`, `ansi-dim`,
+/// `ansi-italic`, `ansi-underline`, `ansi-<color>` and `ansi-bg-<color>` for
+/// the 8 basic colors (`ansi-bright-<color>` for their bright variants),
+/// `ansi-<n>`/`ansi-bg-<n>` for the 256-color palette, and an inline `style`
+/// for 24-bit colors. Other escape sequences (cursor movement, titles) are
+/// dropped, and the text is escaped.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::convert::ansi_to_html;
+///
+/// let log = "\x1b[1;32mPASS\x1b[0m tests <all>\n\x1b[31;4mFAIL\x1b[24m x\x1b[m\x1b[2K";
+/// assert_eq!(
+///     ansi_to_html(log),
+///     concat!(
+///         r#"<pre class="ansi"><span class="ansi-bold ansi-green">PASS</span> tests &lt;all&gt;"#, "\n",
+///         r#"<span class="ansi-underline ansi-red">FAIL</span><span class="ansi-red"> x</span></pre>"#,
+///     )
+/// );
+///
+/// // Parameters too large to be a code are ignored, not read as a reset.
+/// assert_eq!(ansi_to_html("\x1b[1mA\x1b[99999mB"), r#"<pre class="ansi"><span class="ansi-bold">AB</span></pre>"#);
+/// ```
+pub fn ansi_to_html(text: &str) -> String {
+    let mut out = String::from(r#"<pre class="ansi">"#);
+    let mut style = AnsiStyle::default();
+    let mut run = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            run.push(c);
+            continue;
+        }
+        match chars.next() {
+            // CSI: parameters, then a final byte in @..~.
+            Some('[') => {
+                let mut params = String::new();
+                let mut last = None;
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        last = Some(c);
+                        break;
+                    }
+                    params.push(c);
+                }
+                if last == Some('m') {
+                    // An empty parameter means 0, as in `ESC [ m`.
+                    let codes: Vec<Option<u16>> =
+                        params.split(';').map(|p| if p.is_empty() { Some(0) } else { p.parse().ok() }).collect();
+                    let mut next = style;
+                    next.apply(&codes);
+                    if next != style {
+                        push_ansi_run(&mut out, &mut run, &style);
+                        style = next;
+                    }
+                }
+            }
+            // OSC: up to BEL or ST (ESC \).
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' || c == '\x1b' && chars.next_if_eq(&'\\').is_some() {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    push_ansi_run(&mut out, &mut run, &style);
+    out.push_str("</pre>");
+    out
+}
+
+/// Appends the pending text in `style` and clears it.
+fn push_ansi_run(out: &mut String, run: &mut String, style: &AnsiStyle) {
+    if run.is_empty() {
+        return;
+    }
+    match style.open_tag() {
+        Some(tag) => {
+            out.push_str(&tag);
+            out.push_str(&escape(run));
+            out.push_str("</span>");
+        }
+        None => out.push_str(&escape(run)),
+    }
+    run.clear();
+}
@@ -0,0 +1,360 @@
+use crate::css::{split_rules, NESTING_AT_RULES};
+use crate::node::{Element, Node};
+use crate::renderer::append_to_head;
+
+/// Extracts the rules of `stylesheet` that apply to the elements of the
+/// rendered `html`, for inlining as critical CSS.
+///
+/// A style rule is kept, whole, when any selector of its list matches an
+/// element of the document; rules inside `@media`, `@supports` and similar
+/// blocks are filtered the same way, and empty blocks are dropped. Other
+/// at-rules (`@font-face`, `@keyframes`, `@import`, ..) are left to the full
+/// stylesheet. Rules are returned in stylesheet order, one per line.
+///
+/// Matching supports type, universal, class, id and attribute selectors with
+/// descendant, child and sibling combinators. Pseudo-classes and
+/// pseudo-elements are ignored, so `a:hover` is kept whenever the page has a
+/// link: keeping a rule too many is cheaper than a flash of unstyled content.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::rsx;
+/// use forge_rsx::critical::critical_css;
+///
+/// let page = rsx!(lined, main { h1 { class: "title", "Hi" } ul { li { a { href: "/", "Home" } } } });
+/// let css = "body { margin: 0; }
+///     .title { font-size: 2rem; }
+///     .footer { color: gray; }
+///     ul > li a:hover, ol li { color: red; }
+///     @media (min-width: 40em) { .title { font-size: 3rem; } .footer { display: flex; } }
+///     @font-face { font-family: Inter; src: url(inter.woff2); }";
+///
+/// assert_eq!(
+///     critical_css(&page, css),
+///     ".title { font-size: 2rem; }\nul > li a:hover, ol li { color: red; }\n@media (min-width: 40em) { .title { font-size: 3rem; } }"
+/// );
+/// ```
+pub fn critical_css(html: &str, stylesheet: &str) -> String {
+    let tree = Node::parse(html);
+    let mut elements = Vec::new();
+    flatten(&tree, None, &mut elements);
+    filter_rules(stylesheet, &elements).join("\n")
+}
+
+/// Inlines the critical CSS of `stylesheet` (see [`critical_css`]) into the
+/// rendered page and loads the full stylesheet, served at `href`, without
+/// blocking rendering.
+///
+/// A `<style>` element with the critical rules is added at the end of
+/// `<head>` (or before the markup when it has no `head`), followed by a
+/// `<link rel="preload">` that applies the stylesheet once loaded and a
+/// `<noscript>` fallback linking it normally. The preload link uses an inline
+/// `onload` handler, which a strict Content Security Policy must allow.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::rsx;
+/// use forge_rsx::critical::inline_critical;
+///
+/// let page = rsx!(lined, html {
+///     head { title { "Home" } }
+///     body { p { class: "lead", "Welcome" } }
+/// });
+/// let css = ".lead { font-weight: bold; } .modal { display: none; }";
+///
+/// assert_eq!(
+///     inline_critical(&page, css, "/site.css"),
+///     concat!(
+///         "<html><head><title>Home</title>",
+///         "<style>.lead { font-weight: bold; }</style>",
+///         r#"<link rel="preload" href="/site.css" as="style" onload="this.onload=null;this.rel='stylesheet'">"#,
+///         r#"<noscript><link rel="stylesheet" href="/site.css"></noscript>"#,
+///         r#"</head><body><p class="lead">Welcome</p></body></html>"#
+///     )
+/// );
+/// ```
+pub fn inline_critical(html: &str, stylesheet: &str, href: &str) -> String {
+    let mut tree = Node::parse(html);
+    let mut elements = Vec::new();
+    flatten(&tree, None, &mut elements);
+    let critical = filter_rules(stylesheet, &elements).join("\n");
+
+    let mut items = Vec::new();
+    if !critical.is_empty() {
+        items.push(Node::from(Element::new("style").child(Node::text(critical))));
+    }
+    items.push(
+        Element::new("link")
+            .attr("rel", "preload")
+            .attr("href", href)
+            .attr("as", "style")
+            .attr("onload", "this.onload=null;this.rel='stylesheet'")
+            .into(),
+    );
+    items.push(Element::new("noscript").child(Element::new("link").attr("rel", "stylesheet").attr("href", href)).into());
+
+    if !append_to_head(&mut tree, &items) {
+        items.push(tree);
+        tree = Node::Fragment(items);
+    }
+    tree.to_string()
+}
+
+/// An element of the document with the positions of its parent and previous
+/// element sibling in the flattened list.
+struct Flat<'a> {
+    el: &'a Element,
+    parent: Option<usize>,
+    prev: Option<usize>,
+}
+
+/// Lists the elements of a tree in document order; fragments are transparent.
+fn flatten<'a>(node: &'a Node, parent: Option<usize>, out: &mut Vec<Flat<'a>>) {
+    let mut prev = None;
+    flatten_children(std::slice::from_ref(node), parent, &mut prev, out);
+}
+
+fn flatten_children<'a>(nodes: &'a [Node], parent: Option<usize>, prev: &mut Option<usize>, out: &mut Vec<Flat<'a>>) {
+    for node in nodes {
+        match node {
+            Node::Element(el) => {
+                let index = out.len();
+                out.push(Flat { el, parent, prev: *prev });
+                *prev = Some(index);
+                let mut child_prev = None;
+                flatten_children(&el.children, Some(index), &mut child_prev, out);
+            }
+            Node::Fragment(children) => flatten_children(children, parent, prev, out),
+            _ => {}
+        }
+    }
+}
+
+/// Keeps the rules of `css` matching some element, recursing into nesting
+/// at-rules.
+fn filter_rules(css: &str, elements: &[Flat]) -> Vec<String> {
+    let mut kept = Vec::new();
+    for rule in split_rules(css) {
+        let Some(open) = rule.find('{') else { continue };
+        let prelude = rule[..open].trim();
+        if prelude.starts_with('@') {
+            if NESTING_AT_RULES.iter().any(|r| prelude.starts_with(r)) {
+                let body = rule[open + 1..].strip_suffix('}').unwrap_or(&rule[open + 1..]);
+                let inner = filter_rules(body, elements);
+                if !inner.is_empty() {
+                    kept.push(format!("{} {{ {} }}", prelude, inner.join(" ")));
+                }
+            }
+        } else if split_selectors(prelude).iter().any(|s| {
+            let selector = parse_selector(s);
+            (0..elements.len()).any(|i| matches_at(&selector, selector.len(), elements, i))
+        }) {
+            kept.push(rule.to_string());
+        }
+    }
+    kept
+}
+
+/// Splits a selector list on the commas outside brackets, parentheses and
+/// strings.
+fn split_selectors(list: &str) -> Vec<&str> {
+    let mut selectors = Vec::new();
+    let (mut depth, mut start, mut quote) = (0usize, 0, None);
+    for (i, c) in list.char_indices() {
+        match (quote, c) {
+            (Some(q), _) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(' | '[') => depth += 1,
+            (None, ')' | ']') => depth = depth.saturating_sub(1),
+            (None, ',') if depth == 0 => {
+                selectors.push(list[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    selectors.push(list[start..].trim());
+    selectors.retain(|s| !s.is_empty());
+    selectors
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Combinator {
+    Descendant,
+    Child,
+    Adjacent,
+    Sibling,
+}
+
+#[derive(Default)]
+struct Compound {
+    tag: Option<String>,
+    ids: Vec<String>,
+    classes: Vec<String>,
+    attrs: Vec<(String, Option<(String, String)>)>,
+}
+
+/// A complex selector: compounds in source order, each with the combinator
+/// linking it to the previous one (ignored for the first).
+type Selector = Vec<(Combinator, Compound)>;
+
+fn is_ident(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_' || c == '\\'
+}
+
+fn parse_selector(s: &str) -> Selector {
+    let mut selector: Selector = Vec::new();
+    let mut combinator = Combinator::Descendant;
+    let mut current: Option<Compound> = None;
+    let mut chars = s.chars().peekable();
+
+    let ident = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+        let mut name = String::new();
+        while let Some(&c) = chars.peek().filter(|c| is_ident(**c)) {
+            name.push(c);
+            chars.next();
+        }
+        name
+    };
+
+    while let Some(&c) = chars.peek() {
+        let combinator_char = match c {
+            '>' => Some(Combinator::Child),
+            '+' => Some(Combinator::Adjacent),
+            '~' => Some(Combinator::Sibling),
+            _ => None,
+        };
+        if c.is_whitespace() || combinator_char.is_some() {
+            chars.next();
+            if let Some(compound) = current.take() {
+                selector.push((combinator, compound));
+                combinator = Combinator::Descendant;
+            }
+            if let Some(next) = combinator_char {
+                combinator = next;
+            }
+            continue;
+        }
+        let compound = current.get_or_insert_with(Compound::default);
+        match c {
+            '*' => {
+                chars.next();
+            }
+            '.' | '#' => {
+                chars.next();
+                let name = ident(&mut chars);
+                if c == '.' { compound.classes.push(name) } else { compound.ids.push(name) }
+            }
+            '[' => {
+                chars.next();
+                let inner: String = chars.by_ref().take_while(|&c| c != ']').collect();
+                compound.attrs.push(parse_attr(&inner));
+            }
+            ':' => {
+                // Pseudo-classes and pseudo-elements, with their arguments.
+                while chars.next_if(|&c| c == ':').is_some() {}
+                ident(&mut chars);
+                if chars.next_if(|&c| c == '(').is_some() {
+                    let mut depth = 1;
+                    for c in chars.by_ref() {
+                        match c {
+                            '(' => depth += 1,
+                            ')' if depth == 1 => break,
+                            ')' => depth -= 1,
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            c if is_ident(c) => compound.tag = Some(ident(&mut chars)),
+            _ => {
+                chars.next();
+            }
+        }
+    }
+    if let Some(compound) = current {
+        selector.push((combinator, compound));
+    }
+    selector
+}
+
+/// Parses the inside of `[..]`: a name, optionally an operator and a value.
+fn parse_attr(inner: &str) -> (String, Option<(String, String)>) {
+    let Some(eq) = inner.find('=') else { return (inner.trim().to_string(), None) };
+    let (name, op) = match inner[..eq].trim_end().char_indices().last() {
+        Some((i, c)) if "~|^$*".contains(c) => (&inner[..i], &inner[i..eq]),
+        _ => (&inner[..eq], "="),
+    };
+    let mut value = inner[eq + 1..].trim();
+    // A trailing ` i` / ` s` flag is ignored.
+    if let Some((v, flag)) = value.rsplit_once(char::is_whitespace)
+        && matches!(flag, "i" | "s" | "I" | "S")
+    {
+        value = v.trim_end();
+    }
+    let value = value.trim_matches(|c| c == '"' || c == '\'');
+    (name.trim().to_string(), Some((op.trim().to_string(), value.to_string())))
+}
+
+/// Whether the first `len` compounds of `selector` match, the last one
+/// against the element at `index`.
+fn matches_at(selector: &Selector, len: usize, elements: &[Flat], index: usize) -> bool {
+    let Some((combinator, compound)) = len.checked_sub(1).map(|i| &selector[i]) else { return true };
+    if !matches_compound(compound, elements[index].el) {
+        return false;
+    }
+    if len == 1 {
+        return true;
+    }
+    match combinator {
+        Combinator::Child => elements[index].parent.is_some_and(|p| matches_at(selector, len - 1, elements, p)),
+        Combinator::Adjacent => elements[index].prev.is_some_and(|p| matches_at(selector, len - 1, elements, p)),
+        Combinator::Descendant => {
+            let mut ancestor = elements[index].parent;
+            while let Some(a) = ancestor {
+                if matches_at(selector, len - 1, elements, a) {
+                    return true;
+                }
+                ancestor = elements[a].parent;
+            }
+            false
+        }
+        Combinator::Sibling => {
+            let mut sibling = elements[index].prev;
+            while let Some(s) = sibling {
+                if matches_at(selector, len - 1, elements, s) {
+                    return true;
+                }
+                sibling = elements[s].prev;
+            }
+            false
+        }
+    }
+}
+
+fn matches_compound(compound: &Compound, el: &Element) -> bool {
+    if compound.tag.as_ref().is_some_and(|tag| !tag.eq_ignore_ascii_case(&el.tag)) {
+        return false;
+    }
+    let classes = el.get_attr("class").unwrap_or("");
+    if !compound.classes.iter().all(|c| classes.split_ascii_whitespace().any(|k| k == c)) {
+        return false;
+    }
+    if !compound.ids.iter().all(|id| el.get_attr("id") == Some(id)) {
+        return false;
+    }
+    compound.attrs.iter().all(|(name, test)| {
+        let Some(actual) = el.get_attr(name) else { return false };
+        let Some((op, value)) = test else { return true };
+        match op.as_str() {
+            "~" => actual.split_ascii_whitespace().any(|v| v == value),
+            "|" => actual == value || actual.strip_prefix(value.as_str()).is_some_and(|r| r.starts_with('-')),
+            "^" => !value.is_empty() && actual.starts_with(value.as_str()),
+            "$" => !value.is_empty() && actual.ends_with(value.as_str()),
+            "*" => !value.is_empty() && actual.contains(value.as_str()),
+            _ => actual == value,
+        }
+    })
+}
+
@@ -0,0 +1,374 @@
+use crate::context;
+
+/// A stylesheet whose class selectors have been scoped with a unique prefix.
+///
+/// Built by the [`css!`](crate::css!) macro: every `.name` selector becomes
+/// `.rsx-<hash>-name`, where `<hash>` is derived from the stylesheet source, so
+/// the same block always gets the same prefix and different blocks never clash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScopedCss {
+    scope: String,
+    classes: Vec<String>,
+    css: String,
+}
+
+impl ScopedCss {
+    /// Parses `source` and scopes every class selector it contains.
+    ///
+    /// Declarations, strings and at-rule preludes are left untouched; rules
+    /// nested in `@media`, `@supports` and similar blocks are scoped too.
+    ///
+    /// ```rust
+    /// use forge_rsx::css::ScopedCss;
+    ///
+    /// let s = ScopedCss::new(r#"@media (max-width: 600px) { .a { width: 0.5rem; } } a[href$=".pdf"] { }"#);
+    /// assert_eq!(s.classes(), ["a"]);
+    /// assert_eq!(
+    ///     s.css(),
+    ///     format!(r#"@media (max-width: 600px) {{ .{}-a {{ width: 0.5rem; }} }} a[href$=".pdf"] {{ }}"#, s.scope())
+    /// );
+    /// assert_eq!(s.class("a"), format!("{}-a", s.scope()));
+    /// assert_eq!(s.class("p-4"), "p-4");
+    /// ```
+    pub fn new(source: &str) -> Self {
+        let scope = format!("rsx-{:08x}", fnv1a(source));
+        let (css, classes) = rewrite_classes(source, &scope);
+        ScopedCss { scope, classes, css }
+    }
+
+    /// The unique prefix shared by all classes of this stylesheet.
+    pub fn scope(&self) -> &str {
+        &self.scope
+    }
+
+    /// The scoped class name for `name`, for use in `class:` attributes.
+    ///
+    /// Names that do not appear in the stylesheet are returned unchanged, so
+    /// utility classes can be mixed in freely.
+    pub fn class(&self, name: &str) -> String {
+        if self.classes.iter().any(|c| c == name) {
+            format!("{}-{}", self.scope, name)
+        } else {
+            name.to_string()
+        }
+    }
+
+    /// The original class names defined by the stylesheet.
+    pub fn classes(&self) -> &[String] {
+        &self.classes
+    }
+
+    /// The rewritten stylesheet.
+    pub fn css(&self) -> &str {
+        &self.css
+    }
+
+    /// Registers the stylesheet in the render context, so the
+    /// [`Document`](crate::Document) emits it in its `<style>` block.
+    pub fn register(&self) {
+        context::add_style(&self.scope, &self.css);
+    }
+}
+
+/// Defines component-scoped CSS.
+///
+/// The block is parsed once per call site; every evaluation registers it in
+/// the render [`context`](crate::context) (once per page) and returns a
+/// `&'static` [`ScopedCss`] whose [`class`](ScopedCss::class) method yields the
+/// scoped class names.
+///
+/// CSS is written either as tokens or, for values that are not valid Rust
+/// tokens (such as `1.5em` or `#1e1e1e`), as a single string literal.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::{css, rsx, context, Document, Html, Style};
+///
+/// fn card(title: &str) -> Html {
+///     let styles = css! {
+///         .card { padding: 1rem; border-radius: 8px; }
+///         .card:hover > .title { color: #333; }
+///     };
+///     rsx!(lined, div { class: styles.class("card"), h2 { class: styles.class("title"), {title} } })
+/// }
+///
+/// context::clear();
+/// let cards = format!("{}{}", card("One"), card("Two"));
+/// assert!(cards.starts_with(r#"<div class="rsx-"#));
+///
+/// // Both cards registered the same block; the page gets it once.
+/// let page = Document::new().body(cards).render(Style::Lined);
+/// assert_eq!(page.matches("padding: 1rem").count(), 1);
+/// assert!(page.contains("<style>.rsx-"));
+///
+/// let legacy = css!(".note { line-height: 1.5em; }");
+/// assert!(legacy.css().contains("-note { line-height: 1.5em; }"));
+/// ```
+#[cfg(feature = "macros")]
+#[macro_export]
+macro_rules! css {
+    ($source:literal) => {
+        forge_rsx::css!(@scoped $source)
+    };
+    (@scoped $source:expr) => {{
+        static SCOPED: ::std::sync::OnceLock<forge_rsx::css::ScopedCss> = ::std::sync::OnceLock::new();
+        let scoped = SCOPED.get_or_init(|| forge_rsx::css::ScopedCss::new($source));
+        scoped.register();
+        scoped
+    }};
+    ($($tokens:tt)*) => {
+        forge_rsx::css!(@scoped stringify!($($tokens)*))
+    };
+}
+
+/// Merges stylesheets into one, keeping each distinct top-level rule once.
+///
+/// Rules are compared by their trimmed text, so the same rule registered by
+/// several components (or several instances of one) is emitted only once, in
+/// first-seen order.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::css::consolidate;
+///
+/// let merged = consolidate([
+///     ".a { color: red; } .b { margin: 0; }",
+///     ".b { margin: 0; }\n@media print { .a { display: none; } }",
+/// ]);
+/// assert_eq!(merged, ".a { color: red; }\n.b { margin: 0; }\n@media print { .a { display: none; } }");
+/// ```
+pub fn consolidate<I, S>(sheets: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut rules: Vec<String> = Vec::new();
+    for sheet in sheets {
+        for rule in split_rules(sheet.as_ref()) {
+            if !rules.iter().any(|r| r == rule) {
+                rules.push(rule.to_string());
+            }
+        }
+    }
+    rules.join("\n")
+}
+
+/// Removes attribute-less `<style>` elements from rendered markup, returning
+/// the remaining markup and the removed stylesheets.
+///
+/// When an element sits alone on its line (beautified output), the whole line
+/// is removed so no blank line is left behind.
+///
+/// Styles inside `template`, `noscript` or an inline `svg` stay where they
+/// are: moving them to the head would apply them when their container
+/// doesn't (before the template is cloned, or with scripting enabled).
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::{rsx, css::extract_styles};
+///
+/// let html = rsx!(btfy2, div { style { ".x { top: 0; }" } p { "Hi" } });
+/// let (markup, styles) = extract_styles(&html);
+/// assert_eq!(markup, "<div>\n  <p>\n    Hi\n  </p>\n</div>");
+/// assert_eq!(styles, vec![".x { top: 0; }"]);
+///
+/// let html = rsx!(lined, div { noscript { style { ".js { display: none; }" } } style { ".y { top: 0; }" } });
+/// let (markup, styles) = extract_styles(&html);
+/// assert_eq!(markup, "<div><noscript><style>.js { display: none; }</style></noscript></div>");
+/// assert_eq!(styles, vec![".y { top: 0; }"]);
+/// ```
+pub fn extract_styles(html: &str) -> (String, Vec<String>) {
+    const OPEN: &str = "<style>";
+    const CLOSE: &str = "</style>";
+    let mut markup = String::with_capacity(html.len());
+    let mut styles = Vec::new();
+    let mut rest = html;
+    let mut depth = 0;
+
+    while let Some(start) = rest.find(OPEN) {
+        let Some(len) = rest[start..].find(CLOSE) else { break };
+        let end = start + len + CLOSE.len();
+        depth = container_depth(&rest[..start], depth);
+        if depth > 0 {
+            markup.push_str(&rest[..end]);
+            rest = &rest[end..];
+            continue;
+        }
+        styles.push(rest[start + OPEN.len()..start + len].trim().to_string());
+
+        let line_start = rest[..start].rfind('\n').map_or(0, |i| i + 1);
+        let alone = rest[line_start..start].trim().is_empty()
+            && (end == rest.len() || rest[end..].starts_with('\n'));
+        if alone && line_start > 0 {
+            // Drop the preceding line break together with the element.
+            markup.push_str(&rest[..line_start - 1]);
+            rest = &rest[end..];
+        } else if alone {
+            markup.push_str(&rest[..line_start]);
+            rest = rest[end..].strip_prefix('\n').unwrap_or(&rest[end..]);
+        } else {
+            markup.push_str(&rest[..start]);
+            rest = &rest[end..];
+        }
+    }
+    markup.push_str(rest);
+    (markup, styles)
+}
+
+/// Updates the number of open `template`, `noscript` and `svg` elements with
+/// the tags found in `markup`.
+fn container_depth(markup: &str, mut depth: usize) -> usize {
+    const CONTAINERS: [&str; 3] = ["template", "noscript", "svg"];
+    let mut rest = markup;
+    while let Some(i) = rest.find('<') {
+        rest = &rest[i + 1..];
+        let (closing, name) = match rest.strip_prefix('/') {
+            Some(name) => (true, name),
+            None => (false, rest),
+        };
+        let is_container = CONTAINERS.iter().any(|tag| {
+            name.get(..tag.len()).is_some_and(|n| n.eq_ignore_ascii_case(tag))
+                && name[tag.len()..].starts_with(|c: char| c == '>' || c == '/' || c.is_ascii_whitespace())
+        });
+        let self_closing = rest.find('>').is_some_and(|end| rest[..end].ends_with('/'));
+        if is_container && closing {
+            depth = depth.saturating_sub(1);
+        } else if is_container && !self_closing {
+            depth += 1;
+        }
+    }
+    depth
+}
+
+/// Splits a stylesheet into its trimmed top-level rules and statements.
+pub(crate) fn split_rules(css: &str) -> Vec<&str> {
+    let mut rules = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+
+    for (i, c) in css.char_indices() {
+        if let Some(q) = quote {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '"' | '\'' => quote = Some(c),
+            '{' => depth += 1,
+            '}' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    rules.push(css[start..=i].trim());
+                    start = i + 1;
+                }
+            }
+            ';' if depth == 0 => {
+                rules.push(css[start..=i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    let tail = css[start..].trim();
+    if !tail.is_empty() {
+        rules.push(tail);
+    }
+    rules.retain(|r| !r.is_empty());
+    rules
+}
+
+/// 32-bit FNV-1a, used to derive stable scope prefixes.
+fn fnv1a(s: &str) -> u32 {
+    s.bytes().fold(0x811c_9dc5, |h, b| (h ^ b as u32).wrapping_mul(0x0100_0193))
+}
+
+/// At-rules whose blocks contain further style rules rather than declarations.
+pub(crate) const NESTING_AT_RULES: [&str; 5] = ["@media", "@supports", "@container", "@layer", "@scope"];
+
+/// Prefixes every class selector in `source` with `scope`, returning the new
+/// stylesheet and the distinct class names found.
+///
+/// Declarations, strings, at-rule preludes and `@keyframes` bodies are copied
+/// unchanged.
+fn rewrite_classes(source: &str, scope: &str) -> (String, Vec<String>) {
+    let mut out = String::with_capacity(source.len() + 16);
+    let mut classes: Vec<String> = Vec::new();
+    // One entry per open block: `true` when the block contains style rules.
+    let mut blocks: Vec<bool> = Vec::new();
+    let mut prelude_start = 0;
+    let mut in_at_prelude = false;
+    let mut chars = source.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        let in_rules = blocks.last().copied().unwrap_or(true);
+        match c {
+            '"' | '\'' => {
+                out.push(c);
+                while let Some((_, s)) = chars.next() {
+                    out.push(s);
+                    if s == '\\' {
+                        if let Some((_, escaped)) = chars.next() {
+                            out.push(escaped);
+                        }
+                    } else if s == c {
+                        break;
+                    }
+                }
+            }
+            '{' => {
+                let prelude = source[prelude_start..i].trim();
+                blocks.push(in_rules && NESTING_AT_RULES.iter().any(|r| prelude.starts_with(r)));
+                in_at_prelude = false;
+                prelude_start = i + 1;
+                out.push(c);
+            }
+            '}' => {
+                blocks.pop();
+                prelude_start = i + 1;
+                out.push(c);
+            }
+            ';' => {
+                in_at_prelude = false;
+                prelude_start = i + 1;
+                out.push(c);
+            }
+            '@' if in_rules => {
+                in_at_prelude = true;
+                out.push(c);
+            }
+            '.' if in_rules && !in_at_prelude => {
+                out.push(c);
+                let starts_ident = chars
+                    .peek()
+                    .is_some_and(|&(_, n)| n.is_alphabetic() || n == '_' || n == '-');
+                if starts_ident {
+                    let mut name = String::new();
+                    while let Some(&(_, n)) = chars.peek() {
+                        if n.is_alphanumeric() || n == '_' || n == '-' {
+                            name.push(n);
+                            chars.next();
+                        } else {
+                            break;
+                        }
+                    }
+                    out.push_str(scope);
+                    out.push('-');
+                    out.push_str(&name);
+                    if !classes.contains(&name) {
+                        classes.push(name);
+                    }
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+
+    (out, classes)
+}
@@ -0,0 +1,391 @@
+use std::path::Path;
+use std::sync::RwLock;
+
+use crate::context;
+use crate::csp::{self, CspHashes};
+use crate::css;
+use crate::files::{self, WriteMode, WriteOutcome};
+use crate::{raw, rsx};
+use crate::rules::Style;
+use crate::theme::{ColorTheme, ThemeTarget};
+
+/// A full HTML page assembled with `rsx!`.
+///
+/// The document always declares `<!DOCTYPE html>` and `<meta charset="UTF-8">`
+/// (or the charset passed to [`render_bytes`](Document::render_bytes));
+/// everything else is added through the builder methods. Stylesheets are linked
+/// in `<head>`, scripts are appended at the end of `<body>`.
+///
+/// Rendering also takes the items hoisted by components through the
+/// [`context`](crate::context) module: they are appended to `<head>`, and a
+/// title set with [`context::set_title`] replaces the document title.
+///
+/// Styles registered by [`css!`](crate::css!) and attribute-less `style`
+/// elements found in the body are merged into a single `<style>` element in
+/// `<head>`, with duplicate rules removed (see [`css::consolidate`]).
+///
+/// Snippets registered with [`register_snippet`] or [`inject`](Document::inject)
+/// are placed at their [`InjectionPoint`].
+///
+/// A [`ColorTheme`] set with [`color_theme`](Document::color_theme) or
+/// [`context::set_color_theme`] is marked on the `html` or `body` element.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::{rsx, Document, Style};
+///
+/// let page = Document::new()
+///     .lang("en")
+///     .title("Forge RSX")
+///     .meta("viewport", "width=device-width, initial-scale=1.0")
+///     .stylesheet("/app.css")
+///     .script("/app.js")
+///     .body(rsx!(lined, h1 { "Welcome" }))
+///     .render(Style::Lined);
+///
+/// assert_eq!(
+///     page,
+///     concat!(
+///         "<!DOCTYPE html>\n",
+///         r#"<html lang="en"><head><meta charset="UTF-8">"#,
+///         r#"<meta name="viewport" content="width=device-width, initial-scale=1.0">"#,
+///         r#"<title>Forge RSX</title><link rel="stylesheet" href="/app.css"></head>"#,
+///         r#"<body><h1>Welcome</h1><script src="/app.js"></script></body></html>"#,
+///     )
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct Document {
+    lang: String,
+    title: String,
+    metas: Vec<(String, String)>,
+    stylesheets: Vec<String>,
+    scripts: Vec<String>,
+    body: String,
+    color_theme: Option<ColorTheme>,
+    snippets: Vec<(InjectionPoint, String)>,
+}
+
+impl Default for Document {
+    fn default() -> Self {
+        Document {
+            lang: "en".to_string(),
+            title: String::new(),
+            metas: Vec::new(),
+            stylesheets: Vec::new(),
+            scripts: Vec::new(),
+            body: String::new(),
+            color_theme: None,
+            snippets: Vec::new(),
+        }
+    }
+}
+
+impl Document {
+    /// Creates an empty document with `lang="en"`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the `lang` attribute of the `html` element.
+    pub fn lang(mut self, lang: impl Into<String>) -> Self {
+        self.lang = lang.into();
+        self
+    }
+
+    /// Sets the page `title`.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Adds a `<meta name=".." content="..">` tag to `head`.
+    pub fn meta(mut self, name: impl Into<String>, content: impl Into<String>) -> Self {
+        self.metas.push((name.into(), content.into()));
+        self
+    }
+
+    /// Adds a `<link rel="stylesheet" href="..">` tag to `head`.
+    pub fn stylesheet(mut self, href: impl Into<String>) -> Self {
+        self.stylesheets.push(href.into());
+        self
+    }
+
+    /// Adds a `<script src=".."></script>` tag at the end of `body`.
+    pub fn script(mut self, src: impl Into<String>) -> Self {
+        self.scripts.push(src.into());
+        self
+    }
+
+    /// Sets the rendered component placed inside `body`.
+    pub fn body(mut self, component: impl std::fmt::Display) -> Self {
+        self.body = component.to_string();
+        self
+    }
+
+    /// Marks a color theme on the `html` or `body` element (see
+    /// [`ColorTheme`]).
+    pub fn color_theme(mut self, theme: ColorTheme) -> Self {
+        self.color_theme = Some(theme);
+        self
+    }
+
+    /// Adds a raw snippet (e.g. a page-specific tracking pixel) at `point`,
+    /// after the snippets registered with [`register_snippet`].
+    pub fn inject(mut self, point: InjectionPoint, html: impl std::fmt::Display) -> Self {
+        self.snippets.push((point, html.to_string()));
+        self
+    }
+
+    /// Renders the whole page with the given style, consuming the head items
+    /// hoisted into the current thread's context.
+    ///
+    /// ```rust
+    /// use forge_rsx::{rsx, context, Document, Style};
+    ///
+    /// context::clear();
+    /// let body = {
+    ///     context::set_title("Profile");
+    ///     context::add_head(rsx!(lined, meta { name: "robots", content: "noindex" }));
+    ///     rsx!(lined, p { "Hi" })
+    /// };
+    /// let page = Document::new().title("Site").body(body).render(Style::Lined);
+    /// assert!(page.contains(r#"<title>Profile</title><meta name="robots" content="noindex"></head>"#));
+    ///
+    /// // Two instances of a component with an inline `style` child.
+    /// let badge = || rsx!(lined, span { style { ".badge { color: red; }" } "New" });
+    /// let page = Document::new().body(format!("{}{}", badge(), badge())).render(Style::Lined);
+    /// assert!(page.contains("<style>.badge { color: red; }</style></head>"));
+    /// assert!(page.contains("<body><span>New</span><span>New</span></body>"));
+    /// ```
+    pub fn render(&self, style: Style) -> String {
+        self.render_with_charset(style, Encoding::Utf8)
+    }
+
+    /// Renders the page like [`render`](Document::render) and returns it with
+    /// the hashes of its inline scripts and styles (see [`csp::inline_hashes`]),
+    /// for a hash-based `Content-Security-Policy` header instead of nonces.
+    ///
+    /// ```rust
+    /// use forge_rsx::{rsx, Document, Style};
+    /// use forge_rsx::csp::hash_source;
+    ///
+    /// let (page, hashes) = Document::new()
+    ///     .body(rsx!(lined, script { "track()" }))
+    ///     .render_with_hashes(Style::Lined);
+    /// assert!(page.contains("<script>track()</script>"));
+    /// assert_eq!(hashes.header_value(), format!("script-src {}", hash_source("track()")));
+    /// ```
+    pub fn render_with_hashes(&self, style: Style) -> (String, CspHashes) {
+        csp::with_hashes(self.render(style))
+    }
+
+    /// Renders the page like [`render`](Document::render) and writes it to
+    /// `path` atomically (see [`files::write_atomic`]).
+    ///
+    /// ```rust
+    /// use forge_rsx::{rsx, Document, Style};
+    /// use forge_rsx::files::{WriteMode, WriteOutcome};
+    ///
+    /// let path = std::env::temp_dir().join("forge-rsx-doc").join("site").join("index.html");
+    /// let page = Document::new().title("Home").body(rsx!(lined, h1 { "Home" }));
+    /// page.render_to_file(&path, Style::Lined, WriteMode::Always).unwrap();
+    /// assert_eq!(
+    ///     page.render_to_file(&path, Style::Lined, WriteMode::IfChanged).unwrap(),
+    ///     WriteOutcome::Unchanged
+    /// );
+    /// ```
+    pub fn render_to_file(&self, path: impl AsRef<Path>, style: Style, mode: WriteMode) -> crate::error::Result<WriteOutcome> {
+        files::render_to_file(path, self.render(style), mode)
+    }
+
+    /// Renders the page like [`render`](Document::render) and transcodes it to
+    /// `encoding`, declaring the matching `<meta charset>`.
+    ///
+    /// Characters the target encoding cannot represent are written as numeric
+    /// character references (`&#8364;`). These are not decoded inside `script`
+    /// and `style` elements, so keep those ASCII when targeting a legacy charset.
+    ///
+    /// ```rust
+    /// use forge_rsx::{rsx, Document, Style};
+    /// use forge_rsx::document::Encoding;
+    ///
+    /// let bytes = Document::new()
+    ///     .body(rsx!(lined, p { "Café: 5 €" }))
+    ///     .render_bytes(Style::Lined, Encoding::Latin1);
+    /// let html: String = bytes.iter().map(|&b| b as char).collect();
+    ///
+    /// assert!(html.contains(r#"<meta charset="ISO-8859-1">"#));
+    /// assert!(html.contains("<p>Café: 5 &#8364;</p>"));
+    /// assert!(bytes.contains(&0xE9)); // 'é' as a single Latin-1 byte
+    /// ```
+    pub fn render_bytes(&self, style: Style, encoding: Encoding) -> Vec<u8> {
+        encoding.encode(&self.render_with_charset(style, encoding))
+    }
+
+    fn render_with_charset(&self, style: Style, encoding: Encoding) -> String {
+        self.render_with(style, encoding, &mut |sheets| css::consolidate(sheets))
+    }
+
+    /// Renders the page, merging its stylesheets with `consolidate`.
+    pub(crate) fn render_with(
+        &self,
+        style: Style,
+        encoding: Encoding,
+        consolidate: &mut dyn FnMut(&[&str]) -> String,
+    ) -> String {
+        // Read before `take_head`, which ends the render and resets it.
+        let color_theme = context::color_theme();
+        let head = context::take_head();
+        let title = head.title.as_deref().unwrap_or(&self.title);
+        let (body, inline_styles) = css::extract_styles(&self.body);
+        let sheets: Vec<&str> = head.styles.iter().chain(&inline_styles).map(String::as_str).collect();
+        let styles = consolidate(&sheets);
+        let styles = (!styles.is_empty()).then_some(styles);
+        let theme = match (color_theme, &self.color_theme) {
+            (Some(name), Some(theme)) => Some(theme.clone().renamed(name)),
+            (Some(name), None) => Some(ColorTheme::new(name)),
+            (None, theme) => theme.clone(),
+        };
+        let snippets = |point: InjectionPoint| {
+            let mut html = snippets(point);
+            for (_, snippet) in self.snippets.iter().filter(|(p, _)| *p == point) {
+                html.push_str(snippet);
+            }
+            html
+        };
+        // A "false" value omits the attribute.
+        let mark = |target: ThemeTarget, attr: &str| match &theme {
+            Some(theme) if theme.target() == target && theme.attribute().0 == attr => theme.attribute().1,
+            _ => "false".to_string(),
+        };
+        rsx!((style), doctype_html html {
+            lang: &self.lang,
+            "data-theme": mark(ThemeTarget::Html, "data-theme"),
+            class: mark(ThemeTarget::Html, "class"),
+            head {
+                meta { charset: encoding.label() }
+                for m in &self.metas => { meta { name: &m.0, content: &m.1 } }
+                title { {title} }
+                for href in &self.stylesheets => { link { rel: "stylesheet", href: href } }
+                { head.items.iter().map(|item| raw!(item)) }
+                if let Some(css) = &styles => { style { {css} } }
+                {raw!(snippets(InjectionPoint::BeforeHeadEnd))}
+            }
+            body {
+                "data-theme": mark(ThemeTarget::Body, "data-theme"),
+                class: mark(ThemeTarget::Body, "class"),
+                {raw!(&body)}
+                for src in &self.scripts => { script { src: src } }
+                {raw!(snippets(InjectionPoint::BeforeBodyEnd))}
+            }
+        }).into()
+    }
+}
+
+/// A place in the page where [`Document`] emits injected snippets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InjectionPoint {
+    /// The end of `<head>`, after the title, stylesheets and hoisted items.
+    BeforeHeadEnd,
+    /// The end of `<body>`, after the document scripts.
+    BeforeBodyEnd,
+}
+
+/// Snippets added with [`register_snippet`], as `(point, name, html)`.
+static SNIPPETS: RwLock<Vec<(InjectionPoint, String, String)>> = RwLock::new(Vec::new());
+
+/// Registers a raw snippet (an analytics tag, a tag manager, a chat widget)
+/// that every [`Document`] emits at `point` (process-wide).
+///
+/// Snippets are emitted in registration order; registering a `name` again
+/// replaces its snippet in place.
+///
+/// # Examples
+/// ```rust,standalone_crate
+/// use forge_rsx::{rsx, Document, Style};
+/// use forge_rsx::document::{register_snippet, unregister_snippet, InjectionPoint};
+///
+/// register_snippet(InjectionPoint::BeforeHeadEnd, "gtm", rsx!(lined, script { src: "/gtm.js", async: "true" }));
+/// register_snippet(InjectionPoint::BeforeBodyEnd, "chat", rsx!(lined, script { src: "/chat.js", defer: "true" }));
+///
+/// let page = Document::new()
+///     .script("/app.js")
+///     .inject(InjectionPoint::BeforeBodyEnd, rsx!(lined, img { src: "/pixel.gif", alt: "" }))
+///     .render(Style::Lined);
+/// assert!(page.contains(r#"<title></title><script src="/gtm.js" async></script></head>"#));
+/// assert!(page.contains(concat!(
+///     r#"<script src="/app.js"></script><script src="/chat.js" defer></script>"#,
+///     r#"<img src="/pixel.gif" alt=""></body>"#,
+/// )));
+///
+/// unregister_snippet("chat");
+/// assert!(!Document::new().render(Style::Lined).contains("/chat.js"));
+/// ```
+pub fn register_snippet(point: InjectionPoint, name: &str, html: impl std::fmt::Display) {
+    let html = html.to_string();
+    let mut snippets = SNIPPETS.write().unwrap_or_else(|e| e.into_inner());
+    match snippets.iter_mut().find(|(_, n, _)| n == name) {
+        Some(entry) => *entry = (point, name.to_string(), html),
+        None => snippets.push((point, name.to_string(), html)),
+    }
+}
+
+/// Removes the snippet registered under `name`, if any.
+pub fn unregister_snippet(name: &str) {
+    SNIPPETS.write().unwrap_or_else(|e| e.into_inner()).retain(|(_, n, _)| n != name);
+}
+
+/// Concatenates the registered snippets for `point`.
+fn snippets(point: InjectionPoint) -> String {
+    let snippets = SNIPPETS.read().unwrap_or_else(|e| e.into_inner());
+    snippets.iter().filter(|(p, _, _)| *p == point).map(|(_, _, html)| html.as_str()).collect()
+}
+
+/// Character encodings supported by [`Document::render_bytes`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// UTF-8, the encoding used by [`Document::render`].
+    Utf8,
+    /// ISO-8859-1 (Latin-1), one byte per character up to U+00FF.
+    Latin1,
+    /// US-ASCII, seven-bit characters only.
+    Ascii,
+}
+
+impl Encoding {
+    /// Returns the label written in `<meta charset>`.
+    pub fn label(self) -> &'static str {
+        match self {
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Latin1 => "ISO-8859-1",
+            Encoding::Ascii => "US-ASCII",
+        }
+    }
+
+    /// Encodes `text`, replacing unrepresentable characters with numeric
+    /// character references.
+    ///
+    /// ```rust
+    /// use forge_rsx::document::Encoding;
+    ///
+    /// assert_eq!(Encoding::Ascii.encode("naïve"), b"na&#239;ve");
+    /// assert_eq!(Encoding::Latin1.encode("naïve"), b"na\xEFve");
+    /// ```
+    pub fn encode(self, text: &str) -> Vec<u8> {
+        let max = match self {
+            Encoding::Utf8 => return text.as_bytes().to_vec(),
+            Encoding::Latin1 => 0xFF,
+            Encoding::Ascii => 0x7F,
+        };
+        let mut out = Vec::with_capacity(text.len());
+        for c in text.chars() {
+            if (c as u32) <= max {
+                out.push(c as u8);
+            } else {
+                out.extend_from_slice(format!("&#{};", c as u32).as_bytes());
+            }
+        }
+        out
+    }
+}
@@ -0,0 +1,306 @@
+use crate::node::{unescape, Node};
+
+/// Wraps `content` in a downlevel-hidden conditional comment,
+/// `<!--[if condition]>content<![endif]-->`, which only Outlook (and legacy
+/// Internet Explorer) renders.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::email::conditional_comment;
+///
+/// assert_eq!(
+///     conditional_comment("mso", "<br>"),
+///     "<!--[if mso]><br><![endif]-->"
+/// );
+/// ```
+pub fn conditional_comment(condition: &str, content: impl std::fmt::Display) -> String {
+    format!("<!--[if {}]>{}<![endif]-->", condition, content)
+}
+
+/// Wraps `content` in a downlevel-revealed conditional comment, so every client
+/// except Outlook renders it.
+///
+/// ```rust
+/// use forge_rsx::email::not_mso;
+///
+/// assert_eq!(not_mso("<div></div>"), "<!--[if !mso]><!--><div></div><!--<![endif]-->");
+/// ```
+pub fn not_mso(content: impl std::fmt::Display) -> String {
+    format!("<!--[if !mso]><!-->{}<!--<![endif]-->", content)
+}
+
+/// Renders a tree as readable plain text, for the `text/plain` part of a
+/// `multipart/alternative` email sent alongside the HTML body.
+///
+/// - Block elements (paragraphs, `div`s, table cells, ..) start new lines;
+///   paragraphs, headings, lists, quotes and `pre` blocks are separated by a
+///   blank line. Other whitespace is collapsed as a browser would.
+/// - `h1` is underlined with `=`, other headings with `-`.
+/// - List items start with `- ` (or `1. `, `2. `, .. in `ol`), nested lists
+///   are indented under their item, and `blockquote` lines start with `> `.
+/// - Links are written as `label (url)`, or just the URL when the label is
+///   the URL itself; same-page (`#..`) and `javascript:` links keep only their
+///   label. Images contribute their `alt` text.
+/// - `br` breaks the line, `hr` becomes a `---` line, `pre` keeps its text as
+///   is, and `head`, `script`, `style` and `template` are left out.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::rsx;
+/// use forge_rsx::email::render_text;
+/// use forge_rsx::node::Node;
+///
+/// let html = rsx!(lined, div {
+///     h1 { "Your order" }
+///     p { "Thanks for shopping with " b { "Forge" } "!" }
+///     ul {
+///         li { "Tea & biscuits" }
+///         li { "Coffee" ol { li { "Beans" } li { "Filter" } } }
+///     }
+///     p { "Track it " a { href: "https://shop.example/t/42", "online" } "." }
+///     img { src: "/fish.png", alt: "Fish & chips" }
+/// });
+/// assert_eq!(
+///     render_text(&Node::parse(&html)),
+///     "Your order\n==========\n\nThanks for shopping with Forge!\n\n- Tea & biscuits\n- Coffee\n  1. Beans\n  2. Filter\n\nTrack it online (https://shop.example/t/42).\n\nFish & chips"
+/// );
+/// ```
+pub fn render_text(node: &Node) -> String {
+    let mut writer = TextWriter { at_line_start: true, ..TextWriter::default() };
+    writer.node(node, 0);
+    let lines: Vec<&str> = writer.out.lines().map(str::trim_end).collect();
+    lines.join("\n")
+}
+
+/// Elements starting and ending a line.
+const TEXT_BLOCKS: [&str; 27] = [
+    "address", "article", "aside", "body", "caption", "dd", "details", "dialog", "div", "dl", "dt", "fieldset", "figcaption",
+    "figure", "footer", "form", "header", "html", "main", "nav", "section", "summary", "tbody", "td", "tfoot", "th", "thead",
+];
+
+/// Elements left out of the text.
+const TEXT_SKIPPED: [&str; 5] = ["head", "script", "style", "template", "title"];
+
+#[derive(Default)]
+struct TextWriter {
+    out: String,
+    /// Written at the start of each line: list indentation and quote markers.
+    prefix: String,
+    /// A space is due before the next word.
+    space: bool,
+    /// Nothing was written on the current line yet, not even the prefix.
+    at_line_start: bool,
+    /// A blank line is due before the next line.
+    gap: bool,
+}
+
+impl TextWriter {
+    /// Writes the due blank line, then the prefix of a new line.
+    fn start_line(&mut self) {
+        if self.gap {
+            self.out.push_str(self.prefix.trim_end());
+            self.out.push('\n');
+            self.gap = false;
+        }
+        self.out.push_str(&self.prefix);
+        self.at_line_start = false;
+    }
+
+    fn word(&mut self, word: &str) {
+        if self.at_line_start {
+            self.start_line();
+        } else if self.space {
+            self.out.push(' ');
+        }
+        self.out.push_str(word);
+        self.space = false;
+    }
+
+    fn text(&mut self, text: &str) {
+        if text.starts_with(char::is_whitespace) {
+            self.space = true;
+        }
+        for (i, word) in text.split_whitespace().enumerate() {
+            if i > 0 {
+                self.space = true;
+            }
+            self.word(word);
+        }
+        if text.ends_with(char::is_whitespace) {
+            self.space = true;
+        }
+    }
+
+    /// Ends the current line, if anything was written on it.
+    fn line_break(&mut self) {
+        if !self.at_line_start {
+            self.out.push('\n');
+            self.at_line_start = true;
+        }
+        self.space = false;
+    }
+
+    /// Ends the current line and leaves a blank line before the next one.
+    fn gap(&mut self) {
+        self.line_break();
+        self.gap = !self.out.is_empty();
+    }
+
+    fn children(&mut self, node: &Node, lists: usize) {
+        for child in node.children() {
+            self.node(child, lists);
+        }
+    }
+
+    /// Writes a node; `lists` counts the enclosing list items.
+    fn node(&mut self, node: &Node, lists: usize) {
+        let el = match node {
+            Node::Text(text) => return self.text(&unescape(text)),
+            Node::Fragment(_) => return self.children(node, lists),
+            Node::Element(el) => el,
+            _ => return,
+        };
+        let tag = el.tag.as_str();
+        match tag {
+            _ if TEXT_SKIPPED.contains(&tag) => {}
+            "br" => {
+                if self.at_line_start {
+                    self.start_line();
+                }
+                self.out.truncate(self.out.trim_end_matches(' ').len());
+                self.out.push('\n');
+                self.at_line_start = true;
+                self.space = false;
+            }
+            "img" => {
+                if let Some(alt) = el.get_attr("alt") {
+                    self.text(&unescape(alt));
+                }
+            }
+            "a" => {
+                self.children(node, lists);
+                let href = el.get_attr("href").map(|h| unescape(h.trim()).into_owned()).unwrap_or_default();
+                if !href.is_empty() && !href.starts_with('#') && !href.starts_with("javascript:") {
+                    let label = node.text_content();
+                    if label.is_empty() {
+                        self.word(&href);
+                    } else if label != href && label != href.trim_start_matches("mailto:") {
+                        self.space = true;
+                        self.word(&format!("({})", href));
+                    }
+                }
+            }
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.gap();
+                let text = node.text_content();
+                self.text(&text);
+                self.line_break();
+                let underline = if tag == "h1" { "=" } else { "-" };
+                self.word(&underline.repeat(text.chars().count().max(1)));
+                self.gap();
+            }
+            "ul" | "ol" => {
+                if lists == 0 { self.gap() } else { self.line_break() }
+                let mut number: i64 = el.get_attr("start").and_then(|s| s.trim().parse().ok()).unwrap_or(1);
+                for item in node.children() {
+                    match item.as_element() {
+                        Some(li) if li.tag == "li" => {
+                            let bullet = if tag == "ol" { format!("{}.", number) } else { "-".to_string() };
+                            number += 1;
+                            self.line_break();
+                            self.word(&bullet);
+                            self.space = true;
+                            let outer = std::mem::take(&mut self.prefix);
+                            self.prefix = format!("{}{}", outer, " ".repeat(bullet.chars().count() + 1));
+                            self.children(item, lists + 1);
+                            self.prefix = outer;
+                            self.line_break();
+                        }
+                        _ => self.node(item, lists),
+                    }
+                }
+                if lists == 0 { self.gap() } else { self.line_break() }
+            }
+            "blockquote" => {
+                self.gap();
+                self.prefix.push_str("> ");
+                self.children(node, lists);
+                self.line_break();
+                self.prefix.truncate(self.prefix.len() - 2);
+                self.gap();
+            }
+            "pre" => {
+                self.gap();
+                let mut text = String::new();
+                raw_text(node, &mut text);
+                for line in text.trim_matches('\n').lines() {
+                    self.start_line();
+                    self.out.push_str(line);
+                    self.out.push('\n');
+                    self.at_line_start = true;
+                }
+                self.gap();
+            }
+            "hr" => {
+                self.gap();
+                self.word("---");
+                self.gap();
+            }
+            "p" | "table" => {
+                self.gap();
+                self.children(node, lists);
+                self.gap();
+            }
+            _ if TEXT_BLOCKS.contains(&tag) || tag == "tr" || tag == "li" => {
+                self.line_break();
+                self.children(node, lists);
+                self.line_break();
+            }
+            _ => self.children(node, lists),
+        }
+    }
+}
+
+/// Collects the unescaped text of a `pre` element as is.
+fn raw_text(node: &Node, out: &mut String) {
+    match node {
+        Node::Text(text) => out.push_str(&unescape(text)),
+        Node::Element(el) if el.tag == "br" => out.push('\n'),
+        _ => node.children().iter().for_each(|child| raw_text(child, out)),
+    }
+}
+
+/// Emits an Outlook conditional comment (`<!--[if ..]> .. <![endif]-->`).
+///
+/// The content is either an expression implementing `Display`, or a tag
+/// written with `rsx!` syntax, rendered in `lined` style. Use it as a braced
+/// `raw!` child inside `rsx!`.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::{raw, rsx, msoif};
+///
+/// let email = rsx!(lined, td {
+///     { raw!(msoif!("gte mso 9", table { width: 600, tr { td { "Outlook layout" } } })) }
+///     div { class: "fluid", "Everyone else" }
+/// });
+/// assert_eq!(
+///     email,
+///     concat!(
+///         r#"<td><!--[if gte mso 9]><table width="600"><tr><td>Outlook layout</td></tr></table><![endif]-->"#,
+///         r#"<div class="fluid">Everyone else</div></td>"#,
+///     )
+/// );
+///
+/// assert_eq!(msoif!("mso", "<br>"), "<!--[if mso]><br><![endif]-->");
+/// ```
+#[cfg(feature = "macros")]
+#[macro_export]
+macro_rules! msoif {
+    ($condition:expr, $tag:ident { $($content:tt)* }) => {
+        forge_rsx::email::conditional_comment($condition, forge_rsx::rsx!(lined, $tag { $($content)* }))
+    };
+    ($condition:expr, $content:expr) => {
+        forge_rsx::email::conditional_comment($condition, $content)
+    };
+}
@@ -0,0 +1,280 @@
+use crate::rsx;
+
+/// The `sandbox` tokens [`EmbedOptions`] starts with: enough for typical
+/// cross-origin players and widgets, without forms, top navigation or
+/// downloads.
+pub const DEFAULT_SANDBOX: [&str; 4] = ["allow-scripts", "allow-same-origin", "allow-popups", "allow-presentation"];
+
+/// The attributes of an iframe rendered by [`embed_iframe`].
+///
+/// Defaults to the [`DEFAULT_SANDBOX`] tokens, an empty `allow` list (no
+/// permissions policy features), `loading="lazy"` and
+/// `referrerpolicy="strict-origin-when-cross-origin"`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbedOptions {
+    title: String,
+    sandbox: Vec<String>,
+    allow: Vec<String>,
+    referrer_policy: String,
+    lazy: bool,
+    size: Option<(u32, u32)>,
+}
+
+impl EmbedOptions {
+    /// Creates the default options; `title` labels the frame for assistive
+    /// technologies.
+    pub fn new(title: impl Into<String>) -> Self {
+        EmbedOptions {
+            title: title.into(),
+            sandbox: DEFAULT_SANDBOX.iter().map(|t| t.to_string()).collect(),
+            allow: Vec::new(),
+            referrer_policy: "strict-origin-when-cross-origin".to_string(),
+            lazy: true,
+            size: None,
+        }
+    }
+
+    /// Adds a `sandbox` token (e.g. `allow-forms`).
+    pub fn sandbox(mut self, token: &str) -> Self {
+        if !self.sandbox.iter().any(|t| t == token) {
+            self.sandbox.push(token.to_string());
+        }
+        self
+    }
+
+    /// Removes every `sandbox` token, for the most restrictive frame.
+    pub fn strict_sandbox(mut self) -> Self {
+        self.sandbox.clear();
+        self
+    }
+
+    /// Adds a permissions policy feature to `allow` (e.g. `fullscreen`,
+    /// `encrypted-media`).
+    pub fn allow(mut self, feature: &str) -> Self {
+        if !self.allow.iter().any(|f| f == feature) {
+            self.allow.push(feature.to_string());
+        }
+        self
+    }
+
+    /// Sets `referrerpolicy`.
+    pub fn referrer_policy(mut self, policy: &str) -> Self {
+        self.referrer_policy = policy.to_string();
+        self
+    }
+
+    /// Loads the frame eagerly (e.g. above the fold).
+    pub fn eager(mut self) -> Self {
+        self.lazy = false;
+        self
+    }
+
+    /// Sets `width` and `height`, reserving the space before it loads.
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.size = Some((width, height));
+        self
+    }
+}
+
+/// Renders an `iframe` embedding third-party content through a safe
+/// template: always sandboxed, with an explicit `allow` list, a referrer
+/// policy, a `title` and lazy loading (see [`EmbedOptions`]).
+///
+/// Returns `None` unless `url` is an `http:` or `https:` URL, so `javascript:`
+/// or `data:` sources never end up in a frame.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::embed::{embed_iframe, EmbedOptions};
+///
+/// let options = EmbedOptions::new("Launch video").allow("fullscreen").size(560, 315);
+/// assert_eq!(
+///     embed_iframe("https://www.youtube-nocookie.com/embed/xyz", &options).unwrap(),
+///     concat!(
+///         r#"<iframe src="https://www.youtube-nocookie.com/embed/xyz" title="Launch video" "#,
+///         r#"sandbox="allow-scripts allow-same-origin allow-popups allow-presentation" allow="fullscreen" "#,
+///         r#"loading="lazy" referrerpolicy="strict-origin-when-cross-origin" width="560" height="315"></iframe>"#,
+///     )
+/// );
+/// assert!(embed_iframe("javascript:alert(1)", &options).is_none());
+/// ```
+pub fn embed_iframe(url: &str, options: &EmbedOptions) -> Option<String> {
+    let scheme = url.split_once(':')?.0.to_ascii_lowercase();
+    if scheme != "http" && scheme != "https" {
+        return None;
+    }
+    Some(frame(url, options))
+}
+
+/// Renders a component into a sandboxed `iframe srcdoc`, for previews of
+/// emails or user content that must not affect (or be styled by) the page.
+///
+/// The markup is escaped as a double-quoted attribute value: `&` becomes
+/// `&amp;` (so entities in the content survive the extra decoding), `"`
+/// becomes `&quot;`, and `<`/`>` become `&lt;`/`&gt;` so that tools
+/// scanning the page for tags (such as the style hoisting of
+/// [`Document`](crate::Document)) do not see inside it.
+///
+/// The frame follows [`EmbedOptions`], except that a `srcdoc` document
+/// shares the page's origin: `allow-same-origin` is dropped whenever
+/// `allow-scripts` is set, as the pair would lift the sandbox.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::rsx;
+/// use forge_rsx::embed::{srcdoc_iframe, EmbedOptions};
+///
+/// let email = rsx!(lined, p { class: "lead", "Fish & chips" });
+/// assert_eq!(
+///     srcdoc_iframe(&email, &EmbedOptions::new("Email preview").strict_sandbox().eager()),
+///     concat!(
+///         r#"<iframe srcdoc="&lt;p class=&quot;lead&quot;&gt;Fish &amp;amp; chips&lt;/p&gt;" "#,
+///         r#"title="Email preview" sandbox="" loading="eager" referrerpolicy="strict-origin-when-cross-origin"></iframe>"#,
+///     )
+/// );
+///
+/// let preview = srcdoc_iframe("<p>Hi</p>", &EmbedOptions::new("Preview"));
+/// assert!(preview.contains(r#"sandbox="allow-scripts allow-popups allow-presentation""#));
+/// ```
+pub fn srcdoc_iframe(html: impl std::fmt::Display, options: &EmbedOptions) -> String {
+    let mut options = options.clone();
+    if options.sandbox.iter().any(|t| t == "allow-scripts") {
+        options.sandbox.retain(|t| t != "allow-same-origin");
+    }
+    let srcdoc = html
+        .to_string()
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+    let mut out = frame("false", &options);
+    out.insert_str("<iframe".len(), &format!(" srcdoc=\"{}\"", srcdoc));
+    out
+}
+
+/// Renders the `iframe` of [`embed_iframe`] and [`srcdoc_iframe`]; a `"false"`
+/// `src` leaves the attribute out.
+fn frame(src: &str, options: &EmbedOptions) -> String {
+    let (width, height) = options.size.map_or((String::from("false"), String::from("false")), |(w, h)| (w.to_string(), h.to_string()));
+    // An empty `sandbox` attribute is the strictest one, so it is kept.
+    rsx!(lined, iframe {
+        src: src,
+        title: &options.title,
+        sandbox: options.sandbox.join(" "),
+        allow: if options.allow.is_empty() { "false".to_string() } else { options.allow.join("; ") },
+        loading: if options.lazy { "lazy" } else { "eager" },
+        referrerpolicy: &options.referrer_policy,
+        width: width,
+        height: height
+    }).into()
+}
+
+/// An oEmbed response, as returned by [`fetch_oembed`].
+///
+/// Requires the `oembed` feature.
+#[cfg(feature = "oembed")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OEmbed {
+    /// The resource type: `video`, `rich`, `photo` or `link`.
+    pub kind: String,
+    /// The resource title, if given.
+    pub title: Option<String>,
+    /// The provider's embed markup, for `video` and `rich` resources.
+    pub html: Option<String>,
+    /// The image URL of `photo` resources.
+    pub url: Option<String>,
+    /// The provider name, if given.
+    pub provider_name: Option<String>,
+    /// The width of the embed, if given.
+    pub width: Option<u32>,
+    /// The height of the embed, if given.
+    pub height: Option<u32>,
+}
+
+#[cfg(feature = "oembed")]
+impl OEmbed {
+    /// Parses an oEmbed JSON response; invalid JSON is an
+    /// [`Error::Parse`](crate::Error::Parse).
+    ///
+    /// ```rust
+    /// use forge_rsx::embed::{EmbedOptions, OEmbed};
+    ///
+    /// let json = r#"{"type":"video","title":"Demo","width":640,"height":360,
+    ///     "html":"<iframe src=\"https://player.example/v/1\" allowfullscreen></iframe><script src=\"x.js\"></script>"}"#;
+    /// let embed = OEmbed::from_json(json).unwrap();
+    /// assert_eq!(embed.title.as_deref(), Some("Demo"));
+    ///
+    /// // Only the frame URL of the provider markup is kept.
+    /// let html = embed.to_iframe(EmbedOptions::new("")).unwrap();
+    /// assert!(html.starts_with(r#"<iframe src="https://player.example/v/1" title="Demo" sandbox="#));
+    /// assert!(html.ends_with(r#"width="640" height="360"></iframe>"#));
+    ///
+    /// // The provider's escaped `src` is escaped once, not twice.
+    /// let json = r#"{"html":"<iframe src=\"https://player.example/v?id=1&amp;t=2\"></iframe>"}"#;
+    /// let html = OEmbed::from_json(json).unwrap().to_iframe(EmbedOptions::new("Clip")).unwrap();
+    /// assert!(html.starts_with(r#"<iframe src="https://player.example/v?id=1&amp;t=2" title="Clip""#));
+    /// ```
+    pub fn from_json(json: &str) -> crate::error::Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(json)?;
+        let text = |key: &str| value.get(key).and_then(|v| v.as_str()).map(str::to_string);
+        // Some providers send dimensions as strings.
+        let number = |key: &str| match value.get(key)? {
+            serde_json::Value::Number(n) => n.as_u64().and_then(|n| u32::try_from(n).ok()),
+            serde_json::Value::String(s) => s.parse().ok(),
+            _ => None,
+        };
+        Ok(OEmbed {
+            kind: text("type").unwrap_or_default(),
+            title: text("title"),
+            html: text("html"),
+            url: text("url"),
+            provider_name: text("provider_name"),
+            width: number("width"),
+            height: number("height"),
+        })
+    }
+
+    /// Renders the resource with [`embed_iframe`], taking the frame URL from
+    /// the provider markup and discarding the rest of it (scripts included).
+    ///
+    /// The oEmbed title and size are used unless `options` sets them. Returns
+    /// `None` when the markup has no usable `iframe`.
+    pub fn to_iframe(&self, mut options: EmbedOptions) -> Option<String> {
+        let tree = crate::node::Node::parse(self.html.as_deref()?);
+        let src = find_iframe_src(&tree)?;
+        if options.title.is_empty() {
+            options.title = self.title.clone().unwrap_or_default();
+        }
+        if options.size.is_none() {
+            options.size = self.width.zip(self.height);
+        }
+        embed_iframe(&src, &options)
+    }
+}
+
+#[cfg(feature = "oembed")]
+fn find_iframe_src(node: &crate::node::Node) -> Option<String> {
+    if let Some(el) = node.as_element()
+        && el.tag == "iframe"
+    {
+        return el.get_attr("src").map(|src| crate::node::unescape(src).into_owned());
+    }
+    node.children().iter().find_map(find_iframe_src)
+}
+
+/// Looks `url` up at the oEmbed `endpoint` of its provider (e.g.
+/// `https://www.youtube.com/oembed`), requesting the JSON format.
+///
+/// Requires the `oembed` feature. Cache the result: this is a blocking
+/// network request. Request failures are returned as
+/// [`Error::Http`](crate::Error::Http).
+#[cfg(feature = "oembed")]
+pub fn fetch_oembed(endpoint: &str, url: &str) -> crate::error::Result<OEmbed> {
+    let body = ureq::get(endpoint)
+        .query("url", url)
+        .query("format", "json")
+        .call()
+        .and_then(|mut response| response.body_mut().read_to_string())
+        .map_err(|e| crate::Error::Http(e.to_string()))?;
+    OEmbed::from_json(&body)
+}
@@ -0,0 +1,114 @@
+use std::fmt;
+use std::io;
+
+use crate::node::patch::PatchError;
+
+/// A parse error in some input, with the line it was detected on when known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based line of the input where the error was detected (0 when
+    /// unknown).
+    pub line: u64,
+    /// A description of the error.
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line == 0 {
+            f.write_str(&self.message)
+        } else {
+            write!(f, "line {}: {}", self.line, self.message)
+        }
+    }
+}
+
+/// The error type of the fallible helpers of this crate.
+///
+/// The `rsx!` macro itself cannot fail (see `rsx_try!` for templates whose
+/// expressions can); this covers helpers that read, write, fetch or check
+/// markup, so applications can report a failed page instead of catching a
+/// panic.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::Error;
+/// use forge_rsx::filters::{try_apply, Value};
+///
+/// let err = try_apply(Value::from("x"), "shout", &[]).unwrap_err();
+/// assert!(matches!(err, Error::Render(_)));
+/// assert_eq!(err.to_string(), "render error: unknown filter `shout`");
+/// ```
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// A template or helper could not render its input (an unknown filter,
+    /// an out-of-range argument, ..).
+    Render(String),
+    /// Markup failed validation; the list holds every problem found.
+    Validation(Vec<ParseError>),
+    /// Reading or writing a file or stream failed.
+    Io(io::Error),
+    /// Input data (JSON, CSV, ..) could not be parsed.
+    Parse(ParseError),
+    /// A network request failed.
+    Http(String),
+    /// Files or pages the markup refers to do not exist (missing favicons,
+    /// broken links); the list describes each of them.
+    NotFound(Vec<String>),
+    /// A [`Patch`](crate::node::patch::Patch) did not fit the tree it was
+    /// applied to.
+    Patch(PatchError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Render(message) => write!(f, "render error: {}", message),
+            Error::Validation(errors) => {
+                write!(f, "invalid markup")?;
+                for (i, error) in errors.iter().enumerate() {
+                    write!(f, "{} {}", if i == 0 { ":" } else { ";" }, error)?;
+                }
+                Ok(())
+            }
+            Error::Io(e) => write!(f, "I/O error: {}", e),
+            Error::Parse(e) => write!(f, "parse error: {}", e),
+            Error::Http(message) => write!(f, "HTTP error: {}", message),
+            Error::NotFound(items) => write!(f, "not found: {}", items.join("; ")),
+            Error::Patch(e) => write!(f, "patch error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Patch(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<PatchError> for Error {
+    fn from(e: PatchError) -> Self {
+        Error::Patch(e)
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Parse(ParseError { line: e.line() as u64, message: e.to_string() })
+    }
+}
+
+/// A `Result` with this crate's [`Error`].
+pub type Result<T> = std::result::Result<T, Error>;
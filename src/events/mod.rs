@@ -0,0 +1,66 @@
+use crate::context;
+
+/// Events the runtime listens for, delegated from `document`.
+pub const EVENTS: [&str; 7] = ["click", "input", "change", "submit", "keydown", "focusin", "focusout"];
+
+/// The delegation runtime, as plain JavaScript.
+///
+/// For each event in [`EVENTS`] it finds the closest element carrying
+/// `data-on:<event>` and calls `window.forgeActions[action](event, element)`.
+/// Actions without a registered function are dispatched instead as a bubbling
+/// `CustomEvent` named after the action, with the original event as `detail`.
+pub const RUNTIME_JS: &str = r#"(()=>{const a=window.forgeActions=window.forgeActions||{};for(const t of ["click","input","change","submit","keydown","focusin","focusout"]){document.addEventListener(t,e=>{const el=e.target.closest&&e.target.closest("[data-on\\:"+t+"]");if(!el)return;const n=el.getAttribute("data-on:"+t);if(a[n]){a[n](e,el)}else{el.dispatchEvent(new CustomEvent(n,{bubbles:true,detail:e}))}})}})();"#;
+
+/// Returns the attribute name binding `event`, e.g. `data-on:click`.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::events;
+///
+/// assert_eq!(events::attr_name("click"), "data-on:click");
+/// ```
+pub fn attr_name(event: &str) -> String {
+    format!("data-on:{}", event)
+}
+
+/// Returns a complete `data-on:<event>="action"` attribute, for markup built
+/// by hand. Inside `rsx!`, write the attribute directly:
+/// `"data-on:click": "cart.add"`.
+///
+/// ```rust
+/// use forge_rsx::{rsx, events};
+///
+/// assert_eq!(events::on("submit", "signup"), r#"data-on:submit="signup""#);
+///
+/// let button = rsx!(lined, button { "data-on:click": "cart.add", "Add" });
+/// assert_eq!(button, r#"<button data-on:click="cart.add">Add</button>"#);
+/// ```
+pub fn on(event: &str, action: &str) -> String {
+    format!(r#"{}="{}""#, attr_name(event), action.replace('&', "&amp;").replace('"', "&quot;"))
+}
+
+/// Returns the runtime wrapped in a `<script>` element.
+pub fn runtime_script() -> String {
+    format!("<script>{}</script>", RUNTIME_JS)
+}
+
+/// Hoists the runtime into `<head>` through the [`context`] module. Components
+/// using `data-on:*` attributes can all call it; the script is only included
+/// once per page.
+///
+/// ```rust
+/// use forge_rsx::{rsx, context, events, Document, Html, Style};
+///
+/// fn counter() -> Html {
+///     events::use_runtime();
+///     rsx!(lined, button { "data-on:click": "increment", "+1" })
+/// }
+///
+/// context::clear();
+/// let page = Document::new().body(format!("{}{}", counter(), counter())).render(Style::Lined);
+/// assert_eq!(page.matches(events::RUNTIME_JS).count(), 1);
+/// assert!(page.contains(r#"<button data-on:click="increment">+1</button>"#));
+/// ```
+pub fn use_runtime() {
+    context::add_head(runtime_script());
+}
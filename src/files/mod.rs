@@ -0,0 +1,94 @@
+use std::fmt::Display;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::error::Result;
+
+/// Whether [`write_atomic`] rewrites a file whose content is unchanged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WriteMode {
+    /// Always write (and bump the modification time).
+    Always,
+    /// Leave the file alone when it already holds the same bytes, so build
+    /// tools watching modification times only see real changes.
+    #[default]
+    IfChanged,
+}
+
+/// What [`write_atomic`] did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteOutcome {
+    /// The file was created or replaced.
+    Written,
+    /// The file already held the content and was not touched.
+    Unchanged,
+}
+
+/// Distinguishes the temporary files of concurrent writes in one process.
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Writes `contents` to `path` atomically: the bytes go to a temporary file
+/// next to it, which is flushed to disk and renamed over `path`, so readers
+/// (a web server, a browser reloading) never see a partial file. Missing
+/// parent directories are created. Failures are returned as
+/// [`Error::Io`](crate::Error::Io).
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::files::{write_atomic, WriteMode, WriteOutcome};
+///
+/// let path = std::env::temp_dir().join("forge-rsx-doc").join("write_atomic.html");
+/// # let _ = std::fs::remove_file(&path);
+/// assert_eq!(write_atomic(&path, b"<p>Hi</p>", WriteMode::IfChanged).unwrap(), WriteOutcome::Written);
+/// assert_eq!(write_atomic(&path, b"<p>Hi</p>", WriteMode::IfChanged).unwrap(), WriteOutcome::Unchanged);
+/// assert_eq!(write_atomic(&path, b"<p>Hi</p>", WriteMode::Always).unwrap(), WriteOutcome::Written);
+/// assert_eq!(std::fs::read_to_string(&path).unwrap(), "<p>Hi</p>");
+/// ```
+pub fn write_atomic(path: impl AsRef<Path>, contents: &[u8], mode: WriteMode) -> Result<WriteOutcome> {
+    let path = path.as_ref();
+    if mode == WriteMode::IfChanged
+        && let Ok(existing) = fs::read(path)
+        && existing == contents
+    {
+        return Ok(WriteOutcome::Unchanged);
+    }
+
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    fs::create_dir_all(dir)?;
+    let name = path.file_name().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?;
+    let temp = dir.join(format!(
+        ".{}.{}-{}.tmp",
+        name.to_string_lossy(),
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let result = fs::File::create(&temp)
+        .and_then(|mut file| {
+            file.write_all(contents)?;
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&temp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result?;
+    Ok(WriteOutcome::Written)
+}
+
+/// Writes rendered markup (a `String` from `rsx!`, a [`Node`](crate::node::Node),
+/// ..) to `path` with [`write_atomic`].
+///
+/// ```rust
+/// use forge_rsx::rsx;
+/// use forge_rsx::files::{render_to_file, WriteMode};
+///
+/// let path = std::env::temp_dir().join("forge-rsx-doc").join("pages").join("about.html");
+/// render_to_file(&path, rsx!(lined, h1 { "About" }), WriteMode::IfChanged).unwrap();
+/// assert_eq!(std::fs::read_to_string(&path).unwrap(), "<h1>About</h1>");
+/// ```
+pub fn render_to_file(path: impl AsRef<Path>, html: impl Display, mode: WriteMode) -> Result<WriteOutcome> {
+    write_atomic(path, html.to_string().as_bytes(), mode)
+}
@@ -0,0 +1,343 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// A value flowing through a filter chain such as `{ title | upper }`.
+///
+/// The piped expression is converted like a braced child: an `Option` of a
+/// `Display` type becomes [`Value::None`] or [`Value::Text`], a cloneable
+/// iterator of `Display` items becomes [`Value::List`], and anything else
+/// that implements `Display` becomes [`Value::Text`]. Filter arguments are
+/// converted the same way.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Value {
+    /// No value (`None`); renders nothing.
+    #[default]
+    None,
+    /// Text, e.g. a string or a formatted number.
+    Text(String),
+    /// The items of an iterable, each formatted as text; rendered joined by
+    /// `", "`.
+    List(Vec<String>),
+    /// Markup rendered without escaping, from the `escape` and `safe`
+    /// filters or a [`PreEscaped`](crate::rules::PreEscaped) input.
+    Html(String),
+}
+
+impl Value {
+    /// Returns the value as text: the text itself, the items joined by
+    /// `", "`, or an empty string for [`Value::None`].
+    pub fn as_text(&self) -> String {
+        self.to_string()
+    }
+
+    /// Whether the value is [`Value::None`], empty text or an empty list.
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Value::None => true,
+            Value::Text(text) | Value::Html(text) => text.is_empty(),
+            Value::List(items) => items.is_empty(),
+        }
+    }
+
+    /// Returns the value as element content: [`Value::Html`] as is, anything
+    /// else escaped with [`escape_html`](crate::rules::escape_html).
+    pub fn to_html(&self) -> String {
+        match self {
+            Value::Html(html) => html.clone(),
+            value => escape(&value.as_text()),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::None => Ok(()),
+            Value::Text(text) | Value::Html(text) => f.write_str(text),
+            Value::List(items) => f.write_str(&items.join(", ")),
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(text: &str) -> Self {
+        Value::Text(text.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(text: String) -> Self {
+        Value::Text(text)
+    }
+}
+
+/// Wraps a reference to a piped expression or filter argument so
+/// [`filter_value!`](crate::filter_value) can pick its conversion to a
+/// [`Value`] based on its type, like [`ChildExpr`](crate::rules::ChildExpr)
+/// does for children.
+pub struct FilterInput<'a, T: ?Sized>(pub &'a T);
+
+/// Converts `Option<T>` inputs. Highest priority.
+pub trait OptionValue {
+    fn to_filter_value(&self) -> Value;
+}
+
+impl<T: fmt::Display> OptionValue for &FilterInput<'_, Option<T>> {
+    fn to_filter_value(&self) -> Value {
+        self.0.as_ref().map_or(Value::None, |v| Value::Text(v.to_string()))
+    }
+}
+
+impl<T: fmt::Display> OptionValue for &FilterInput<'_, &Option<T>> {
+    fn to_filter_value(&self) -> Value {
+        self.0.as_ref().map_or(Value::None, |v| Value::Text(v.to_string()))
+    }
+}
+
+impl<T: fmt::Display> OptionValue for &FilterInput<'_, crate::rules::PreEscaped<T>> {
+    fn to_filter_value(&self) -> Value {
+        Value::Html(self.0.to_string())
+    }
+}
+
+impl OptionValue for &FilterInput<'_, Value> {
+    fn to_filter_value(&self) -> Value {
+        self.0.clone()
+    }
+}
+
+/// Converts cloneable iterators into [`Value::List`].
+pub trait IterValue {
+    fn to_filter_value(&self) -> Value;
+}
+
+impl<I> IterValue for &&FilterInput<'_, I>
+where
+    I: Iterator + Clone,
+    I::Item: fmt::Display,
+{
+    fn to_filter_value(&self) -> Value {
+        Value::List(self.0.clone().map(|v| v.to_string()).collect())
+    }
+}
+
+/// Converts any other `Display` input into [`Value::Text`]. Lowest priority.
+pub trait DisplayValue {
+    fn to_filter_value(&self) -> Value;
+}
+
+impl<T: fmt::Display + ?Sized> DisplayValue for FilterInput<'_, T> {
+    fn to_filter_value(&self) -> Value {
+        Value::Text(self.0.to_string())
+    }
+}
+
+/// Converts an expression into a filter [`Value`] (see [`Value`] for the
+/// rules). Used by the filter syntax of `rsx!`.
+///
+/// ```rust
+/// use forge_rsx::filter_value;
+/// use forge_rsx::filters::Value;
+///
+/// let tags = vec!["rust", "html"];
+/// assert_eq!(filter_value!(tags.iter()), Value::List(vec!["rust".into(), "html".into()]));
+/// assert_eq!(filter_value!(None::<&str>), Value::None);
+/// assert_eq!(filter_value!(42), Value::Text("42".into()));
+/// ```
+#[cfg(feature = "macros")]
+#[macro_export]
+macro_rules! filter_value {
+    ($e:expr) => {{
+        #[allow(unused_imports)]
+        use forge_rsx::filters::{DisplayValue as _, IterValue as _, OptionValue as _};
+        (&&forge_rsx::filters::FilterInput(&$e)).to_filter_value()
+    }};
+}
+
+type FilterFn = Arc<dyn Fn(Value, &[Value]) -> Value + Send + Sync>;
+
+fn registry() -> &'static RwLock<HashMap<String, FilterFn>> {
+    static FILTERS: OnceLock<RwLock<HashMap<String, FilterFn>>> = OnceLock::new();
+    FILTERS.get_or_init(|| {
+        let mut filters: HashMap<String, FilterFn> = HashMap::new();
+        filters.insert("upper".into(), Arc::new(|v: Value, _: &[Value]| map_text(v, |s| s.to_uppercase())));
+        filters.insert("lower".into(), Arc::new(|v: Value, _: &[Value]| map_text(v, |s| s.to_lowercase())));
+        filters.insert("truncate".into(), Arc::new(truncate_filter));
+        filters.insert("default".into(), Arc::new(default_filter));
+        filters.insert("join".into(), Arc::new(join_filter));
+        filters.insert("escape".into(), Arc::new(|v: Value, _: &[Value]| Value::Html(v.to_html())));
+        filters.insert("safe".into(), Arc::new(|v: Value, _: &[Value]| Value::Html(v.as_text())));
+        #[cfg(feature = "i18n")]
+        filters.insert("currency".into(), Arc::new(crate::i18n::currency_filter));
+        RwLock::new(filters)
+    })
+}
+
+/// `truncate(length, end = "…")`: shortens text to `length` characters,
+/// counting a character with its combining marks, variation selectors and
+/// zero-width-joined emoji parts as one, and appends `end` when something
+/// was cut.
+fn truncate_filter(value: Value, args: &[Value]) -> Value {
+    let Some(length) = args.first().and_then(|n| n.as_text().trim().parse::<usize>().ok()) else {
+        return value;
+    };
+    let end = args.get(1).map_or_else(|| "\u{2026}".to_string(), Value::as_text);
+    map_text(value, |text| match cluster_boundaries(text).nth(length) {
+        Some(cut) => format!("{}{}", text[..cut].trim_end(), end),
+        None => text.to_string(),
+    })
+}
+
+/// `default(fallback)`: replaces `None`, empty text or an empty list.
+fn default_filter(value: Value, args: &[Value]) -> Value {
+    match args.first() {
+        Some(fallback) if value.is_empty() => fallback.clone(),
+        _ => value,
+    }
+}
+
+/// `join(separator = "")`: joins the items of a list into text.
+fn join_filter(value: Value, args: &[Value]) -> Value {
+    match value {
+        Value::List(items) => Value::Text(items.join(&args.first().map(Value::as_text).unwrap_or_default())),
+        value => value,
+    }
+}
+
+pub(crate) use crate::rules::escape_html as escape;
+
+/// Byte offsets where user-perceived characters start.
+/// An approximation of grapheme clusters that keeps combining marks,
+/// variation selectors, emoji modifiers and ZWJ sequences with their base.
+pub(crate) fn cluster_boundaries(text: &str) -> impl Iterator<Item = usize> + '_ {
+    let mut joined = false;
+    text.char_indices().filter_map(move |(i, c)| {
+        let extends = matches!(c,
+            '\u{300}'..='\u{36f}' | '\u{1ab0}'..='\u{1aff}' | '\u{1dc0}'..='\u{1dff}' | '\u{20d0}'..='\u{20ff}'
+            | '\u{fe00}'..='\u{fe0f}' | '\u{fe20}'..='\u{fe2f}' | '\u{200d}' | '\u{1f3fb}'..='\u{1f3ff}'
+            | '\u{e0020}'..='\u{e007f}');
+        let starts = (i == 0 || !extends) && !joined;
+        joined = c == '\u{200d}';
+        starts.then_some(i)
+    })
+}
+
+/// Applies `f` to text, or to every item of a list.
+///
+/// Escaped text in a [`Value::Html`] (the output of `escape`, or entities in
+/// `safe` input) is unescaped, transformed and escaped again, so `f` sees the
+/// visible text; markup containing tags is left as it is.
+fn map_text(value: Value, f: impl Fn(&str) -> String) -> Value {
+    match value {
+        Value::None => Value::None,
+        Value::Text(text) => Value::Text(f(&text)),
+        Value::Html(html) if html.contains('<') => Value::Html(html),
+        Value::Html(html) => Value::Html(escape(&f(&crate::node::unescape(&html)))),
+        Value::List(items) => Value::List(items.iter().map(|s| f(s)).collect()),
+    }
+}
+
+/// Registers `filter` under `name` for the filter syntax of `rsx!`, replacing
+/// any filter (built-in or not) of the same name.
+///
+/// A filter receives the piped value and the evaluated arguments, so
+/// applications can add domain-specific formatting without changing the
+/// crate. Register filters once at startup; the registry is shared by all
+/// threads.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::rsx;
+/// use forge_rsx::filters::{register_filter, Value};
+///
+/// register_filter("mask_email", |value, _| {
+///     let text = value.as_text();
+///     match text.split_once('@') {
+///         Some((user, domain)) => format!("{}***@{}", &user[..1], domain).into(),
+///         None => value,
+///     }
+/// });
+/// register_filter("unit", |value, args| {
+///     let unit = args.first().map(Value::as_text).unwrap_or_default();
+///     format!("{}\u{a0}{}", value, unit).into()
+/// });
+///
+/// let email = "montasir@example.com";
+/// let weight = 2.5;
+/// assert_eq!(
+///     rsx!(lined, p { { email | mask_email } ", " { weight | unit("kg") } }),
+///     "<p>m***@example.com, 2.5\u{a0}kg</p>"
+/// );
+/// ```
+pub fn register_filter(name: &str, filter: impl Fn(Value, &[Value]) -> Value + Send + Sync + 'static) {
+    registry().write().unwrap_or_else(|e| e.into_inner()).insert(name.to_string(), Arc::new(filter));
+}
+
+/// Runs the filter registered as `name` on `value`, or returns
+/// [`Error::Render`](crate::Error::Render) if there is none.
+///
+/// This is what `{ value | name(args..) }` in `rsx!` expands to: `rsx_try!`
+/// returns the error, while `rsx!` panics on it so that a typo in a template
+/// fails loudly instead of rendering unfiltered text. Built-in
+/// filters (applied to every item of a list, except `join` and `default`):
+/// `upper`, `lower` and `truncate` work on the visible text of escaped
+/// markup (after `escape`, or entities passed through `safe`) and leave
+/// markup containing tags unchanged.
+///
+/// - `upper`, `lower`: change the case of text;
+/// - `truncate(length, end = "…")`: cuts text to `length` characters,
+///   without splitting an accented letter or an emoji sequence, and appends
+///   `end` if it was cut;
+/// - `default(fallback)`: replaces `None`, empty text or an empty list;
+/// - `join(separator = "")`: joins the items of a list;
+/// - `escape`: escapes `&`, `<`, `>`, `"` and `'` at that point of the
+///   chain (its result is escaped anyway unless it is markup);
+/// - `safe`: marks trusted markup, rendered without escaping;
+/// - `currency(code)`: formats an amount as a price in the current locale
+///   (with the `i18n` feature, see `i18n::currency`).
+///
+/// ```rust
+/// use forge_rsx::{raw, rsx};
+///
+/// let title = "Crème brûlée, a caramelised custard dessert";
+/// let subtitle: Option<&str> = None;
+/// let tags = ["sweet", "french"];
+/// let comment = "<b>5/5</b>";
+/// assert_eq!(
+///     rsx!(lined, article {
+///         h2 { { title | truncate(12) } }
+///         p { { subtitle | default("No subtitle") } }
+///         p { { (tags.iter()) | upper | join(" · ") } }
+///         blockquote { { comment | escape } }
+///     }),
+///     concat!(
+///         "<article><h2>Crème brûlée…</h2><p>No subtitle</p>",
+///         "<p>SWEET · FRENCH</p><blockquote>&lt;b&gt;5/5&lt;/b&gt;</blockquote></article>",
+///     )
+/// );
+///
+/// // Text filters see the text behind escaped markup, not its entities.
+/// let cartoon = "<b>Tom & Jerry</b>";
+/// assert_eq!(rsx!(lined, i { { cartoon | escape | upper } }), "<i>&lt;B&gt;TOM &amp; JERRY&lt;/B&gt;</i>");
+/// assert_eq!(rsx!(lined, i { { cartoon | escape | truncate(4) } }), "<i>&lt;b&gt;T…</i>");
+/// assert_eq!(rsx!(lined, i { { (raw!("a &amp; b")) | truncate(3) } }), "<i>a &amp;…</i>");
+///
+/// let family = "👨\u{200d}👩\u{200d}👧 family";
+/// assert_eq!(rsx!(lined, b { { family | truncate(1, "...") } }), "<b>👨\u{200d}👩\u{200d}👧...</b>");
+/// ```
+pub fn try_apply(value: Value, name: &str, args: &[Value]) -> crate::error::Result<Value> {
+    let filter = registry().read().unwrap_or_else(|e| e.into_inner()).get(name).cloned();
+    match filter {
+        Some(filter) => Ok(filter(value, args)),
+        None => Err(crate::Error::Render(format!("unknown filter `{}`", name))),
+    }
+}
+
+/// Like [`try_apply`], for filters known to be registered.
+///
+/// # Panics
+/// Panics if no filter is registered under `name` (see [`register_filter`]).
+pub fn apply_or_panic(value: Value, name: &str, args: &[Value]) -> Value {
+    try_apply(value, name, args).unwrap_or_else(|e| panic!("{}", e))
+}
@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use crate::output::Html;
+
+type RenderFn = Arc<dyn Fn() -> String + Send + Sync>;
+type Hook = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// A registered fragment: its renderer and, once rendered, its output.
+struct Entry {
+    render: RenderFn,
+    cached: Option<String>,
+    /// Bumped by every invalidation and re-registration, so a render that
+    /// raced with one does not store stale output.
+    generation: u64,
+}
+
+/// Process-wide registry of fragments, keyed by name.
+static FRAGMENTS: OnceLock<RwLock<HashMap<String, Entry>>> = OnceLock::new();
+
+/// Callbacks registered with [`on_invalidate`].
+static HOOKS: RwLock<Vec<Hook>> = RwLock::new(Vec::new());
+
+fn fragments() -> &'static RwLock<HashMap<String, Entry>> {
+    FRAGMENTS.get_or_init(Default::default)
+}
+
+/// Renders a fragment shared by every page (a footer, a navigation bar) once
+/// per process, returning the cached markup on later calls.
+///
+/// The first call registers the expression as the fragment `name`; it is
+/// rendered again only after [`invalidate_fragment`] or
+/// [`invalidate_fragments`]. The expression must not borrow locals, since the
+/// registry keeps it for re-rendering, and should not depend on the request.
+/// Items it hoists with [`context`](crate::context) are only registered on
+/// the render that fills the cache.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::{fragment, rsx};
+/// use forge_rsx::fragments::{invalidate_fragment, use_fragment};
+///
+/// fn page(title: &str) -> String {
+///     let footer = fragment!("docs-footer", rsx!(lined, footer { p { "© Forge" } }));
+///     rsx!(lined, body { h1 { {title} } {footer} }).into()
+/// }
+///
+/// assert_eq!(page("Home"), "<body><h1>Home</h1><footer><p>© Forge</p></footer></body>");
+/// assert_eq!(use_fragment("docs-footer").unwrap(), "<footer><p>© Forge</p></footer>");
+///
+/// invalidate_fragment("docs-footer"); // re-rendered on next use
+/// assert_eq!(page("About"), "<body><h1>About</h1><footer><p>© Forge</p></footer></body>");
+/// ```
+#[cfg(feature = "macros")]
+#[macro_export]
+macro_rules! fragment {
+    ($name:expr, $html:expr $(,)?) => {
+        forge_rsx::fragments::define_fragment($name, || $html)
+    };
+}
+
+/// Returns the cached output of the fragment `name`, first registering
+/// `render` as its renderer if `name` is unknown. The function behind
+/// [`fragment!`](crate::fragment).
+pub fn define_fragment<R: Into<String>>(name: &str, render: impl Fn() -> R + Send + Sync + 'static) -> Html {
+    if !fragments().read().unwrap_or_else(|e| e.into_inner()).contains_key(name) {
+        fragments()
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(name.to_string())
+            .or_insert_with(|| Entry { render: Arc::new(move || render().into()), cached: None, generation: 0 });
+    }
+    use_fragment(name).unwrap_or_default()
+}
+
+/// Registers `render` as the fragment `name`, replacing any previous renderer
+/// and its cached output.
+pub fn register_fragment<R: Into<String>>(name: impl Into<String>, render: impl Fn() -> R + Send + Sync + 'static) {
+    let render: RenderFn = Arc::new(move || render().into());
+    let mut registry = fragments().write().unwrap_or_else(|e| e.into_inner());
+    let entry = registry.entry(name.into()).or_insert_with(|| Entry { render: render.clone(), cached: None, generation: 0 });
+    entry.render = render;
+    entry.cached = None;
+    entry.generation += 1;
+}
+
+/// Returns the output of the fragment `name`, rendering it if it is not
+/// cached, or `None` when `name` is not registered.
+///
+/// Output rendered while the fragment is invalidated or re-registered (by
+/// another thread, or by the render itself) is returned but not cached.
+///
+/// ```rust
+/// use forge_rsx::fragments::{invalidate_fragment, register_fragment, use_fragment};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// static RENDERS: AtomicUsize = AtomicUsize::new(0);
+/// register_fragment("docs-stock", || {
+///     let n = RENDERS.fetch_add(1, Ordering::SeqCst) + 1;
+///     if n == 1 {
+///         invalidate_fragment("docs-stock"); // e.g. the data changed mid-render
+///     }
+///     format!("<p>render {}</p>", n)
+/// });
+/// assert_eq!(use_fragment("docs-stock").unwrap(), "<p>render 1</p>");
+/// assert_eq!(use_fragment("docs-stock").unwrap(), "<p>render 2</p>");
+/// assert_eq!(use_fragment("docs-stock").unwrap(), "<p>render 2</p>");
+/// ```
+pub fn use_fragment(name: &str) -> Option<Html> {
+    let (render, generation) = {
+        let registry = fragments().read().unwrap_or_else(|e| e.into_inner());
+        let entry = registry.get(name)?;
+        if let Some(html) = &entry.cached {
+            return Some(Html::pre_escaped(html.clone()));
+        }
+        (entry.render.clone(), entry.generation)
+    };
+    // Rendered without the lock held, so fragments may use other fragments.
+    // The output is only cached if the fragment was not invalidated or
+    // replaced meanwhile; this call still returns it.
+    let html = render();
+    if let Some(entry) = fragments().write().unwrap_or_else(|e| e.into_inner()).get_mut(name)
+        && entry.generation == generation
+    {
+        entry.cached = Some(html.clone());
+    }
+    Some(Html::pre_escaped(html))
+}
+
+/// Drops the cached output of the fragment `name`, so it is rendered again on
+/// its next use, and calls the [`on_invalidate`] hooks. Unknown names are
+/// ignored.
+pub fn invalidate_fragment(name: &str) {
+    let known = match fragments().write().unwrap_or_else(|e| e.into_inner()).get_mut(name) {
+        Some(entry) => {
+            entry.cached = None;
+            entry.generation += 1;
+            true
+        }
+        None => false,
+    };
+    if known {
+        run_hooks(&[name.to_string()]);
+    }
+}
+
+/// Drops the cached output of every fragment (e.g. after a deploy or a
+/// settings change) and calls the [`on_invalidate`] hooks for each.
+pub fn invalidate_fragments() {
+    let names: Vec<String> = {
+        let mut registry = fragments().write().unwrap_or_else(|e| e.into_inner());
+        registry.values_mut().for_each(|entry| {
+            entry.cached = None;
+            entry.generation += 1;
+        });
+        registry.keys().cloned().collect()
+    };
+    run_hooks(&names);
+}
+
+/// Registers a callback run with the name of each invalidated fragment, e.g.
+/// to purge a CDN or log cache churn.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::fragments::{invalidate_fragment, on_invalidate, register_fragment};
+/// use std::sync::{Arc, Mutex};
+///
+/// let purged = Arc::new(Mutex::new(Vec::new()));
+/// let log = purged.clone();
+/// on_invalidate(move |name| log.lock().unwrap().push(name.to_string()));
+///
+/// register_fragment("docs-nav", || "<nav></nav>");
+/// invalidate_fragment("docs-nav");
+/// invalidate_fragment("docs-unknown");
+/// assert!(purged.lock().unwrap().iter().any(|name| name == "docs-nav"));
+/// assert!(!purged.lock().unwrap().iter().any(|name| name == "docs-unknown"));
+/// ```
+pub fn on_invalidate(hook: impl Fn(&str) + Send + Sync + 'static) {
+    HOOKS.write().unwrap_or_else(|e| e.into_inner()).push(Arc::new(hook));
+}
+
+/// Calls every hook for each of `names`, without holding the hook lock.
+fn run_hooks(names: &[String]) {
+    let hooks = HOOKS.read().unwrap_or_else(|e| e.into_inner()).clone();
+    for name in names {
+        for hook in &hooks {
+            hook(name);
+        }
+    }
+}
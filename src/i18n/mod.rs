@@ -0,0 +1,141 @@
+use crate::context;
+use crate::filters::Value;
+
+/// Sets the locale (a BCP 47 tag such as `en-US`, `de-DE` or `fr`) used by
+/// [`currency`] and the `currency` filter on the current thread, e.g. per
+/// request. Defaults to `en`; [`context::clear`] resets it.
+///
+/// ```rust
+/// use forge_rsx::i18n::{locale, set_locale};
+///
+/// set_locale("de-DE");
+/// let other = std::thread::spawn(locale).join().unwrap();
+/// assert_eq!((locale().as_str(), other.as_str()), ("de-DE", "en"));
+/// ```
+pub fn set_locale(locale: &str) {
+    context::set_locale(locale.to_string());
+}
+
+/// Returns the locale of the current thread.
+pub fn locale() -> String {
+    context::locale().unwrap_or_else(|| "en".to_string())
+}
+
+/// Number and price conventions of a language.
+struct Conventions {
+    group: &'static str,
+    decimal: char,
+    /// Symbol after the amount, separated by a no-break space.
+    symbol_after: bool,
+}
+
+fn conventions(locale: &str) -> Conventions {
+    let language = locale.split(['-', '_']).next().unwrap_or_default().to_ascii_lowercase();
+    match language.as_str() {
+        "de" | "es" | "it" | "nl" | "pt" | "da" | "id" | "tr" => {
+            Conventions { group: ".", decimal: ',', symbol_after: true }
+        }
+        "fr" => Conventions { group: "\u{202f}", decimal: ',', symbol_after: true },
+        "sv" | "fi" | "nb" | "no" | "pl" | "cs" | "ru" | "uk" => {
+            Conventions { group: "\u{a0}", decimal: ',', symbol_after: true }
+        }
+        _ => Conventions { group: ",", decimal: '.', symbol_after: false },
+    }
+}
+
+/// The symbol of an ISO 4217 currency code, or the code itself.
+fn symbol(code: &str) -> &str {
+    match code {
+        "USD" => "$",
+        "EUR" => "€",
+        "GBP" => "£",
+        "JPY" | "CNY" => "¥",
+        "INR" => "₹",
+        "KRW" => "₩",
+        "RUB" => "₽",
+        "TRY" => "₺",
+        "ILS" => "₪",
+        "NGN" => "₦",
+        "UAH" => "₴",
+        "VND" => "₫",
+        "PHP" => "₱",
+        code => code,
+    }
+}
+
+/// Digits after the decimal separator for a currency code.
+fn minor_digits(code: &str) -> usize {
+    match code {
+        "JPY" | "KRW" | "VND" | "CLP" | "ISK" | "UGX" => 0,
+        "BHD" | "KWD" | "OMR" | "JOD" | "TND" => 3,
+        _ => 2,
+    }
+}
+
+/// Formats `amount` as a price in the currency `code` (ISO 4217, e.g.
+/// `EUR`) following the conventions of [`locale`]: grouping and decimal
+/// separators, the number of decimals of the currency, and whether the
+/// symbol goes before or after the amount.
+///
+/// The rules cover the common European and English conventions; other
+/// locales use the English ones.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::rsx;
+/// use forge_rsx::i18n::{currency, currency_in, set_locale};
+///
+/// assert_eq!(currency(1234.5, "USD"), "$1,234.50");
+/// assert_eq!(currency(-3.0, "GBP"), "-£3.00");
+/// assert_eq!(currency_in(1234.5, "EUR", "de-DE"), "1.234,50\u{a0}€");
+/// assert_eq!(currency_in(1234567.891, "EUR", "fr"), "1\u{202f}234\u{202f}567,89\u{a0}€");
+/// assert_eq!(currency_in(1500.0, "JPY", "en"), "¥1,500");
+///
+/// set_locale("de-DE");
+/// let price = 19.99;
+/// assert_eq!(rsx!(lined, span { { price | currency("EUR") } }), "<span>19,99\u{a0}€</span>");
+/// set_locale("en");
+/// ```
+pub fn currency(amount: f64, code: &str) -> String {
+    currency_in(amount, code, &locale())
+}
+
+/// Like [`currency`], with an explicit locale.
+pub fn currency_in(amount: f64, code: &str, locale: &str) -> String {
+    let conventions = conventions(locale);
+    let digits = minor_digits(code);
+    let formatted = format!("{:.*}", digits, amount.abs());
+    let (int, frac) = formatted.split_once('.').unwrap_or((&formatted, ""));
+
+    let mut number = String::new();
+    for (i, c) in int.chars().enumerate() {
+        if i > 0 && (int.len() - i) % 3 == 0 {
+            number.push_str(conventions.group);
+        }
+        number.push(c);
+    }
+    if !frac.is_empty() {
+        number.push(conventions.decimal);
+        number.push_str(frac);
+    }
+
+    let sign = if amount < 0.0 && formatted.bytes().any(|b| b.is_ascii_digit() && b != b'0') { "-" } else { "" };
+    let symbol = symbol(code);
+    if conventions.symbol_after {
+        format!("{}{}\u{a0}{}", sign, number, symbol)
+    } else if symbol == code {
+        format!("{}{}\u{a0}{}", sign, code, number)
+    } else {
+        format!("{}{}{}", sign, symbol, number)
+    }
+}
+
+/// The `currency(code)` filter: formats a numeric value with [`currency`].
+/// Text that is not a number is left unchanged.
+pub(crate) fn currency_filter(value: Value, args: &[Value]) -> Value {
+    let code = args.first().map(Value::as_text).unwrap_or_else(|| "USD".to_string());
+    match value.as_text().trim().parse::<f64>() {
+        Ok(amount) => Value::Text(currency(amount, &code)),
+        Err(_) => value,
+    }
+}
@@ -0,0 +1,170 @@
+use std::fs;
+use std::path::Path;
+
+use crate::error::{Error, ParseError, Result};
+use crate::{rsx, Html};
+
+/// Sprite URL used by [`icon`].
+pub const DEFAULT_SPRITE: &str = "/icons.svg";
+
+/// Renders an inline `svg` that references the symbol `name` in the default
+/// sprite ([`DEFAULT_SPRITE`]).
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::icons::icon;
+///
+/// assert_eq!(
+///     icon("check", 16),
+///     r##"<svg class="icon icon-check" width="16" height="16" aria-hidden="true"><use href="/icons.svg#check"></use></svg>"##
+/// );
+/// ```
+pub fn icon(name: &str, size: u32) -> String {
+    icon_from(DEFAULT_SPRITE, name, size)
+}
+
+/// Renders an inline `svg` that references the symbol `name` in `sprite`.
+pub fn icon_from(sprite: &str, name: &str, size: u32) -> String {
+    rsx!(lined, svg {
+        class: ["icon", format!("icon-{}", name)],
+        width: size,
+        height: size,
+        "aria-hidden": "true",
+        use { href: format!("{}#{}", sprite, name) }
+    }).into()
+}
+
+/// Assembles a sprite from every `.svg` file in `dir`.
+///
+/// Each file becomes a `<symbol>` whose `id` is the file stem and whose
+/// `viewBox` is copied from the file's root `svg` element. Files are sorted by
+/// name so the output is stable. Intended for build scripts; see
+/// [`write_sprite`].
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::icons::build_sprite;
+///
+/// let dir = std::env::temp_dir().join("forge-rsx-icons-doc");
+/// std::fs::create_dir_all(&dir).unwrap();
+/// std::fs::write(
+///     dir.join("check.svg"),
+///     r#"<?xml version="1.0"?><svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24"><path d="M5 12l5 5L20 7"/></svg>"#,
+/// ).unwrap();
+///
+/// let sprite = build_sprite(&dir).unwrap();
+/// assert!(sprite.contains(r#"<symbol id="check" viewBox="0 0 24 24"><path d="M5 12l5 5L20 7"/></symbol>"#));
+/// ```
+pub fn build_sprite(dir: impl AsRef<Path>) -> Result<String> {
+    let mut files: Vec<_> = fs::read_dir(dir)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|e| e.eq_ignore_ascii_case("svg")))
+        .collect();
+    files.sort();
+
+    let mut symbols = Vec::with_capacity(files.len());
+    for path in files {
+        let source = fs::read_to_string(&path)?;
+        let id = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        let (view_box, inner) = svg_parts(&source).ok_or_else(|| {
+            Error::Parse(ParseError { line: 0, message: format!("{} has no root <svg> element", path.display()) })
+        })?;
+        symbols.push(match view_box {
+            Some(vb) => format!(r#"<symbol id="{}" viewBox="{}">{}</symbol>"#, id, vb, inner),
+            None => format!(r#"<symbol id="{}">{}</symbol>"#, id, inner),
+        });
+    }
+
+    Ok(format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" style="display: none">{}</svg>"#,
+        symbols.concat()
+    ))
+}
+
+/// Builds the sprite for `dir` (see [`build_sprite`]) and writes it to `out`.
+pub fn write_sprite(dir: impl AsRef<Path>, out: impl AsRef<Path>) -> Result<()> {
+    Ok(fs::write(out, build_sprite(dir)?)?)
+}
+
+/// File names referenced by [`favicons`], relative to its base path.
+pub const FAVICON_FILES: [&str; 5] = [
+    "favicon.ico",
+    "icon.svg",
+    "apple-touch-icon.png",
+    "manifest.webmanifest",
+    "mask-icon.svg",
+];
+
+/// Renders the modern minimal favicon set for files served under `base_path`:
+/// a 32×32 `favicon.ico` for legacy browsers, a scalable `icon.svg`, a
+/// 180×180 `apple-touch-icon.png`, the web app `manifest.webmanifest` and a
+/// Safari `mask-icon.svg`.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::icons::favicons;
+///
+/// assert_eq!(
+///     favicons("/static/"),
+///     concat!(
+///         r#"<link rel="icon" href="/static/favicon.ico" sizes="32x32">"#,
+///         r#"<link rel="icon" href="/static/icon.svg" type="image/svg+xml">"#,
+///         r#"<link rel="apple-touch-icon" href="/static/apple-touch-icon.png" sizes="180x180">"#,
+///         r#"<link rel="manifest" href="/static/manifest.webmanifest">"#,
+///         r##"<link rel="mask-icon" href="/static/mask-icon.svg" color="#000000">"##,
+///     )
+/// );
+/// ```
+pub fn favicons(base_path: &str) -> String {
+    let base = base_path.trim_end_matches('/');
+    let url = |file: &str| format!("{}/{}", base, file);
+    [
+        rsx!(lined, link { rel: "icon", href: url("favicon.ico"), sizes: "32x32" }),
+        rsx!(lined, link { rel: "icon", href: url("icon.svg"), type: "image/svg+xml" }),
+        rsx!(lined, link { rel: "apple-touch-icon", href: url("apple-touch-icon.png"), sizes: "180x180" }),
+        rsx!(lined, link { rel: "manifest", href: url("manifest.webmanifest") }),
+        rsx!(lined, link { rel: "mask-icon", href: url("mask-icon.svg"), color: "#000000" }),
+    ]
+    .into_iter()
+    .collect::<Html>()
+    .into()
+}
+
+/// Checks that every file [`favicons`] references exists in `dir`, the
+/// directory served at its base path. Meant for build scripts and tests; the
+/// [`Error::NotFound`] lists the missing files.
+///
+/// ```rust
+/// use forge_rsx::icons::check_favicons;
+///
+/// let dir = std::env::temp_dir().join("forge-rsx-favicons-doc");
+/// std::fs::create_dir_all(&dir).unwrap();
+/// std::fs::write(dir.join("favicon.ico"), b"").unwrap();
+///
+/// let err = check_favicons(&dir).unwrap_err();
+/// assert!(matches!(&err, forge_rsx::Error::NotFound(missing) if missing.len() == 4));
+/// assert!(err.to_string().starts_with("not found: favicon file `icon.svg` in "));
+/// assert!(err.to_string().contains("apple-touch-icon.png"));
+/// ```
+pub fn check_favicons(dir: impl AsRef<Path>) -> Result<()> {
+    let dir = dir.as_ref();
+    let missing: Vec<_> = FAVICON_FILES
+        .iter()
+        .filter(|f| !dir.join(f).is_file())
+        .map(|f| format!("favicon file `{}` in {}", f, dir.display()))
+        .collect();
+    if missing.is_empty() { Ok(()) } else { Err(Error::NotFound(missing)) }
+}
+
+/// Splits an SVG document into its root `viewBox` and the markup inside the root element.
+fn svg_parts(source: &str) -> Option<(Option<&str>, &str)> {
+    let open = source.find("<svg")?;
+    let open_end = open + source[open..].find('>')?;
+    let close = source.rfind("</svg>")?;
+    let root = &source[open..open_end];
+    let view_box = root.find("viewBox=\"").and_then(|i| {
+        let value = &root[i + "viewBox=\"".len()..];
+        value.find('"').map(|end| &value[..end])
+    });
+    Some((view_box, source.get(open_end + 1..close)?.trim()))
+}
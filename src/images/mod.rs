@@ -0,0 +1,206 @@
+use std::fs;
+use std::path::Path;
+
+use crate::error::{Error, ParseError, Result};
+use crate::rsx;
+
+/// Renders a `picture` element with a `source srcset` entry and a fallback `img`.
+///
+/// `src` is a URL template: `{w}` is replaced by each width, or, when absent,
+/// a `w` query parameter is appended (the convention of most image CDNs). The
+/// fallback `img` uses the largest width.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::images::responsive_img;
+///
+/// let html = responsive_img("/img/hero-{w}.jpg", &[480, 960], "(max-width: 600px) 100vw, 50vw", "Hero");
+/// assert_eq!(
+///     html,
+///     concat!(
+///         r#"<picture><source type="image/jpeg" srcset="/img/hero-480.jpg 480w, /img/hero-960.jpg 960w" "#,
+///         r#"sizes="(max-width: 600px) 100vw, 50vw"><img src="/img/hero-960.jpg" alt="Hero"></picture>"#,
+///     )
+/// );
+///
+/// let cdn = responsive_img("https://cdn.example.com/a.png?fit=crop", &[320], "100vw", "A");
+/// assert!(cdn.contains(r#"srcset="https://cdn.example.com/a.png?fit=crop&amp;w=320 320w""#));
+/// ```
+pub fn responsive_img(src: &str, widths: &[u32], sizes: &str, alt: &str) -> String {
+    responsive_img_with_formats(src, widths, sizes, alt, &[])
+}
+
+/// Like [`responsive_img`], with additional `source` entries for modern
+/// formats (e.g. `["avif", "webp"]`), listed before the original format.
+///
+/// The alternative URLs are derived by replacing the file extension of `src`.
+///
+/// ```rust
+/// use forge_rsx::images::responsive_img_with_formats;
+///
+/// let html = responsive_img_with_formats("/p-{w}.jpg", &[640], "100vw", "Photo", &["webp"]);
+/// assert!(html.starts_with(r#"<picture><source type="image/webp" srcset="/p-640.webp 640w" sizes="100vw"><source type="image/jpeg""#));
+/// ```
+pub fn responsive_img_with_formats(src: &str, widths: &[u32], sizes: &str, alt: &str, formats: &[&str]) -> String {
+    let mut sources: Vec<(String, String)> = formats
+        .iter()
+        .map(|format| {
+            let url = with_extension(src, format);
+            (mime_type(format), srcset(&url, widths))
+        })
+        .collect();
+    sources.push((mime_type(extension(src).unwrap_or_default()), srcset(src, widths)));
+    let fallback = widths.iter().max().map_or_else(|| src.replace("{w}", ""), |w| width_url(src, *w));
+
+    rsx!(lined, picture {
+        for source in &sources => { source { type: &source.0, srcset: &source.1, sizes: sizes } }
+        img { src: fallback, alt: alt }
+    }).into()
+}
+
+/// Builds a `srcset` value (`url 480w, url 960w`) from a URL template.
+///
+/// ```rust
+/// assert_eq!(forge_rsx::images::srcset("/a-{w}.jpg", &[1, 2]), "/a-1.jpg 1w, /a-2.jpg 2w");
+/// ```
+pub fn srcset(src: &str, widths: &[u32]) -> String {
+    widths
+        .iter()
+        .map(|w| format!("{} {}w", width_url(src, *w), w))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Expands the URL template for one width.
+fn width_url(src: &str, width: u32) -> String {
+    if src.contains("{w}") {
+        src.replace("{w}", &width.to_string())
+    } else {
+        let separator = if src.contains('?') { '&' } else { '?' };
+        format!("{}{}w={}", src, separator, width)
+    }
+}
+
+/// The extension of the path part of `src` (query and fragment excluded).
+fn extension(src: &str) -> Option<&str> {
+    let path = src.split(['?', '#']).next().unwrap_or(src);
+    let file = path.rsplit('/').next().unwrap_or(path);
+    file.rsplit_once('.').map(|(_, ext)| ext)
+}
+
+/// Replaces the extension of the path part of `src`.
+fn with_extension(src: &str, ext: &str) -> String {
+    let path_end = src.find(['?', '#']).unwrap_or(src.len());
+    let (path, rest) = src.split_at(path_end);
+    match extension(path) {
+        Some(old) => format!("{}{}{}", &path[..path.len() - old.len()], ext, rest),
+        None => format!("{}.{}{}", path, ext, rest),
+    }
+}
+
+/// The MIME type for an image file extension.
+fn mime_type(ext: &str) -> String {
+    match ext.to_ascii_lowercase().as_str() {
+        "jpg" | "jpeg" => "image/jpeg".to_string(),
+        "svg" => "image/svg+xml".to_string(),
+        "" => String::new(),
+        other => format!("image/{}", other),
+    }
+}
+
+/// Renders a lazily loaded `img` with `loading="lazy"` and `decoding="async"`.
+///
+/// Passing the intrinsic `(width, height)` lets the browser reserve space
+/// before the image arrives, preventing layout shift.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::images::img_lazy;
+///
+/// assert_eq!(
+///     img_lazy("/a.png", "A", Some((640, 480))),
+///     r#"<img src="/a.png" alt="A" loading="lazy" decoding="async" width="640" height="480">"#
+/// );
+/// assert_eq!(
+///     img_lazy("/a.png", "A", None),
+///     r#"<img src="/a.png" alt="A" loading="lazy" decoding="async">"#
+/// );
+/// ```
+pub fn img_lazy(src: &str, alt: &str, dims: Option<(u32, u32)>) -> String {
+    match dims {
+        Some((width, height)) => rsx!(lined, img {
+            src: src, alt: alt, loading: "lazy", decoding: "async", width: width, height: height
+        }).into(),
+        None => rsx!(lined, img { src: src, alt: alt, loading: "lazy", decoding: "async" }).into(),
+    }
+}
+
+/// Like [`img_lazy`], reading the dimensions from the image file at `path`.
+///
+/// PNG, GIF, JPEG and WebP files are supported; other formats return an
+/// [`Error::Parse`](crate::Error::Parse).
+pub fn img_lazy_from_file(src: &str, alt: &str, path: impl AsRef<Path>) -> Result<String> {
+    Ok(img_lazy(src, alt, Some(image_dimensions(path)?)))
+}
+
+/// Reads the `(width, height)` of a PNG, GIF, JPEG or WebP file from its header.
+///
+/// ```rust
+/// use forge_rsx::images::image_dimensions;
+///
+/// // A 1x1 transparent GIF.
+/// let path = std::env::temp_dir().join("forge-rsx-pixel.gif");
+/// std::fs::write(&path, b"GIF89a\x01\x00\x01\x00\x80\x00\x00\xff\xff\xff\x00\x00\x00!\xf9\x04\x01\x00\x00\x00\x00,\x00\x00\x00\x00\x01\x00\x01\x00\x00\x02\x02D\x01\x00;").unwrap();
+/// assert_eq!(image_dimensions(&path).unwrap(), (1, 1));
+/// ```
+pub fn image_dimensions(path: impl AsRef<Path>) -> Result<(u32, u32)> {
+    let bytes = fs::read(path.as_ref())?;
+    header_dimensions(&bytes).ok_or_else(|| {
+        Error::Parse(ParseError {
+            line: 0,
+            message: format!("{}: unsupported or truncated image", path.as_ref().display()),
+        })
+    })
+}
+
+/// Parses the dimensions out of an image header.
+fn header_dimensions(b: &[u8]) -> Option<(u32, u32)> {
+    let be16 = |i: usize| Some(u16::from_be_bytes([*b.get(i)?, *b.get(i + 1)?]) as u32);
+    let le16 = |i: usize| Some(u16::from_le_bytes([*b.get(i)?, *b.get(i + 1)?]) as u32);
+    let be32 = |i: usize| Some(u32::from_be_bytes(b.get(i..i + 4)?.try_into().ok()?));
+    let le24 = |i: usize| Some(u32::from_le_bytes([*b.get(i)?, *b.get(i + 1)?, *b.get(i + 2)?, 0]));
+
+    if b.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return Some((be32(16)?, be32(20)?));
+    }
+    if b.starts_with(b"GIF87a") || b.starts_with(b"GIF89a") {
+        return Some((le16(6)?, le16(8)?));
+    }
+    if b.starts_with(b"RIFF") && b.get(8..12) == Some(b"WEBP") {
+        return match b.get(12..16)? {
+            b"VP8 " => Some((le16(26)? & 0x3fff, le16(28)? & 0x3fff)),
+            b"VP8L" => {
+                let bits = u32::from_le_bytes(b.get(21..25)?.try_into().ok()?);
+                Some(((bits & 0x3fff) + 1, ((bits >> 14) & 0x3fff) + 1))
+            }
+            b"VP8X" => Some((le24(24)? + 1, le24(27)? + 1)),
+            _ => None,
+        };
+    }
+    if b.starts_with(&[0xff, 0xd8]) {
+        // Walk the JPEG segments until a start-of-frame marker.
+        let mut i = 2;
+        while i + 9 < b.len() {
+            if b[i] != 0xff {
+                return None;
+            }
+            let marker = b[i + 1];
+            let is_sof = (0xc0..=0xcf).contains(&marker) && !matches!(marker, 0xc4 | 0xc8 | 0xcc);
+            if is_sof {
+                return Some((be16(i + 7)?, be16(i + 5)?));
+            }
+            i += 2 + be16(i + 2)? as usize;
+        }
+    }
+    None
+}
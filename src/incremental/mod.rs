@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+/// Returns the marker a page shell renders where the component `id` goes.
+///
+/// The marker is an HTML comment, so a shell rendered on its own is still
+/// valid markup.
+pub fn placeholder(id: &str) -> String {
+    format!("<!--forge-rsx:{}-->", id)
+}
+
+/// A part of the rendered shell.
+enum Segment {
+    Static(String),
+    Slot(String),
+}
+
+struct Component {
+    render: Box<dyn Fn() -> String>,
+    cached: Option<String>,
+}
+
+/// A page whose components are re-rendered only when marked dirty.
+///
+/// The shell (the static part of the page) is rendered once and split at its
+/// [`placeholder`]s. Each render then reuses the cached output of every clean
+/// component and splices the fresh output of dirty ones into it, which suits
+/// servers that keep serving the same page with one changing widget.
+///
+/// Component output is spliced as is: in indented styles its continuation
+/// lines are not re-indented to the placeholder's depth.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::{raw, rsx};
+/// use forge_rsx::incremental::{placeholder, Incremental};
+/// use std::cell::Cell;
+/// use std::rc::Rc;
+///
+/// let count = Rc::new(Cell::new(0));
+/// let clock = {
+///     let count = count.clone();
+///     move || {
+///         count.set(count.get() + 1);
+///         rsx!(lined, span { {format!("tick {}", count.get())} })
+///     }
+/// };
+///
+/// let mut page = Incremental::new(|| {
+///     rsx!(lined, body { h1 { "Dashboard" } {raw!(placeholder("clock"))} })
+/// })
+/// .component("clock", clock);
+///
+/// assert_eq!(page.render(), "<body><h1>Dashboard</h1><span>tick 1</span></body>");
+/// assert_eq!(page.render(), "<body><h1>Dashboard</h1><span>tick 1</span></body>");
+/// assert_eq!(count.get(), 1); // served from the cache
+///
+/// page.mark_dirty("clock");
+/// assert_eq!(page.render(), "<body><h1>Dashboard</h1><span>tick 2</span></body>");
+/// ```
+pub struct Incremental {
+    shell: Box<dyn Fn() -> String>,
+    segments: Option<Vec<Segment>>,
+    components: HashMap<String, Component>,
+    output: Option<String>,
+}
+
+impl Incremental {
+    /// Creates a page from a closure rendering its shell.
+    pub fn new<R: Into<String>>(shell: impl Fn() -> R + 'static) -> Self {
+        Incremental {
+            shell: Box::new(move || shell().into()),
+            segments: None,
+            components: HashMap::new(),
+            output: None,
+        }
+    }
+
+    /// Registers the component rendered at `placeholder(id)`.
+    pub fn component<R: Into<String>>(mut self, id: impl Into<String>, render: impl Fn() -> R + 'static) -> Self {
+        self.components
+            .insert(id.into(), Component { render: Box::new(move || render().into()), cached: None });
+        self.output = None;
+        self
+    }
+
+    /// Marks the component `id` for re-rendering on the next
+    /// [`render`](Incremental::render). Unknown ids are ignored.
+    pub fn mark_dirty(&mut self, id: &str) {
+        if let Some(component) = self.components.get_mut(id) {
+            component.cached = None;
+            self.output = None;
+        }
+    }
+
+    /// Marks the shell and every component for re-rendering.
+    pub fn invalidate(&mut self) {
+        self.segments = None;
+        self.output = None;
+        for component in self.components.values_mut() {
+            component.cached = None;
+        }
+    }
+
+    /// Returns the page, re-rendering only what was marked dirty since the
+    /// last call.
+    pub fn render(&mut self) -> &str {
+        if self.output.is_none() {
+            let segments = self.segments.get_or_insert_with(|| split_shell(&(self.shell)()));
+            let mut out = String::new();
+            for segment in segments.iter() {
+                match segment {
+                    Segment::Static(text) => out.push_str(text),
+                    Segment::Slot(id) => {
+                        if let Some(component) = self.components.get_mut(id) {
+                            let cached = component.cached.get_or_insert_with(|| (component.render)());
+                            out.push_str(cached);
+                        }
+                    }
+                }
+            }
+            self.output = Some(out);
+        }
+        self.output.as_deref().unwrap_or_default()
+    }
+}
+
+/// Splits a rendered shell at its placeholders.
+fn split_shell(html: &str) -> Vec<Segment> {
+    const OPEN: &str = "<!--forge-rsx:";
+    let mut segments = Vec::new();
+    let mut rest = html;
+    while let Some(start) = rest.find(OPEN) {
+        let after = &rest[start + OPEN.len()..];
+        let Some(end) = after.find("-->") else { break };
+        segments.push(Segment::Static(rest[..start].to_string()));
+        segments.push(Segment::Slot(after[..end].to_string()));
+        rest = &after[end + 3..];
+    }
+    segments.push(Segment::Static(rest.to_string()));
+    segments
+}
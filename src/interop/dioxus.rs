@@ -0,0 +1,109 @@
+//! Converting trees to [Dioxus](https://dioxuslabs.com) virtual DOM nodes.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use ::dioxus_core::{
+    Attribute, AttributeValue, DynamicNode, Template, TemplateAttribute, TemplateNode, VNode, VPlaceholder,
+    VText,
+};
+
+use crate::node::{Node, intern, unescape};
+
+const SVG_NAMESPACE: &str = "http://www.w3.org/2000/svg";
+
+thread_local! {
+    /// One template per tag and namespace, leaked on first use.
+    static TEMPLATES: RefCell<HashMap<(&'static str, Option<&'static str>), Template>> = RefCell::new(HashMap::new());
+}
+
+/// The template of a single root whose only content is one dynamic node.
+static DYNAMIC_ROOT: Template = Template {
+    roots: &[TemplateNode::Dynamic { id: 0 }],
+    node_paths: &[&[0]],
+    attr_paths: &[],
+};
+
+/// Converts a tree into a Dioxus `VNode`, so markup shared with
+/// server-rendered pages (e.g. `Node::parse(&rsx!(..))`) can be returned from
+/// a Dioxus component.
+///
+/// Every element becomes a node of its own with dynamic attributes and
+/// children, using one template per tag name. Text and attribute values are
+/// unescaped, boolean attributes are set to `true`, `svg` subtrees get the SVG namespace, and comments and
+/// doctypes become placeholders. Tag and attribute names are
+/// [interned](intern), so they should come from a fixed set rather than user
+/// input.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::rsx;
+/// use forge_rsx::node::Node;
+/// use forge_rsx::interop::dioxus::to_dioxus;
+/// use dioxus_core::{DynamicNode, TemplateNode};
+///
+/// let tree = Node::parse(&rsx!(lined, p { class: "lead", "Tom & Jerry" }));
+/// let vnode = to_dioxus(&tree);
+///
+/// let TemplateNode::Element { tag, .. } = vnode.template.roots[0] else { panic!() };
+/// assert_eq!(tag, "p");
+/// assert_eq!(vnode.dynamic_attrs[0][0].name, "class");
+/// let DynamicNode::Fragment(children) = &vnode.dynamic_nodes[0] else { panic!() };
+/// let DynamicNode::Text(text) = &children[0].dynamic_nodes[0] else { panic!() };
+/// assert_eq!(text.value, "Tom & Jerry");
+/// ```
+pub fn to_dioxus(node: &Node) -> VNode {
+    convert(node, None, false)
+}
+
+fn convert(node: &Node, namespace: Option<&'static str>, raw: bool) -> VNode {
+    let dynamic = |node: DynamicNode| VNode::new(None, DYNAMIC_ROOT, Box::new([node]), Box::default());
+    match node {
+        Node::Element(el) => {
+            let tag = intern(&el.tag);
+            let namespace = if tag == "svg" { Some(SVG_NAMESPACE) } else { namespace };
+            let attrs: Box<[Attribute]> = el
+                .attrs
+                .iter()
+                .map(|(name, value)| {
+                    let value = match value {
+                        Some(value) => AttributeValue::Text(unescape(value).into_owned()),
+                        None => AttributeValue::Bool(true),
+                    };
+                    Attribute::new(intern(name), value, None, false)
+                })
+                .collect();
+            let raw = el.tag == "script" || el.tag == "style";
+            let children: Vec<VNode> = el.children.iter().map(|child| convert(child, namespace, raw)).collect();
+            let children = if children.is_empty() {
+                DynamicNode::Placeholder(VPlaceholder::default())
+            } else {
+                DynamicNode::Fragment(children)
+            };
+            VNode::new(None, element_template(tag, namespace), Box::new([children]), Box::new([attrs]))
+        }
+        Node::Text(text) if raw => dynamic(DynamicNode::Text(VText::new(text))),
+        Node::Text(text) => dynamic(DynamicNode::Text(VText::new(unescape(text)))),
+        Node::Fragment(nodes) if !nodes.is_empty() => {
+            dynamic(DynamicNode::Fragment(nodes.iter().map(|child| convert(child, namespace, raw)).collect()))
+        }
+        _ => dynamic(DynamicNode::Placeholder(VPlaceholder::default())),
+    }
+}
+
+/// Returns the template of a `tag` element with one dynamic attribute list
+/// and one dynamic child.
+fn element_template(tag: &'static str, namespace: Option<&'static str>) -> Template {
+    TEMPLATES.with(|templates| {
+        *templates.borrow_mut().entry((tag, namespace)).or_insert_with(|| Template {
+            roots: Box::leak(Box::new([TemplateNode::Element {
+                tag,
+                namespace,
+                attrs: &[TemplateAttribute::Dynamic { id: 0 }],
+                children: &[TemplateNode::Dynamic { id: 0 }],
+            }])),
+            node_paths: &[&[0, 0]],
+            attr_paths: &[&[0]],
+        })
+    })
+}
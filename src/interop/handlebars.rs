@@ -0,0 +1,70 @@
+//! Calling components from [Handlebars](https://docs.rs/handlebars) templates.
+
+use ::handlebars::{
+    Context, Handlebars, Helper, HelperDef, HelperResult, JsonValue, Output, RenderContext,
+};
+
+/// A component exposed as a Handlebars helper; see [`register_rsx_helper`].
+pub struct RsxHelper<F>(pub F);
+
+impl<F, R> HelperDef for RsxHelper<F>
+where
+    F: Fn(&JsonValue) -> R + Send + Sync,
+    R: Into<String>,
+{
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let mut props = match h.param(0).map(|p| p.value()) {
+            Some(JsonValue::Object(map)) => map.clone(),
+            _ => Default::default(),
+        };
+        for (key, value) in h.hash() {
+            props.insert(key.to_string(), value.value().clone());
+        }
+        out.write(&(self.0)(&JsonValue::Object(props)).into())?;
+        Ok(())
+    }
+}
+
+/// Registers `component` as the Handlebars helper `name`, so existing
+/// templates can call it while the rest of the page is still rendered by
+/// Handlebars.
+///
+/// The component receives its props as one JSON object: the fields of an
+/// object passed as the first positional argument (`{{card user}}`),
+/// overridden by the hash arguments (`{{card user size="lg"}}`). Its output
+/// is written as-is, without Handlebars' escaping.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::{rsx, Html};
+/// use forge_rsx::interop::handlebars::register_rsx_helper;
+/// use handlebars::{Handlebars, JsonValue};
+///
+/// fn avatar(props: &JsonValue) -> Html {
+///     let name = props["name"].as_str().unwrap_or_default();
+///     let size = props["size"].as_u64().unwrap_or(32);
+///     rsx!(lined, img { src: &format!("/avatars/{}.png", name), alt: name, width: &size.to_string() })
+/// }
+///
+/// let mut registry = Handlebars::new();
+/// register_rsx_helper(&mut registry, "avatar", avatar);
+/// registry.register_template_string("profile", "<h1>{{user.name}}</h1>{{avatar user size=64}}").unwrap();
+///
+/// let data: JsonValue = r#"{ "user": { "name": "ann" } }"#.parse().unwrap();
+/// let html = registry.render("profile", &data).unwrap();
+/// assert_eq!(html, r#"<h1>ann</h1><img src="/avatars/ann.png" alt="ann" width="64">"#);
+/// ```
+pub fn register_rsx_helper<F, R>(registry: &mut Handlebars<'_>, name: &str, component: F)
+where
+    F: Fn(&JsonValue) -> R + Send + Sync + 'static,
+    R: Into<String>,
+{
+    registry.register_helper(name, Box::new(RsxHelper(component)));
+}
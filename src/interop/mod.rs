@@ -0,0 +1,181 @@
+#[cfg(feature = "dioxus")]
+pub mod dioxus;
+#[cfg(feature = "handlebars")]
+pub mod handlebars;
+#[cfg(feature = "tera")]
+pub mod tera;
+#[cfg(feature = "yew")]
+pub mod yew;
+
+use crate::node::Node;
+use crate::output::Html;
+
+/// Markup that can be handed to another HTML library without being escaped
+/// again.
+///
+/// Other engines treat the strings behind `rsx!`'s [`Html`] as text to
+/// escape. [`into_html`](IntoHtml::into_html) converts it (or a `String` or
+/// [`Node`]) into any [`FromHtml`] wrapper, such as `maud::PreEscaped` with
+/// the `maud` feature or `askama::filters::Safe` with the `askama` feature.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::rsx;
+/// use forge_rsx::interop::IntoHtml;
+///
+/// let html: String = rsx!(lined, b { "bold" }).into_html();
+/// assert_eq!(html, "<b>bold</b>");
+/// ```
+pub trait IntoHtml {
+    /// Returns the markup as a string.
+    fn into_html_string(self) -> String;
+
+    /// Wraps the markup in `W`.
+    fn into_html<W: FromHtml>(self) -> W
+    where
+        Self: Sized,
+    {
+        W::from_html(self.into_html_string())
+    }
+}
+
+impl IntoHtml for String {
+    fn into_html_string(self) -> String {
+        self
+    }
+}
+
+impl IntoHtml for &str {
+    fn into_html_string(self) -> String {
+        self.to_string()
+    }
+}
+
+impl IntoHtml for &String {
+    fn into_html_string(self) -> String {
+        self.clone()
+    }
+}
+
+impl IntoHtml for Html {
+    fn into_html_string(self) -> String {
+        self.into_string()
+    }
+}
+
+impl IntoHtml for &Html {
+    fn into_html_string(self) -> String {
+        self.as_str().to_string()
+    }
+}
+
+impl IntoHtml for Node {
+    fn into_html_string(self) -> String {
+        self.to_string()
+    }
+}
+
+impl IntoHtml for &Node {
+    fn into_html_string(self) -> String {
+        self.to_string()
+    }
+}
+
+/// A wrapper type marking a string as markup, the target of
+/// [`IntoHtml::into_html`].
+pub trait FromHtml {
+    /// Wraps already rendered markup.
+    fn from_html(html: String) -> Self;
+}
+
+impl FromHtml for String {
+    fn from_html(html: String) -> Self {
+        html
+    }
+}
+
+impl FromHtml for Html {
+    fn from_html(html: String) -> Self {
+        Html::pre_escaped(html)
+    }
+}
+
+/// Lets maud templates embed `rsx!` output with `(markup)`.
+///
+/// ```rust
+/// use forge_rsx::rsx;
+/// use forge_rsx::interop::IntoHtml;
+/// use forge_rsx::node::Node;
+/// use maud::{html, Markup, PreEscaped};
+///
+/// let badge: PreEscaped<String> = rsx!(lined, span { class: "badge", "New" }).into_html();
+/// let tree = Node::parse("<em>now</em>");
+/// let page: Markup = html! { h1 { "Inbox " (badge) } p { (tree) } };
+/// assert_eq!(page.into_string(), r#"<h1>Inbox <span class="badge">New</span></h1><p><em>now</em></p>"#);
+/// ```
+#[cfg(feature = "maud")]
+impl FromHtml for ::maud::PreEscaped<String> {
+    fn from_html(html: String) -> Self {
+        ::maud::PreEscaped(html)
+    }
+}
+
+/// Renders the node as markup, without escaping it again.
+#[cfg(feature = "maud")]
+impl ::maud::Render for Node {
+    fn render_to(&self, buffer: &mut String) {
+        buffer.push_str(&self.to_string());
+    }
+}
+
+#[cfg(feature = "maud")]
+impl From<Node> for ::maud::PreEscaped<String> {
+    fn from(node: Node) -> Self {
+        ::maud::PreEscaped(node.to_string())
+    }
+}
+
+/// Lets askama templates embed `rsx!` output without the `|safe` filter.
+///
+/// ```rust
+/// use forge_rsx::rsx;
+/// use forge_rsx::interop::IntoHtml;
+/// use forge_rsx::node::Node;
+/// use askama::Template;
+/// use askama::filters::Safe;
+///
+/// #[derive(Template)]
+/// #[template(source = "<h1>{{ title }} {{ badge }}</h1>{{ body }}", ext = "html")]
+/// struct Page<'a> {
+///     title: &'a str,
+///     badge: Safe<String>,
+///     body: Node,
+/// }
+///
+/// let page = Page {
+///     title: "Q&A",
+///     badge: rsx!(lined, span { class: "badge", "New" }).into_html(),
+///     body: Node::parse("<p>Ask <em>anything</em></p>"),
+/// };
+/// assert_eq!(
+///     page.render().unwrap(),
+///     r#"<h1>Q&#38;A <span class="badge">New</span></h1><p>Ask <em>anything</em></p>"#
+/// );
+/// ```
+#[cfg(feature = "askama")]
+impl FromHtml for ::askama::filters::Safe<String> {
+    fn from_html(html: String) -> Self {
+        ::askama::filters::Safe(html)
+    }
+}
+
+/// Nodes are inserted into askama templates without escaping.
+#[cfg(feature = "askama")]
+impl ::askama::filters::HtmlSafe for Node {}
+
+#[cfg(feature = "askama")]
+impl From<Node> for ::askama::filters::Safe<String> {
+    fn from(node: Node) -> Self {
+        ::askama::filters::Safe(node.to_string())
+    }
+}
@@ -0,0 +1,63 @@
+//! Calling components from [Tera](https://keats.github.io/tera/) templates.
+
+use std::collections::HashMap;
+
+use ::tera::{Function, Result, Tera, Value};
+
+/// A component exposed as a Tera function; see [`register_component`].
+///
+/// The keyword arguments of the call are passed to the component as one
+/// `tera::Value` object, and its output is marked safe so Tera's
+/// autoescaping keeps the markup intact.
+pub struct TeraComponent<F>(pub F);
+
+impl<F, R> Function for TeraComponent<F>
+where
+    F: Fn(&Value) -> R + Send + Sync,
+    R: Into<String>,
+{
+    fn call(&self, args: &HashMap<String, Value>) -> Result<Value> {
+        let props = Value::Object(args.iter().map(|(k, v)| (k.clone(), v.clone())).collect());
+        Ok(Value::String((self.0)(&props).into()))
+    }
+
+    fn is_safe(&self) -> bool {
+        true
+    }
+}
+
+/// Registers `component` as the Tera function `name`, so existing templates
+/// can call it (`{{ name(prop=value, ..) }}`) while the rest of the page is
+/// still rendered by Tera.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::{rsx, Html};
+/// use forge_rsx::interop::tera::register_component;
+/// use tera::{Context, Tera, Value};
+///
+/// fn badge(props: &Value) -> Html {
+///     let label = props["label"].as_str().unwrap_or_default();
+///     let count = props["count"].as_u64().unwrap_or(0);
+///     rsx!(lined, span { class: "badge", {label} ": " {count} })
+/// }
+///
+/// let mut tera = Tera::default();
+/// register_component(&mut tera, "badge", badge);
+/// tera.add_raw_template("page.html", r#"<h1>{{ title }} {{ badge(label="New", count=n) }}</h1>"#).unwrap();
+///
+/// let mut context = Context::new();
+/// context.insert("title", "Inbox");
+/// context.insert("n", &3);
+/// assert_eq!(
+///     tera.render("page.html", &context).unwrap(),
+///     r#"<h1>Inbox <span class="badge">New: 3</span></h1>"#
+/// );
+/// ```
+pub fn register_component<F, R>(tera: &mut Tera, name: &str, component: F)
+where
+    F: Fn(&Value) -> R + Send + Sync + 'static,
+    R: Into<String>,
+{
+    tera.register_function(name, TeraComponent(component));
+}
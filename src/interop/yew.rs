@@ -0,0 +1,65 @@
+//! Converting trees to [Yew](https://yew.rs) virtual DOM nodes.
+
+use ::yew::Html;
+use ::yew::virtual_dom::{VList, VTag, VText};
+
+use crate::node::{Node, intern, unescape};
+
+/// Converts a tree into `yew::Html`, so markup shared with server-rendered
+/// pages (e.g. `Node::parse(&rsx!(..))`) can be returned from a Yew
+/// component's view.
+///
+/// Text and attribute values are unescaped; `value` and `checked` are set as
+/// properties on form controls, like Yew's `html!` does. Comments and
+/// doctypes are dropped. Attribute names are [interned](intern), so they
+/// should come from a fixed set rather than user input.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::rsx;
+/// use forge_rsx::node::Node;
+/// use forge_rsx::interop::yew::to_yew;
+/// use yew::Html;
+///
+/// let tree = Node::parse(&rsx!(lined, ul { class: "menu", li { "Tom & Jerry" } }));
+/// let Html::VTag(ul) = to_yew(&tree) else { panic!() };
+/// assert_eq!(ul.tag(), "ul");
+/// assert_eq!(ul.attributes.iter().collect::<Vec<_>>(), [("class", "menu")]);
+///
+/// let Some(Html::VList(items)) = ul.children() else { panic!() };
+/// let Html::VTag(li) = &items[0] else { panic!() };
+/// let Some(Html::VList(text)) = li.children() else { panic!() };
+/// let Html::VText(text) = &text[0] else { panic!() };
+/// assert_eq!(&*text.text, "Tom & Jerry");
+/// ```
+pub fn to_yew(node: &Node) -> Html {
+    match node {
+        Node::Element(el) => {
+            let mut tag = VTag::new(el.tag.to_string());
+            let is_control = matches!(el.tag.as_str(), "input" | "textarea" | "select");
+            for (name, value) in &el.attrs {
+                let value = value.as_deref().map(unescape).unwrap_or_default();
+                match name.as_str() {
+                    "value" if is_control => tag.set_value(value.into_owned()),
+                    "checked" if is_control => tag.set_checked(true),
+                    name => tag.add_attribute(intern(name), value.into_owned()),
+                }
+            }
+            if el.tag == "textarea" {
+                let text: String = el.children.iter().map(|child| child.to_string()).collect();
+                tag.set_value(unescape(&text).into_owned());
+            } else {
+                let raw = el.tag == "script" || el.tag == "style";
+                tag.add_children(el.children.iter().filter_map(|child| match child {
+                    Node::Text(text) if raw => Some(VText::new(text.to_string()).into()),
+                    Node::Comment(_) | Node::Doctype(_) => None,
+                    child => Some(to_yew(child)),
+                }));
+            }
+            tag.into()
+        }
+        Node::Text(text) => VText::new(unescape(text).into_owned()).into(),
+        Node::Fragment(nodes) => VList::with_children(nodes.iter().map(to_yew).collect(), None).into(),
+        Node::Comment(_) | Node::Doctype(_) => VList::new().into(),
+    }
+}
@@ -0,0 +1,101 @@
+/// Props serialized into an island's `data-props` attribute, as JSON.
+///
+/// Implemented for already serialized JSON (`&str`, `String`), for `()` (no
+/// props, `{}`), and with the `json` feature for `serde_json::Value`, so any
+/// `Serialize` type can be passed as `serde_json::to_value(&props)?`.
+pub trait IslandProps {
+    /// Returns the props as a JSON document.
+    fn to_props_json(&self) -> String;
+}
+
+impl IslandProps for str {
+    fn to_props_json(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl IslandProps for String {
+    fn to_props_json(&self) -> String {
+        self.clone()
+    }
+}
+
+impl IslandProps for () {
+    fn to_props_json(&self) -> String {
+        "{}".to_string()
+    }
+}
+
+impl<T: IslandProps + ?Sized> IslandProps for &T {
+    fn to_props_json(&self) -> String {
+        (**self).to_props_json()
+    }
+}
+
+#[cfg(feature = "json")]
+impl IslandProps for serde_json::Value {
+    fn to_props_json(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// Wraps server-rendered `html` in a `<forge-island>` marker naming the
+/// client component and carrying its props, so a client bundler can find and
+/// hydrate only the interactive parts of the page:
+/// `document.querySelectorAll("forge-island")`, then
+/// `JSON.parse(el.dataset.props)`.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::islands::island;
+///
+/// assert_eq!(
+///     island("Counter", r#"{"start":1}"#, "<button>1</button>"),
+///     r#"<forge-island data-component="Counter" data-props="{&quot;start&quot;:1}"><button>1</button></forge-island>"#
+/// );
+/// assert_eq!(
+///     island(r#"x" onmouseover="alert(1)"#, "{}", ""),
+///     r#"<forge-island data-component="x&quot; onmouseover=&quot;alert(1)" data-props="{}"></forge-island>"#
+/// );
+/// ```
+pub fn island(component: &str, props: &(impl IslandProps + ?Sized), html: impl std::fmt::Display) -> String {
+    let escape = |value: &str| value.replace('&', "&amp;").replace('"', "&quot;");
+    format!(
+        r#"<forge-island data-component="{}" data-props="{}">{}</forge-island>"#,
+        escape(component),
+        escape(&props.to_props_json()),
+        html
+    )
+}
+
+/// Renders an island: `island!(Component, props, html)`.
+///
+/// The component name is written as an identifier; `props` is anything
+/// implementing [`IslandProps`](crate::islands::IslandProps), and `html` the
+/// server-rendered markup, usually an `rsx!` call.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::{raw, rsx, island};
+///
+/// let count = 3;
+/// let html = rsx!(lined, section {
+///     h2 { "Cart" }
+///     { raw!(island!(CartCounter, format!(r#"{{"count":{}}}"#, count), rsx!(lined, span { {count} }))) }
+/// });
+/// assert_eq!(
+///     html,
+///     concat!(
+///         "<section><h2>Cart</h2>",
+///         r#"<forge-island data-component="CartCounter" data-props="{&quot;count&quot;:3}">"#,
+///         "<span>3</span></forge-island></section>",
+///     )
+/// );
+/// ```
+#[cfg(feature = "macros")]
+#[macro_export]
+macro_rules! island {
+    ($component:ident, $props:expr, $html:expr $(,)?) => {
+        forge_rsx::islands::island(stringify!($component), &$props, $html)
+    };
+}
@@ -1,5 +1,8 @@
 #![doc = include_str!("../README.md")]
 
+// Lets the exported macros (which expand to `forge_rsx::...` paths) be used inside this crate.
+extern crate self as forge_rsx;
+
 /// ### Rules Module
 ///
 /// A module that encapsulates the rules and functionalities of the `rsx` macro.
@@ -8,7 +11,7 @@
 /// 
 /// Usage:
 /// ```rust
-/// use forge_rsx::{rsx, get_char};
+/// use forge_rsx::{raw, rsx, get_char};
 /// 
 /// fn main() {
 ///     // 1. Component defined with 'lined' (minified single line)
@@ -54,25 +57,25 @@
 ///                         span {
 ///                             {
 ///                                 if fruit == &"🍇" {
-///                                     &format!("{} {}", fruit.to_string(), "Grapes")
+///                                     format!("{} {}", fruit.to_string(), "Grapes")
 ///                                 } else if fruit == &"mango" {
-///                                     &format!("{} {}", "🥭", fruit.to_lowercase())
+///                                     format!("{} {}", "🥭", fruit.to_lowercase())
 ///                                 } else {
-///                                     &fruit.to_uppercase()
+///                                     fruit.to_uppercase()
 ///                                 }
 ///                             }
 ///                         }
 ///                     }
 ///                 }
 ///                 li { 
-///                     {"<!-- How to join RSX component -->"}
-///                     {&apple_component.to_string()} 
+///                     {raw!("<!-- How to join RSX component -->")}
+///                     {raw!(&apple_component)} 
 ///                     {
-///                         if get_char(&apple, 1).to_string() == "🍎" {
+///                         raw!(if get_char(&apple, 1).to_string() == "🍎" {
 ///                             "🍎".to_string()
 ///                         } else {
 ///                             apple_component.to_string()
-///                         }
+///                         })
 ///                     }
 ///                 }
 ///             } 
@@ -156,6 +159,255 @@
 /// - `btfy4`: uses 4 spaces indentation
 pub mod rules;
 
+pub use rules::{Style, default_style, set_default_style, with_style};
+
+/// ### Error Module
+///
+/// The crate-wide [`Error`] type returned by fallible helpers.
+pub mod error;
+
+pub use error::Error;
+
+/// ### Output Module
+///
+/// The `Html` type returned by `rsx!`: escaped markup that is inserted as is
+/// when embedded in another `rsx!` call, and the `Render` trait for
+/// components that render into their parent at its indentation.
+pub mod output;
+
+pub use output::{Html, Render};
+
+/// ### Document Module
+///
+/// A builder that assembles a complete HTML page (doctype, `html`, `head` and
+/// `body`) around a rendered component, so the scaffold is not repeated in
+/// every handler.
+#[cfg(feature = "macros")]
+pub mod document;
+
+#[cfg(feature = "macros")]
+pub use document::Document;
+
+/// ### Context Module
+///
+/// Per-thread render context that lets nested components hoist items (such as
+/// `meta`, `link` or a page title) into the document `<head>`.
+pub mod context;
+
+/// ### Theme Module
+///
+/// Design-system tokens: logical names such as `button.primary` mapped to
+/// class lists and attributes, defined once and referenced in templates.
+pub mod theme;
+
+/// ### CSS Module
+///
+/// Component-scoped styles via the `css!` macro: class selectors get a unique
+/// prefix and the rules are collected into the document's `<style>` block.
+pub mod css;
+
+/// ### Classes Module
+///
+/// Helpers for building `class` attribute values: conditional lists with
+/// `class_list!` and Tailwind-aware merging with `tw_merge!`.
+pub mod classes;
+
+/// ### Icons Module
+///
+/// Inline SVG icons that reference symbols in a sprite, plus a build-time
+/// helper that assembles the sprite from a directory of SVG files, and the
+/// favicon link set.
+#[cfg(feature = "macros")]
+pub mod icons;
+
+/// ### Images Module
+///
+/// Helpers for responsive (`picture`/`srcset`) and lazily loaded image markup.
+#[cfg(feature = "macros")]
+pub mod images;
+
+/// ### Scripts Module
+///
+/// Helpers for `script` elements, such as embedding JSON payloads safely
+/// (`json_script` requires the `json` feature).
+#[cfg(feature = "macros")]
+pub mod scripts;
+
+/// ### CSP Module
+///
+/// SHA-256 hashes of inline scripts and styles for hash-based Content
+/// Security Policies.
+pub mod csp;
+
+/// ### Widgets Module
+///
+/// Generators for accessible markup of common UI patterns, such as month
+/// calendars, tabs, accordions, disclosures, modal dialogs and sortable
+/// tables. Titles and labels are escaped as text; panels, bodies and cell
+/// contents are inserted as markup.
+#[cfg(feature = "macros")]
+pub mod widgets;
+
+/// ### Convert Module
+///
+/// Converters rendering data formats, such as CSV, JSON values, text diffs
+/// and ANSI-colored logs, as HTML (`render_json_value` requires the `json`
+/// feature).
+pub mod convert;
+
+/// ### Embed Module
+///
+/// Sandboxed `iframe` embeds of third-party content and `srcdoc` previews,
+/// with optional oEmbed lookups (the `oembed` feature).
+#[cfg(feature = "macros")]
+pub mod embed;
+
+/// ### Session Module
+///
+/// A render session sharing memoized components, merged stylesheets and an
+/// asset manifest across many pages.
+#[cfg(feature = "macros")]
+pub mod session;
+
+/// ### Files Module
+///
+/// Atomic, change-aware writing of rendered pages, for static site and
+/// report generators.
+pub mod files;
+
+/// ### Watch Module
+///
+/// File watching with debounced rebuild callbacks (requires the `notify`
+/// feature).
+#[cfg(feature = "notify")]
+pub mod watch;
+
+/// ### Preview Module
+///
+/// A development harness rendering registered component stories in
+/// isolation, with an index page of previews and markup.
+#[cfg(feature = "macros")]
+pub mod preview;
+
+/// ### Templates Module
+///
+/// `template`/`slot` helpers for web components, with a registry that can
+/// instantiate templates on the server for no-JS fallback rendering.
+#[cfg(feature = "macros")]
+pub mod templates;
+
+/// ### Fragments Module
+///
+/// A process-wide registry of fragments shared by every page (`fragment!` /
+/// `use_fragment`), rendered once and re-rendered after invalidation.
+pub mod fragments;
+
+/// ### Email Module
+///
+/// Helpers for HTML email, such as Outlook conditional comments (`msoif!`)
+/// and the plain-text alternative of a message (`render_text`).
+pub mod email;
+
+/// ### Node Module
+///
+/// A runtime HTML tree, parsed from `rsx!` output or built directly, with
+/// pretty and compact renderers.
+pub mod node;
+
+pub use node::render_canonical;
+
+/// ### Outline Module
+///
+/// Extracts the heading and ARIA landmark structure of a page as a tree, for
+/// tests and audits of page structure.
+pub mod outline;
+
+/// ### Links Module
+///
+/// Extracts the links of a page, classified as internal or external, and
+/// checks internal ones so static site builds can fail on broken links.
+pub mod links;
+
+/// ### Incremental Module
+///
+/// Cached page rendering where only components marked dirty are re-rendered.
+pub mod incremental;
+
+/// ### Events Module
+///
+/// `data-on:<event>` attribute helpers and a tiny delegation runtime for simple
+/// interactivity.
+pub mod events;
+
+/// ### Islands Module
+///
+/// Island markers (`island!`) for partial hydration of interactive components.
+pub mod islands;
+
+/// ### Print Module
+///
+/// A post-processing transform producing printable variants of rendered pages.
+pub mod print;
+
+/// ### SEO Module
+///
+/// Canonical, robots and `hreflang` head tags for the head-hoisting context.
+#[cfg(feature = "macros")]
+pub mod seo;
+
+/// ### OG Image Module
+///
+/// A constrained page profile (fixed viewport, inlined styles, no external
+/// resources) for rendering social preview images with a headless browser.
+pub mod og_image;
+
+/// ### Manifest Module
+///
+/// A PWA web app manifest builder with the matching head tags.
+#[cfg(feature = "macros")]
+pub mod manifest;
+
+/// ### Critical Module
+///
+/// Critical CSS extraction: inlines the stylesheet rules a page uses and
+/// defers loading the full stylesheet (requires the `critical` feature).
+#[cfg(feature = "critical")]
+pub mod critical;
+
+/// ### Renderer Module
+///
+/// A post-render pipeline of stages that rewrite the tree or the output (e.g.
+/// `rel="noopener"`, whitespace minification, analytics injection).
+pub mod renderer;
+
+/// ### Filters Module
+///
+/// Jinja-style filters for braced children (`{ title | upper }`), resolved
+/// against a registry of filter functions.
+pub mod filters;
+
+/// ### I18n Module
+///
+/// Locale-aware formatting, such as prices with `currency` (requires the
+/// `i18n` feature).
+#[cfg(feature = "i18n")]
+pub mod i18n;
+
+/// ### Interop Module
+///
+/// Adapters for calling forge-rsx components from other template engines
+/// (Tera and Handlebars) and for converting trees to Yew and Dioxus nodes,
+/// each behind the feature of the same name, plus the `IntoHtml` trait for
+/// handing output to the pre-escaped wrappers of maud and askama.
+pub mod interop;
+
+/// ### Validate Module
+///
+/// Checks rendered markup with a real HTML5 parser (requires the `validate`
+/// feature).
+#[cfg(feature = "validate")]
+pub mod validate;
+
 /// Returns the character at the specified 1-based index `n` from the input string `s`.
 ///
 /// If `n` exceeds the number of characters in `s`, the function returns an empty string.
@@ -185,4 +437,222 @@ pub fn get_char(s: &str, index: usize) -> String {
         // Get the char at the position
         s.chars().nth(char_index).unwrap().to_string()
     }
-}
\ No newline at end of file
+}
+
+/// Returns the character at the 1-based position `n` counted from the end of
+/// `s`: `1` is the last character.
+///
+/// If `n` is 0 or exceeds the number of characters in `s`, the function
+/// returns an empty string.
+///
+/// # Arguments
+///
+/// * `s` - A string slice from which to extract a character.
+/// * `n` - The 1-based position of the character, counted from the end.
+///
+/// # Examples
+///
+/// ```rust
+/// use forge_rsx::get_char_from_end;
+/// let s = "forge-rsx 🦀";
+/// assert_eq!(get_char_from_end(s, 1), "🦀"); // Last character
+/// assert_eq!(get_char_from_end(s, 3), "x");
+/// assert_eq!(get_char_from_end(s, 0), ""); // Out of bounds, returns empty
+/// assert_eq!(get_char_from_end(s, 50), ""); // Out of bounds, returns empty
+/// ```
+pub fn get_char_from_end(s: &str, n: usize) -> String {
+    if n == 0 {
+        return String::new();
+    }
+    s.chars().rev().nth(n - 1).map(String::from).unwrap_or_default()
+}
+
+/// Returns the character at `index`, which is 1-based like in [`get_char`]
+/// when positive and counts from the end like in [`get_char_from_end`] when
+/// negative (`-1` is the last character).
+///
+/// Index 0 and out-of-bounds indices return an empty string.
+///
+/// # Examples
+///
+/// ```rust
+/// use forge_rsx::get_char_at;
+/// let s = "Hello";
+/// assert_eq!(get_char_at(s, 1), "H");
+/// assert_eq!(get_char_at(s, -1), "o");
+/// assert_eq!(get_char_at(s, -5), "H");
+/// assert_eq!(get_char_at(s, -6), "");
+/// assert_eq!(get_char_at(s, 0), "");
+/// ```
+pub fn get_char_at(s: &str, index: isize) -> String {
+    if index < 0 {
+        get_char_from_end(s, index.unsigned_abs())
+    } else {
+        get_char(s, index as usize)
+    }
+}
+
+/// Returns the first character of `s`, or an empty string if `s` is empty.
+///
+/// # Examples
+///
+/// ```rust
+/// use forge_rsx::{rsx, first_char};
+/// let name = "Ærøskøbing";
+/// assert_eq!(first_char(name), "Æ");
+/// assert_eq!(rsx!(lined, span { class: "avatar", {first_char(name)} }), r#"<span class="avatar">Æ</span>"#);
+/// assert_eq!(first_char(""), "");
+/// ```
+pub fn first_char(s: &str) -> String {
+    s.chars().next().map(String::from).unwrap_or_default()
+}
+
+/// Returns the last character of `s`, or an empty string if `s` is empty.
+///
+/// # Examples
+///
+/// ```rust
+/// use forge_rsx::last_char;
+/// assert_eq!(last_char("🍎 Apple"), "e");
+/// assert_eq!(last_char("🍎"), "🍎");
+/// assert_eq!(last_char(""), "");
+/// ```
+pub fn last_char(s: &str) -> String {
+    s.chars().next_back().map(String::from).unwrap_or_default()
+}
+
+/// Returns the number of user-perceived characters in `s`, counting a
+/// character with its combining marks, variation selectors and
+/// zero-width-joined emoji parts as one (the same way as the `truncate`
+/// filter).
+///
+/// # Examples
+///
+/// ```rust
+/// use forge_rsx::char_count;
+/// assert_eq!(char_count("Hello"), 5);
+/// assert_eq!(char_count("cafe\u{301}"), 4); // "é" written as e + combining accent
+/// assert_eq!(char_count("👨\u{200d}👩\u{200d}👧"), 1);
+/// assert_eq!(char_count(""), 0);
+/// ```
+pub fn char_count(s: &str) -> usize {
+    filters::cluster_boundaries(s).count()
+}
+
+/// Returns the number of terminal columns `s` occupies in a monospace font:
+/// East Asian wide and fullwidth characters and emoji take two columns,
+/// combining marks and other zero-width characters none, and everything else
+/// one.
+///
+/// Useful for aligning text inside `pre { }` blocks; see also [`char_count`].
+///
+/// # Examples
+///
+/// ```rust
+/// use forge_rsx::display_width;
+/// assert_eq!(display_width("Total"), 5);
+/// assert_eq!(display_width("合計"), 4);
+/// assert_eq!(display_width("🍎 Apple"), 8);
+/// assert_eq!(display_width("cafe\u{301}"), 4);
+/// assert_eq!(display_width("👨\u{200d}👩\u{200d}👧"), 2);
+/// assert_eq!(display_width("🇸🇩"), 2);
+/// ```
+pub fn display_width(s: &str) -> usize {
+    let mut width = 0;
+    let mut joined = false;
+    let mut regional_indicator = false;
+    for c in s.chars() {
+        let w = match c as u32 {
+            _ if joined => 0,
+            // Flags are pairs of regional indicators.
+            0x1F1E6..=0x1F1FF if regional_indicator => 0,
+            0x1F1E6..=0x1F1FF => 2,
+            _ => char_width(c),
+        };
+        regional_indicator = (0x1F1E6..=0x1F1FF).contains(&(c as u32)) && !regional_indicator;
+        joined = c == '\u{200d}';
+        width += w;
+    }
+    width
+}
+
+/// Column width of a single character (see [`display_width`]).
+fn char_width(c: char) -> usize {
+    match c as u32 {
+        0..=0x1F | 0x7F..=0x9F => 0,
+        0x300..=0x36F | 0x483..=0x489 | 0x591..=0x5BD | 0x610..=0x61A | 0x64B..=0x65F | 0x1AB0..=0x1AFF
+        | 0x1DC0..=0x1DFF | 0x200B..=0x200F | 0x20D0..=0x20FF | 0xFE00..=0xFE0F | 0xFE20..=0xFE2F
+        | 0xFEFF | 0x1F3FB..=0x1F3FF | 0xE0000..=0xE007F => 0,
+        0x1100..=0x115F | 0x231A..=0x231B | 0x2329..=0x232A | 0x23E9..=0x23EC | 0x23F0 | 0x23F3
+        | 0x25FD..=0x25FE | 0x2614..=0x2615 | 0x2648..=0x2653 | 0x267F | 0x2693 | 0x26A1 | 0x26AA..=0x26AB
+        | 0x26BD..=0x26BE | 0x26C4..=0x26C5 | 0x26CE | 0x26D4 | 0x26EA | 0x26F2..=0x26F3 | 0x26F5 | 0x26FA
+        | 0x26FD | 0x2705 | 0x270A..=0x270B | 0x2728 | 0x274C | 0x274E | 0x2753..=0x2755 | 0x2757
+        | 0x2795..=0x2797 | 0x27B0 | 0x27BF | 0x2B1B..=0x2B1C | 0x2B50 | 0x2B55 | 0x2E80..=0x303E
+        | 0x3041..=0x33FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xA000..=0xA4CF | 0xA960..=0xA97F
+        | 0xAC00..=0xD7A3 | 0xF900..=0xFAFF | 0xFE10..=0xFE19 | 0xFE30..=0xFE6F | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6 | 0x16FE0..=0x18AFF | 0x1B000..=0x1B2FF | 0x1F004 | 0x1F0CF | 0x1F18E
+        | 0x1F191..=0x1F19A | 0x1F200..=0x1F251 | 0x1F300..=0x1F320 | 0x1F32D..=0x1F335
+        | 0x1F337..=0x1F37C | 0x1F37E..=0x1F393 | 0x1F3A0..=0x1F3CA | 0x1F3CF..=0x1F3D3
+        | 0x1F3E0..=0x1F3F0 | 0x1F3F4 | 0x1F3F8..=0x1F43E | 0x1F440 | 0x1F442..=0x1F4FC
+        | 0x1F4FF..=0x1F53D | 0x1F54B..=0x1F54E | 0x1F550..=0x1F567 | 0x1F57A | 0x1F595..=0x1F596
+        | 0x1F5A4 | 0x1F5FB..=0x1F64F | 0x1F680..=0x1F6C5 | 0x1F6CC | 0x1F6D0..=0x1F6D2
+        | 0x1F6D5..=0x1F6D7 | 0x1F6DC..=0x1F6DF | 0x1F6EB..=0x1F6EC | 0x1F6F4..=0x1F6FC
+        | 0x1F7E0..=0x1F7EB | 0x1F7F0 | 0x1F90C..=0x1F93A | 0x1F93C..=0x1F945 | 0x1F947..=0x1F9FF
+        | 0x1FA70..=0x1FAFF | 0x20000..=0x2FFFD | 0x30000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
+
+/// Pads `s` with spaces on the left (right-aligning it) to `width` columns,
+/// as measured by [`display_width`]. Text already as wide is returned as is.
+///
+/// Together with [`pad_right`] and [`center`], this lines up columns of
+/// plain-text content inside `pre { }` blocks, such as invoices or log views.
+///
+/// # Examples
+///
+/// ```rust
+/// use forge_rsx::{rsx, pad_left, pad_right};
+///
+/// let lines = [("Coffee", "3.50"), ("抹茶ラテ", "12.00")];
+/// let invoice: String = lines
+///     .iter()
+///     .map(|(item, price)| format!("{}{}\n", pad_right(item, 10), pad_left(price, 6)))
+///     .collect();
+/// assert_eq!(invoice, "Coffee      3.50\n抹茶ラテ   12.00\n");
+/// assert_eq!(rsx!(lined, pre { {&invoice} }), format!("<pre>{}</pre>", invoice));
+/// ```
+pub fn pad_left(s: &str, width: usize) -> String {
+    format!("{}{}", " ".repeat(width.saturating_sub(display_width(s))), s)
+}
+
+/// Pads `s` with spaces on the right (left-aligning it) to `width` columns,
+/// as measured by [`display_width`]. See [`pad_left`].
+///
+/// # Examples
+///
+/// ```rust
+/// use forge_rsx::pad_right;
+/// assert_eq!(pad_right("ab", 4), "ab  ");
+/// assert_eq!(pad_right("日本", 5), "日本 ");
+/// assert_eq!(pad_right("toolong", 3), "toolong");
+/// ```
+pub fn pad_right(s: &str, width: usize) -> String {
+    format!("{}{}", s, " ".repeat(width.saturating_sub(display_width(s))))
+}
+
+/// Centers `s` within `width` columns, as measured by [`display_width`]; an
+/// odd leftover space goes on the right. See [`pad_left`].
+///
+/// # Examples
+///
+/// ```rust
+/// use forge_rsx::center;
+/// assert_eq!(center("ab", 6), "  ab  ");
+/// assert_eq!(center("ab", 5), " ab  ");
+/// assert_eq!(center("🍎", 4), " 🍎 ");
+/// ```
+pub fn center(s: &str, width: usize) -> String {
+    let padding = width.saturating_sub(display_width(s));
+    format!("{}{}{}", " ".repeat(padding / 2), s, " ".repeat(padding - padding / 2))
+}
@@ -0,0 +1,257 @@
+use std::path::Path;
+
+use crate::node::{unescape, Node};
+
+/// Where a [`Link`] points, from its `href`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    /// A path on the same site: relative (`about`, `../blog/`) or
+    /// root-relative (`/about`), possibly with a query or fragment.
+    Internal,
+    /// A fragment of the same page (`#top`).
+    Anchor,
+    /// Another site: an `http:`/`https:` URL or a protocol-relative `//host`.
+    External,
+    /// Any other scheme, such as `mailto:`, `tel:` or `javascript:`.
+    Other,
+}
+
+/// A link found by [`extract_links`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    /// The `href`, unescaped.
+    pub href: String,
+    /// The `rel` attribute, empty without one.
+    pub rel: String,
+    /// The link text, with whitespace collapsed; images contribute their
+    /// `alt` text.
+    pub text: String,
+    /// Where the link points.
+    pub kind: LinkKind,
+}
+
+impl Link {
+    /// Returns `true` for [`LinkKind::Internal`] links.
+    pub fn is_internal(&self) -> bool {
+        self.kind == LinkKind::Internal
+    }
+
+    /// Returns `true` when `rel` contains `token` (e.g. `nofollow`).
+    pub fn has_rel(&self, token: &str) -> bool {
+        self.rel.split_ascii_whitespace().any(|t| t.eq_ignore_ascii_case(token))
+    }
+}
+
+/// Collects the links (`a` and `area` elements with an `href`) of a tree in
+/// document order, classified as internal, same-page, external or other.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::rsx;
+/// use forge_rsx::links::{extract_links, LinkKind};
+/// use forge_rsx::node::Node;
+///
+/// let page = rsx!(lined, main {
+///     a { href: "/docs/", "Read the docs" }
+///     a { href: "#install", "Install" }
+///     a { href: "https://crates.io/crates/forge-rsx", rel: "external nofollow", "crates.io" }
+///     a { href: "mailto:team@example.com", "Mail us" }
+/// });
+/// let links = extract_links(&Node::parse(&page));
+///
+/// let kinds: Vec<LinkKind> = links.iter().map(|l| l.kind).collect();
+/// assert_eq!(kinds, [LinkKind::Internal, LinkKind::Anchor, LinkKind::External, LinkKind::Other]);
+/// assert_eq!(links[0].text, "Read the docs");
+/// assert!(links[2].has_rel("nofollow"));
+/// ```
+pub fn extract_links(node: &Node) -> Vec<Link> {
+    let mut links = Vec::new();
+    collect(node, &mut links);
+    links
+}
+
+fn collect(node: &Node, links: &mut Vec<Link>) {
+    if let Some(el) = node.as_element()
+        && (el.tag == "a" || el.tag == "area")
+        && let Some(href) = el.get_attr("href")
+    {
+        let href = unescape(href.trim()).into_owned();
+        links.push(Link {
+            kind: classify(&href),
+            rel: el.get_attr("rel").unwrap_or("").trim().to_string(),
+            text: node.text_content(),
+            href,
+        });
+    }
+    for child in node.children() {
+        collect(child, links);
+    }
+}
+
+/// Classifies an `href` (see [`LinkKind`]).
+pub fn classify(href: &str) -> LinkKind {
+    if href.starts_with('#') {
+        return LinkKind::Anchor;
+    }
+    if href.starts_with("//") {
+        return LinkKind::External;
+    }
+    // A scheme is letters, digits, `+`, `-` and `.` before the first `:`,
+    // which must come before any `/`, `?` or `#`.
+    let scheme = href
+        .find([':', '/', '?', '#'])
+        .filter(|&i| href[i..].starts_with(':') && i > 0)
+        .map(|i| &href[..i])
+        .filter(|s| s.starts_with(|c: char| c.is_ascii_alphabetic()) && s.chars().all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c)));
+    match scheme {
+        Some(s) if s.eq_ignore_ascii_case("http") || s.eq_ignore_ascii_case("https") => LinkKind::External,
+        Some(_) => LinkKind::Other,
+        None => LinkKind::Internal,
+    }
+}
+
+/// Resolves an internal `href` found on the page at `page` (a site path such
+/// as `/blog/post/`) to a site path, without query or fragment.
+///
+/// ```rust
+/// use forge_rsx::links::resolve_path;
+///
+/// assert_eq!(resolve_path("/blog/post/", "../about?x=1#team"), "/blog/about");
+/// assert_eq!(resolve_path("/blog/post.html", "next.html"), "/blog/next.html");
+/// assert_eq!(resolve_path("/blog/", "/docs/"), "/docs/");
+/// ```
+pub fn resolve_path(page: &str, href: &str) -> String {
+    let href = &href[..href.find(['?', '#']).unwrap_or(href.len())];
+    let joined = if href.starts_with('/') {
+        href.to_string()
+    } else {
+        let base = &page[..page.rfind('/').map_or(0, |i| i + 1)];
+        format!("/{}{}", base.trim_start_matches('/'), href)
+    };
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in joined.split('/').skip(1) {
+        match segment {
+            "." => {}
+            ".." => {
+                segments.pop();
+            }
+            _ => segments.push(segment),
+        }
+    }
+    // `..` or `.` at the end still denote a directory.
+    let dir = joined.ends_with("/.") || joined.ends_with("/..");
+    let mut path = format!("/{}", segments.join("/"));
+    if dir && !path.ends_with('/') {
+        path.push('/');
+    }
+    path
+}
+
+/// Checks the internal links of the page at `page` against `exists`, which
+/// answers whether a resolved site path (see [`resolve_path`]) is served.
+///
+/// Returns every broken link as an [`Error::NotFound`](crate::Error::NotFound),
+/// so a static site build can stop before deploying them. Same-page anchors
+/// and external links are not checked; see [`check_links_async`] to check
+/// external links with an async client.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::rsx;
+/// use forge_rsx::links::{check_internal_links, extract_links};
+/// use forge_rsx::node::Node;
+///
+/// let page = rsx!(lined, nav { a { href: "/", "Home" } a { href: "../missing/", "Gone" } });
+/// let links = extract_links(&Node::parse(&page));
+/// let pages = ["/", "/blog/", "/blog/post/"];
+///
+/// let err = check_internal_links("/blog/post/", &links, |path| pages.contains(&path)).unwrap_err();
+/// assert_eq!(err.to_string(), "not found: link `../missing/` (/blog/missing/)");
+/// ```
+pub fn check_internal_links(page: &str, links: &[Link], exists: impl Fn(&str) -> bool) -> crate::error::Result<()> {
+    let broken: Vec<String> = links
+        .iter()
+        .filter(|link| link.is_internal())
+        .filter_map(|link| {
+            let path = resolve_path(page, &link.href);
+            (!exists(&path)).then(|| format!("link `{}` ({})", link.href, path))
+        })
+        .collect();
+    if broken.is_empty() { Ok(()) } else { Err(crate::Error::NotFound(broken)) }
+}
+
+/// Like [`check_internal_links`], with an async `exists` that also checks
+/// external links: it receives the resolved site path of each internal link
+/// and the `href` of each external one, e.g. to send a `HEAD` request with
+/// the caller's HTTP client. Links are checked one at a time, in document
+/// order, on the caller's runtime; same-page anchors and other schemes are
+/// skipped.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::rsx;
+/// use forge_rsx::links::{check_links_async, extract_links};
+/// use forge_rsx::node::Node;
+/// use std::future::Future;
+/// use std::task::{Context, Poll, Waker};
+///
+/// // Stands in for an HTTP client.
+/// async fn reachable(target: String) -> bool {
+///     target == "/" || target.starts_with("https://crates.io/")
+/// }
+///
+/// let page = rsx!(lined, p {
+///     a { href: "/", "Home" }
+///     a { href: "https://crates.io/crates/forge-rsx", "crates.io" }
+///     a { href: "https://gone.example/", "Gone" }
+/// });
+/// let links = extract_links(&Node::parse(&page));
+/// let mut check = std::pin::pin!(check_links_async("/", &links, reachable));
+///
+/// // Any executor works; this future never waits.
+/// let Poll::Ready(result) = check.as_mut().poll(&mut Context::from_waker(Waker::noop())) else { unreachable!() };
+/// assert_eq!(result.unwrap_err().to_string(), "not found: link `https://gone.example/`");
+/// ```
+pub async fn check_links_async<F, Fut>(page: &str, links: &[Link], exists: F) -> crate::error::Result<()>
+where
+    F: Fn(String) -> Fut,
+    Fut: std::future::Future<Output = bool>,
+{
+    let mut broken = Vec::new();
+    for link in links {
+        let message = match link.kind {
+            LinkKind::Internal => {
+                let path = resolve_path(page, &link.href);
+                if exists(path.clone()).await {
+                    continue;
+                }
+                format!("link `{}` ({})", link.href, path)
+            }
+            LinkKind::External => {
+                if exists(link.href.clone()).await {
+                    continue;
+                }
+                format!("link `{}`", link.href)
+            }
+            LinkKind::Anchor | LinkKind::Other => continue,
+        };
+        broken.push(message);
+    }
+    if broken.is_empty() { Ok(()) } else { Err(crate::Error::NotFound(broken)) }
+}
+
+/// Returns an `exists` function for [`check_internal_links`] that looks site
+/// paths up in a build output directory: `/about` matches `about`,
+/// `about.html` or `about/index.html` under `root`, and `/blog/` matches
+/// `blog/index.html`.
+pub fn output_dir(root: impl AsRef<Path>) -> impl Fn(&str) -> bool {
+    let root = root.as_ref().to_path_buf();
+    move |path| {
+        let relative = path.trim_start_matches('/');
+        if relative.is_empty() || path.ends_with('/') {
+            return root.join(relative).join("index.html").is_file();
+        }
+        let file = root.join(relative);
+        file.is_file() || root.join(format!("{}.html", relative)).is_file() || file.join("index.html").is_file()
+    }
+}
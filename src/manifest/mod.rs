@@ -0,0 +1,220 @@
+use std::path::Path;
+
+use crate::error::Result;
+use crate::rsx;
+
+/// How an installed web app is displayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisplayMode {
+    /// Full screen, without browser UI.
+    Fullscreen,
+    /// Its own window, like a native app.
+    #[default]
+    Standalone,
+    /// Its own window with minimal navigation controls.
+    MinimalUi,
+    /// A regular browser tab.
+    Browser,
+}
+
+impl DisplayMode {
+    /// The value used in the manifest.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DisplayMode::Fullscreen => "fullscreen",
+            DisplayMode::Standalone => "standalone",
+            DisplayMode::MinimalUi => "minimal-ui",
+            DisplayMode::Browser => "browser",
+        }
+    }
+}
+
+/// An entry of the manifest's `icons` list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestIcon {
+    /// Icon URL.
+    pub src: String,
+    /// Pixel sizes, e.g. `192x192`.
+    pub sizes: String,
+    /// MIME type, e.g. `image/png`.
+    pub mime_type: String,
+    /// Optional purpose, e.g. `maskable`.
+    pub purpose: Option<String>,
+}
+
+/// A web app manifest (`manifest.webmanifest`), which lets browsers install a
+/// site as a progressive web app.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::manifest::{DisplayMode, Manifest};
+///
+/// let manifest = Manifest::new("Forge Notes")
+///     .short_name("Notes")
+///     .theme_color("#0f172a")
+///     .display(DisplayMode::Standalone)
+///     .icon("/icon-192.png", "192x192", "image/png")
+///     .maskable_icon("/icon-512.png", "512x512", "image/png");
+///
+/// assert_eq!(
+///     manifest.to_json(),
+///     concat!(
+///         r#"{"name":"Forge Notes","short_name":"Notes","start_url":"/","display":"standalone","#,
+///         r##""theme_color":"#0f172a","icons":[{"src":"/icon-192.png","sizes":"192x192","type":"image/png"},"##,
+///         r#"{"src":"/icon-512.png","sizes":"512x512","type":"image/png","purpose":"maskable"}]}"#,
+///     )
+/// );
+/// assert_eq!(
+///     manifest.head_tags("/manifest.webmanifest"),
+///     r##"<link rel="manifest" href="/manifest.webmanifest"><meta name="theme-color" content="#0f172a">"##
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    name: String,
+    short_name: Option<String>,
+    start_url: String,
+    display: DisplayMode,
+    theme_color: Option<String>,
+    background_color: Option<String>,
+    icons: Vec<ManifestIcon>,
+}
+
+impl Manifest {
+    /// Creates a manifest for the app `name`, starting at `/` in standalone
+    /// mode.
+    pub fn new(name: impl Into<String>) -> Self {
+        Manifest {
+            name: name.into(),
+            short_name: None,
+            start_url: "/".to_string(),
+            display: DisplayMode::default(),
+            theme_color: None,
+            background_color: None,
+            icons: Vec::new(),
+        }
+    }
+
+    /// Sets the name shown where space is limited, such as under a home screen icon.
+    pub fn short_name(mut self, short_name: impl Into<String>) -> Self {
+        self.short_name = Some(short_name.into());
+        self
+    }
+
+    /// Sets the URL opened when the app is launched.
+    pub fn start_url(mut self, start_url: impl Into<String>) -> Self {
+        self.start_url = start_url.into();
+        self
+    }
+
+    /// Sets the display mode.
+    pub fn display(mut self, display: DisplayMode) -> Self {
+        self.display = display;
+        self
+    }
+
+    /// Sets the theme color, also emitted as `<meta name="theme-color">`.
+    pub fn theme_color(mut self, color: impl Into<String>) -> Self {
+        self.theme_color = Some(color.into());
+        self
+    }
+
+    /// Sets the splash screen background color.
+    pub fn background_color(mut self, color: impl Into<String>) -> Self {
+        self.background_color = Some(color.into());
+        self
+    }
+
+    /// Adds an icon.
+    pub fn icon(mut self, src: impl Into<String>, sizes: impl Into<String>, mime_type: impl Into<String>) -> Self {
+        self.icons.push(ManifestIcon { src: src.into(), sizes: sizes.into(), mime_type: mime_type.into(), purpose: None });
+        self
+    }
+
+    /// Adds an icon with `"purpose": "maskable"`, which platforms may crop to
+    /// their own shape.
+    pub fn maskable_icon(
+        mut self,
+        src: impl Into<String>,
+        sizes: impl Into<String>,
+        mime_type: impl Into<String>,
+    ) -> Self {
+        self.icons.push(ManifestIcon {
+            src: src.into(),
+            sizes: sizes.into(),
+            mime_type: mime_type.into(),
+            purpose: Some("maskable".to_string()),
+        });
+        self
+    }
+
+    /// Serializes the manifest as JSON.
+    pub fn to_json(&self) -> String {
+        let mut fields = vec![format!(r#""name":{}"#, json_string(&self.name))];
+        if let Some(short_name) = &self.short_name {
+            fields.push(format!(r#""short_name":{}"#, json_string(short_name)));
+        }
+        fields.push(format!(r#""start_url":{}"#, json_string(&self.start_url)));
+        fields.push(format!(r#""display":"{}""#, self.display.as_str()));
+        if let Some(color) = &self.theme_color {
+            fields.push(format!(r#""theme_color":{}"#, json_string(color)));
+        }
+        if let Some(color) = &self.background_color {
+            fields.push(format!(r#""background_color":{}"#, json_string(color)));
+        }
+        if !self.icons.is_empty() {
+            let icons: Vec<_> = self
+                .icons
+                .iter()
+                .map(|icon| {
+                    let purpose = icon
+                        .purpose
+                        .as_deref()
+                        .map(|p| format!(r#","purpose":{}"#, json_string(p)))
+                        .unwrap_or_default();
+                    format!(
+                        r#"{{"src":{},"sizes":{},"type":{}{}}}"#,
+                        json_string(&icon.src),
+                        json_string(&icon.sizes),
+                        json_string(&icon.mime_type),
+                        purpose
+                    )
+                })
+                .collect();
+            fields.push(format!(r#""icons":[{}]"#, icons.join(",")));
+        }
+        format!("{{{}}}", fields.join(","))
+    }
+
+    /// Writes the JSON manifest to `path`, usually `manifest.webmanifest` in
+    /// the directory of static files.
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        Ok(std::fs::write(path, self.to_json())?)
+    }
+
+    /// Renders the head tags pointing to the manifest served at `href`: the
+    /// `<link rel="manifest">` and, when set, `<meta name="theme-color">`.
+    pub fn head_tags(&self, href: &str) -> String {
+        let link = rsx!(lined, link { rel: "manifest", href: href });
+        match &self.theme_color {
+            Some(color) => format!("{}{}", link, rsx!(lined, meta { name: "theme-color", content: color })),
+            None => link.into(),
+        }
+    }
+}
+
+/// Quotes and escapes a string as a JSON string literal.
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
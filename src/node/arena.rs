@@ -0,0 +1,158 @@
+//! Arena-backed trees, enabled by the `arena` feature.
+//!
+//! An [`ArenaNode`] borrows all its strings and child lists from a
+//! [`bumpalo::Bump`], so a tree built per request is freed in one step when
+//! the arena is reset or dropped, without a call to the global allocator for
+//! each node.
+
+use super::sink::{self, HtmlSink, Markup};
+use super::{CompactOptions, Node, PrettyOptions};
+use bumpalo::Bump;
+use bumpalo::collections::Vec as BumpVec;
+use std::io;
+
+/// A node allocated in a [`Bump`] arena. It is `Copy`, and needs no drop.
+///
+/// # Examples
+/// ```rust
+/// use bumpalo::Bump;
+/// use forge_rsx::node::arena::ArenaNode;
+///
+/// let bump = Bump::new();
+/// let items = ["Home", "About"];
+/// let list = ArenaNode::element(
+///     &bump,
+///     "ul",
+///     &[("class", Some("menu"))],
+///     items.iter().map(|item| ArenaNode::element(&bump, "li", &[], [ArenaNode::text(&bump, item)])),
+/// );
+/// assert_eq!(list.to_string(), r#"<ul class="menu"><li>Home</li><li>About</li></ul>"#);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArenaNode<'a> {
+    /// An element with its attributes and children.
+    Element(&'a ArenaElement<'a>),
+    /// Text content.
+    Text(&'a str),
+    /// A comment, without the `<!--` and `-->` delimiters.
+    Comment(&'a str),
+    /// A doctype declaration, without `<!` and `>`.
+    Doctype(&'a str),
+    /// A sequence of sibling nodes without a wrapping element.
+    Fragment(&'a [ArenaNode<'a>]),
+}
+
+/// An element allocated in a [`Bump`] arena.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArenaElement<'a> {
+    /// The tag name.
+    pub tag: &'a str,
+    /// Attributes in source order; `None` marks a boolean attribute.
+    pub attrs: &'a [(&'a str, Option<&'a str>)],
+    /// Child nodes.
+    pub children: &'a [ArenaNode<'a>],
+}
+
+impl<'a> ArenaNode<'a> {
+    /// Allocates an element, copying the tag and attributes into the arena.
+    pub fn element(
+        bump: &'a Bump,
+        tag: &str,
+        attrs: &[(&str, Option<&str>)],
+        children: impl IntoIterator<Item = ArenaNode<'a>>,
+    ) -> Self {
+        let attrs = bump.alloc_slice_fill_iter(
+            attrs
+                .iter()
+                .map(|(k, v)| (&*bump.alloc_str(k), v.map(|v| &*bump.alloc_str(v)))),
+        );
+        let children = BumpVec::from_iter_in(children, bump).into_bump_slice();
+        ArenaNode::Element(bump.alloc(ArenaElement { tag: bump.alloc_str(tag), attrs, children }))
+    }
+
+    /// Allocates a text node. The text is used as markup.
+    pub fn text(bump: &'a Bump, text: &str) -> Self {
+        ArenaNode::Text(bump.alloc_str(text))
+    }
+
+    /// Allocates a fragment of sibling nodes.
+    pub fn fragment(bump: &'a Bump, nodes: impl IntoIterator<Item = ArenaNode<'a>>) -> Self {
+        ArenaNode::Fragment(BumpVec::from_iter_in(nodes, bump).into_bump_slice())
+    }
+
+    /// Copies a heap-allocated [`Node`] tree into the arena.
+    ///
+    /// ```rust
+    /// use bumpalo::Bump;
+    /// use forge_rsx::node::{arena::ArenaNode, Node};
+    ///
+    /// let bump = Bump::new();
+    /// let node = Node::parse(r#"<p class="note">Hi <b>there</b></p>"#);
+    /// let copy = ArenaNode::from_node(&bump, &node);
+    /// assert_eq!(copy.to_node(), node);
+    /// ```
+    pub fn from_node(bump: &'a Bump, node: &Node) -> Self {
+        match node {
+            Node::Element(el) => ArenaNode::element(
+                bump,
+                &el.tag,
+                &el.attrs.iter().map(|(k, v)| (k.as_str(), v.as_deref())).collect::<Vec<_>>(),
+                el.children.iter().map(|child| ArenaNode::from_node(bump, child)),
+            ),
+            Node::Text(text) => ArenaNode::Text(bump.alloc_str(text)),
+            Node::Comment(text) => ArenaNode::Comment(bump.alloc_str(text)),
+            Node::Doctype(text) => ArenaNode::Doctype(bump.alloc_str(text)),
+            Node::Fragment(nodes) => {
+                ArenaNode::fragment(bump, nodes.iter().map(|node| ArenaNode::from_node(bump, node)))
+            }
+        }
+    }
+
+    /// Copies the tree out of the arena into a heap-allocated [`Node`].
+    pub fn to_node(&self) -> Node {
+        match *self {
+            ArenaNode::Element(el) => Node::Element(super::Element {
+                tag: el.tag.into(),
+                attrs: el.attrs.iter().map(|(k, v)| ((*k).into(), v.map(Into::into))).collect(),
+                children: el.children.iter().map(ArenaNode::to_node).collect(),
+            }),
+            ArenaNode::Text(text) => Node::Text(text.into()),
+            ArenaNode::Comment(text) => Node::Comment(text.into()),
+            ArenaNode::Doctype(text) => Node::Doctype(text.into()),
+            ArenaNode::Fragment(nodes) => Node::Fragment(nodes.iter().map(ArenaNode::to_node).collect()),
+        }
+    }
+
+    /// Renders the tree on a single line, like [`Node::render_compact`].
+    pub fn render_compact(&self, options: &CompactOptions) -> String {
+        let mut out = String::new();
+        // Writing to a string cannot fail.
+        let _ = self.write_events(&mut Markup::new(&mut out, options.quote), options.collapse_whitespace, false);
+        out
+    }
+
+    /// Renders the tree with indentation, like [`Node::render_pretty`]. The
+    /// tree is copied to the heap first.
+    pub fn render_pretty(&self, options: &PrettyOptions) -> String {
+        self.to_node().render_pretty(options)
+    }
+
+    fn write_events(&self, sink: &mut impl HtmlSink, collapse_whitespace: bool, raw: bool) -> io::Result<()> {
+        match *self {
+            ArenaNode::Text(text) => sink::write_text(sink, text, collapse_whitespace, raw),
+            ArenaNode::Comment(text) => sink.write_comment(text),
+            ArenaNode::Doctype(text) => sink.write_doctype(text),
+            ArenaNode::Fragment(nodes) => nodes.iter().try_for_each(|node| node.write_events(sink, collapse_whitespace, raw)),
+            ArenaNode::Element(el) => sink::write_element(sink, el.tag, el.attrs.iter().copied(), raw, |sink, raw| {
+                el.children.iter().try_for_each(|child| child.write_events(sink, collapse_whitespace, raw))
+            }),
+        }
+    }
+}
+
+impl std::fmt::Display for ArenaNode<'_> {
+    /// Formats the node with [`ArenaNode::render_compact`] and default options.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.render_compact(&CompactOptions::default()))
+    }
+}
@@ -0,0 +1,1042 @@
+use crate::rules::is_void_element;
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::sync::{OnceLock, RwLock};
+
+#[cfg(feature = "arena")]
+pub mod arena;
+pub mod patch;
+pub mod sink;
+
+/// The string type used for names and text in the tree.
+///
+/// With the `compact_str` feature this is [`compact_str::CompactString`],
+/// which stores strings of up to 24 bytes inline, saving an allocation for
+/// most tag names, attributes and short text nodes. Otherwise it is `String`.
+#[cfg(feature = "compact_str")]
+pub type Str = compact_str::CompactString;
+/// The string type used for names and text in the tree.
+///
+/// With the `compact_str` feature this is `compact_str::CompactString`,
+/// which stores strings of up to 24 bytes inline, saving an allocation for
+/// most tag names, attributes and short text nodes. Otherwise it is `String`.
+#[cfg(not(feature = "compact_str"))]
+pub type Str = String;
+
+/// A tag or attribute name.
+///
+/// Names known to the interner (standard HTML tags and common attributes,
+/// plus anything added with [`intern`]) are stored as shared `&'static str`
+/// references, so list-heavy trees don't allocate a `"li"` or `"class"` per
+/// node. Other names are stored as an owned [`Str`].
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::node::{intern, Name};
+///
+/// assert!(Name::from("div").is_interned());
+/// assert!(!Name::from("x-widget").is_interned());
+///
+/// intern("x-widget");
+/// let name = Name::from(String::from("x-widget"));
+/// assert!(name.is_interned());
+/// assert_eq!(name, "x-widget");
+/// ```
+#[derive(Clone)]
+pub struct Name(NameRepr);
+
+#[derive(Clone)]
+enum NameRepr {
+    Static(&'static str),
+    Owned(Str),
+}
+
+impl Name {
+    /// Returns the name as a string slice.
+    pub fn as_str(&self) -> &str {
+        match &self.0 {
+            NameRepr::Static(s) => s,
+            NameRepr::Owned(s) => s,
+        }
+    }
+
+    /// Whether the name points into the interner instead of owning its text.
+    pub fn is_interned(&self) -> bool {
+        matches!(self.0, NameRepr::Static(_))
+    }
+}
+
+impl std::ops::Deref for Name {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl From<&str> for Name {
+    fn from(name: &str) -> Self {
+        match lookup(name) {
+            Some(s) => Name(NameRepr::Static(s)),
+            None => Name(NameRepr::Owned(name.into())),
+        }
+    }
+}
+
+impl From<String> for Name {
+    // A no-op conversion unless the `compact_str` feature is enabled.
+    #[allow(clippy::useless_conversion)]
+    fn from(name: String) -> Self {
+        match lookup(&name) {
+            Some(s) => Name(NameRepr::Static(s)),
+            None => Name(NameRepr::Owned(name.into())),
+        }
+    }
+}
+
+#[cfg(feature = "compact_str")]
+impl From<compact_str::CompactString> for Name {
+    fn from(name: compact_str::CompactString) -> Self {
+        match lookup(&name) {
+            Some(s) => Name(NameRepr::Static(s)),
+            None => Name(NameRepr::Owned(name)),
+        }
+    }
+}
+
+impl PartialEq for Name {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for Name {}
+
+impl PartialEq<str> for Name {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for Name {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl std::hash::Hash for Name {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl std::fmt::Debug for Name {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl std::fmt::Display for Name {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Names interned from the start.
+const KNOWN_NAMES: [&str; 162] = [
+    // Elements.
+    "a", "abbr", "address", "area", "article", "aside", "audio", "b", "base", "bdi", "bdo",
+    "blockquote", "body", "br", "button", "canvas", "caption", "cite", "code", "col", "colgroup",
+    "data", "datalist", "dd", "del", "details", "dfn", "dialog", "div", "dl", "dt", "em", "embed",
+    "fieldset", "figcaption", "figure", "footer", "form", "h1", "h2", "h3", "h4", "h5", "h6", "head",
+    "header", "hgroup", "hr", "html", "i", "iframe", "img", "input", "ins", "kbd", "label", "legend",
+    "li", "link", "main", "map", "mark", "menu", "meta", "meter", "nav", "noscript", "object", "ol",
+    "optgroup", "option", "output", "p", "picture", "pre", "progress", "q", "rp", "rt", "ruby", "s",
+    "samp", "script", "search", "section", "select", "slot", "small", "source", "span", "strong",
+    "style", "sub", "summary", "sup", "svg", "table", "tbody", "td", "template", "textarea", "tfoot",
+    "th", "thead", "time", "title", "tr", "track", "u", "ul", "var", "video", "wbr",
+    // Attributes.
+    "action", "alt", "aria-hidden", "aria-label", "async", "autocomplete", "charset", "checked",
+    "class", "cols", "colspan", "content", "crossorigin", "defer", "dir", "disabled", "download",
+    "draggable", "for", "height", "hidden", "href", "hreflang", "id", "integrity", "lang", "loading",
+    "max", "method", "min", "multiple", "name", "placeholder", "readonly", "rel", "required", "role",
+    "rows", "rowspan", "selected", "sizes", "src", "srcset", "step", "tabindex", "target", "type",
+    "value", "width",
+];
+
+fn interner() -> &'static RwLock<HashSet<&'static str>> {
+    static INTERNER: OnceLock<RwLock<HashSet<&'static str>>> = OnceLock::new();
+    INTERNER.get_or_init(|| RwLock::new(KNOWN_NAMES.into_iter().collect()))
+}
+
+fn lookup(name: &str) -> Option<&'static str> {
+    interner().read().unwrap_or_else(|e| e.into_inner()).get(name).copied()
+}
+
+/// Adds `name` to the interner and returns the shared copy, so later
+/// [`Name`]s with this text don't allocate. Intended for the fixed set of
+/// custom element and attribute names an application uses: interned names are
+/// never freed.
+pub fn intern(name: &str) -> &'static str {
+    if let Some(s) = lookup(name) {
+        return s;
+    }
+    let mut set = interner().write().unwrap_or_else(|e| e.into_inner());
+    if let Some(s) = set.get(name) {
+        return s;
+    }
+    let leaked: &'static str = Box::leak(name.to_string().into_boxed_str());
+    set.insert(leaked);
+    leaked
+}
+
+/// Elements whose content is kept verbatim by the parser and the renderers.
+pub(crate) const RAW_TEXT_ELEMENTS: [&str; 4] = ["pre", "textarea", "script", "style"];
+
+/// Elements kept on the same line as their surrounding text by
+/// [`Node::render_pretty`] unless configured otherwise.
+pub const INLINE_ELEMENTS: [&str; 22] = [
+    "a", "abbr", "b", "bdi", "bdo", "br", "cite", "code", "data", "em", "i", "img", "kbd", "label",
+    "mark", "q", "s", "small", "span", "strong", "sub", "sup",
+];
+
+/// The inline elements of [`Node::render_canonical`], frozen independently of
+/// [`INLINE_ELEMENTS`].
+const CANONICAL_INLINE_ELEMENTS: [&str; 19] = [
+    "a", "abbr", "b", "br", "cite", "code", "em", "i", "img", "kbd", "label", "mark", "q", "s", "small",
+    "span", "strong", "sub", "sup",
+];
+
+/// Parses `html` and renders it with [`Node::render_canonical`], the output
+/// format meant for golden-file tests.
+///
+/// ```rust
+/// use forge_rsx::{rsx, render_canonical};
+///
+/// let card = rsx!(lined, div { "data-id": "7", class: "card", "Hi" });
+/// assert_eq!(render_canonical(&card), "<div class=\"card\" data-id=\"7\">Hi</div>\n");
+/// ```
+pub fn render_canonical(html: &str) -> String {
+    Node::parse(html).render_canonical()
+}
+
+/// A node of an HTML tree.
+///
+/// Trees are built with [`Element`] or parsed from the markup returned by
+/// `rsx!` with [`Node::parse`], so one component can be emitted in several
+/// formats without invoking the macro again. Text, comment and attribute
+/// contents are stored as they appear in the markup, entities included.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::rsx;
+/// use forge_rsx::node::{Node, PrettyOptions, CompactOptions};
+///
+/// let html = rsx!(lined, ul { class: "menu", li { "Home" } li { a { href: "/about", "About" } } });
+/// let tree = Node::parse(&html);
+///
+/// assert_eq!(tree.render_compact(&CompactOptions::default()), html);
+/// assert_eq!(
+///     tree.render_pretty(&PrettyOptions::default()),
+///     "<ul class=\"menu\">\n  <li>Home</li>\n  <li><a href=\"/about\">About</a></li>\n</ul>"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Node {
+    /// An element with its attributes and children.
+    Element(Element),
+    /// Text content.
+    Text(Str),
+    /// A comment, without the `<!--` and `-->` delimiters.
+    Comment(Str),
+    /// A doctype declaration, without `<!` and `>` (e.g. `DOCTYPE html`).
+    Doctype(Str),
+    /// A sequence of sibling nodes without a wrapping element.
+    Fragment(Vec<Node>),
+}
+
+/// An HTML element.
+///
+/// ```rust
+/// use forge_rsx::node::{Element, Node};
+///
+/// let input: Node = Element::new("input").attr("type", "checkbox").flag("checked").into();
+/// assert_eq!(input.to_string(), r#"<input type="checkbox" checked>"#);
+///
+/// let p: Node = Element::new("p").child(Node::text("Hi")).into();
+/// assert_eq!(p.to_string(), "<p>Hi</p>");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Element {
+    /// The tag name.
+    pub tag: Name,
+    /// Attributes in source order; `None` marks a boolean attribute.
+    pub attrs: Vec<(Name, Option<Str>)>,
+    /// Child nodes.
+    pub children: Vec<Node>,
+}
+
+impl Element {
+    /// Creates an element without attributes or children.
+    pub fn new(tag: impl Into<Name>) -> Self {
+        Element { tag: tag.into(), attrs: Vec::new(), children: Vec::new() }
+    }
+
+    /// Adds an attribute with a value.
+    pub fn attr(mut self, name: impl Into<Name>, value: impl Into<Str>) -> Self {
+        self.attrs.push((name.into(), Some(value.into())));
+        self
+    }
+
+    /// Adds a boolean attribute, rendered without a value.
+    pub fn flag(mut self, name: impl Into<Name>) -> Self {
+        self.attrs.push((name.into(), None));
+        self
+    }
+
+    /// Appends a child node.
+    pub fn child(mut self, child: impl Into<Node>) -> Self {
+        self.children.push(child.into());
+        self
+    }
+
+    /// Returns the value of the first attribute called `name`. Boolean
+    /// attributes have an empty value.
+    pub fn get_attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_deref().unwrap_or(""))
+    }
+
+    fn attr_pairs(&self) -> impl Iterator<Item = (&str, Option<&str>)> {
+        self.attrs.iter().map(|(k, v)| (k.as_str(), v.as_deref()))
+    }
+
+    fn is_void(&self) -> bool {
+        is_void_element(&self.tag)
+    }
+
+    fn is_raw(&self) -> bool {
+        RAW_TEXT_ELEMENTS.contains(&self.tag.as_str())
+    }
+}
+
+impl From<Element> for Node {
+    fn from(element: Element) -> Self {
+        Node::Element(element)
+    }
+}
+
+/// The quote character used around attribute values.
+///
+/// A value containing the preferred quote is wrapped in the other one; if it
+/// contains both, the preferred quote is escaped as an entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuoteStyle {
+    /// `name="value"`, like `rsx!`.
+    #[default]
+    Double,
+    /// `name='value'`.
+    Single,
+}
+
+/// Options for [`Node::render_pretty`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrettyOptions {
+    /// Spaces per nesting level; ignored when `use_tabs` is set.
+    pub indent: usize,
+    /// Indents with one tab per level.
+    pub use_tabs: bool,
+    /// Quote character for attribute values.
+    pub quote: QuoteStyle,
+    /// Elements kept inline with surrounding text (see [`INLINE_ELEMENTS`]).
+    pub inline_elements: Vec<String>,
+    /// Per-tag formatting hooks, run in order on every matching element.
+    pub hooks: Vec<FormatHook>,
+}
+
+impl Default for PrettyOptions {
+    fn default() -> Self {
+        PrettyOptions {
+            indent: 2,
+            use_tabs: false,
+            quote: QuoteStyle::Double,
+            inline_elements: INLINE_ELEMENTS.iter().map(|s| s.to_string()).collect(),
+            hooks: Vec::new(),
+        }
+    }
+}
+
+/// Layout decisions a [`FormatHook`] can make for an element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Layout {
+    /// `Some(true)` keeps the element and its content on one line,
+    /// `Some(false)` puts each child on its own line; `None` decides as usual.
+    pub inline: Option<bool>,
+    /// Separates the element from the preceding output with a blank line.
+    pub blank_line_before: bool,
+    /// Separates the element from the following output with a blank line.
+    pub blank_line_after: bool,
+}
+
+type HookFn = dyn Fn(&mut Element, &mut Layout) + Send + Sync;
+
+/// A formatting callback consulted by [`Node::render_pretty`] for elements
+/// with a given tag (`"*"` matches every element), so a team can encode its
+/// HTML style guide once.
+///
+/// The callback receives a copy of the element, which it may rewrite (e.g.
+/// reorder children) without affecting the tree, and its [`Layout`].
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::node::{FormatHook, Node, PrettyOptions};
+///
+/// let options = PrettyOptions {
+///     hooks: vec![
+///         FormatHook::inline("td"),
+///         FormatHook::blank_line_before("section"),
+///         // Sort `meta` tags by name, keeping them before other children.
+///         FormatHook::new("head", |head, _| {
+///             head.children.sort_by_key(|child| match child.as_element() {
+///                 Some(el) if el.tag == "meta" => (0, el.get_attr("name").unwrap_or("").to_string()),
+///                 _ => (1, String::new()),
+///             });
+///         }),
+///     ],
+///     ..PrettyOptions::default()
+/// };
+///
+/// let page = Node::parse(concat!(
+///     r#"<head><meta name="viewport" content="width=device-width"><meta name="author" content="Ann"></head>"#,
+///     "<main><section><table><tr><td><p>A</p></td></tr></table></section><section></section></main>",
+/// ));
+/// assert_eq!(
+///     page.render_pretty(&options),
+///     concat!(
+///         "<head>\n",
+///         "  <meta name=\"author\" content=\"Ann\">\n",
+///         "  <meta name=\"viewport\" content=\"width=device-width\">\n",
+///         "</head>\n",
+///         "<main>\n",
+///         "\n",
+///         "  <section>\n",
+///         "    <table>\n",
+///         "      <tr>\n",
+///         "        <td><p>A</p></td>\n",
+///         "      </tr>\n",
+///         "    </table>\n",
+///         "  </section>\n",
+///         "\n",
+///         "  <section></section>\n",
+///         "</main>",
+///     )
+/// );
+/// ```
+#[derive(Clone)]
+pub struct FormatHook {
+    tag: String,
+    callback: std::sync::Arc<HookFn>,
+}
+
+impl FormatHook {
+    /// Creates a hook calling `callback` for every element named `tag`.
+    pub fn new(
+        tag: impl Into<String>,
+        callback: impl Fn(&mut Element, &mut Layout) + Send + Sync + 'static,
+    ) -> Self {
+        FormatHook { tag: tag.into(), callback: std::sync::Arc::new(callback) }
+    }
+
+    /// Always renders `tag` elements on a single line.
+    pub fn inline(tag: impl Into<String>) -> Self {
+        FormatHook::new(tag, |_, layout| layout.inline = Some(true))
+    }
+
+    /// Puts a blank line before every `tag` element.
+    pub fn blank_line_before(tag: impl Into<String>) -> Self {
+        FormatHook::new(tag, |_, layout| layout.blank_line_before = true)
+    }
+
+    fn applies_to(&self, tag: &str) -> bool {
+        self.tag == "*" || self.tag.eq_ignore_ascii_case(tag)
+    }
+}
+
+impl std::fmt::Debug for FormatHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FormatHook").field("tag", &self.tag).finish_non_exhaustive()
+    }
+}
+
+/// Hooks are equal when they share the same callback.
+impl PartialEq for FormatHook {
+    fn eq(&self, other: &Self) -> bool {
+        self.tag == other.tag && std::sync::Arc::ptr_eq(&self.callback, &other.callback)
+    }
+}
+
+impl Eq for FormatHook {}
+
+/// Options for [`Node::render_compact`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CompactOptions {
+    /// Quote character for attribute values.
+    pub quote: QuoteStyle,
+    /// Collapses runs of whitespace in text to a single space (outside `pre`,
+    /// `textarea`, `script` and `style`).
+    pub collapse_whitespace: bool,
+}
+
+impl Node {
+    /// Creates a text node. The text is used as markup, so escape it first if
+    /// it comes from user input.
+    pub fn text(text: impl Into<Str>) -> Self {
+        Node::Text(text.into())
+    }
+
+    /// Parses markup into a tree.
+    ///
+    /// Returns the single top-level node, or a [`Node::Fragment`] when the
+    /// markup has several (or none). The parser is lenient: stray closing tags
+    /// are ignored and unclosed elements are closed at the end of their parent.
+    ///
+    /// ```rust
+    /// use forge_rsx::node::Node;
+    ///
+    /// let tree = Node::parse("<!DOCTYPE html>\n<p class='x' hidden>a<br>b</p>");
+    /// let Node::Fragment(nodes) = &tree else { panic!() };
+    /// assert_eq!(nodes[0], Node::Doctype("DOCTYPE html".into()));
+    /// let Node::Element(p) = &nodes[2] else { panic!() };
+    /// assert_eq!(p.get_attr("class"), Some("x"));
+    /// assert_eq!(p.get_attr("hidden"), Some(""));
+    /// assert_eq!(p.children.len(), 3);
+    /// ```
+    pub fn parse(html: &str) -> Node {
+        let mut nodes = Parser { src: html, pos: 0 }.parse_nodes(None);
+        if nodes.len() == 1 {
+            nodes.remove(0)
+        } else {
+            Node::Fragment(nodes)
+        }
+    }
+
+    /// Returns the children of an element or fragment.
+    pub fn children(&self) -> &[Node] {
+        match self {
+            Node::Element(el) => &el.children,
+            Node::Fragment(nodes) => nodes,
+            _ => &[],
+        }
+    }
+
+    /// Returns the element if this node is one.
+    pub fn as_element(&self) -> Option<&Element> {
+        match self {
+            Node::Element(el) => Some(el),
+            _ => None,
+        }
+    }
+
+    /// Returns the text of the tree, unescaped, with whitespace runs collapsed
+    /// to single spaces; images contribute their `alt` text, as a separate
+    /// word.
+    ///
+    /// ```rust
+    /// use forge_rsx::node::Node;
+    ///
+    /// let tree = Node::parse("<h2>Tom &amp; <img alt=\"Jerry\">\n  <i>show</i></h2>");
+    /// assert_eq!(tree.text_content(), "Tom & Jerry show");
+    ///
+    /// let tree = Node::parse("<a><img alt=\"Fish &amp; chips\"><img alt=\"Q&amp;A\"></a>");
+    /// assert_eq!(tree.text_content(), "Fish & chips Q&A");
+    /// ```
+    pub fn text_content(&self) -> String {
+        fn walk(node: &Node, out: &mut String) {
+            match node {
+                Node::Text(text) => out.push_str(&unescape(text)),
+                Node::Element(el) if el.tag == "img" => {
+                    out.push(' ');
+                    out.push_str(&unescape(el.get_attr("alt").unwrap_or("")));
+                    out.push(' ');
+                }
+                _ => node.children().iter().for_each(|child| walk(child, out)),
+            }
+        }
+        let mut text = String::new();
+        walk(self, &mut text);
+        text.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+    /// Renders the tree on a single line.
+    ///
+    /// Whitespace-only text containing a line break (indentation) is dropped,
+    /// other whitespace-only text is kept as one space so inline siblings stay
+    /// separated.
+    ///
+    /// ```rust
+    /// use forge_rsx::node::{CompactOptions, Node, QuoteStyle};
+    ///
+    /// let tree = Node::parse("<div>\n  <p>One   two</p>\n  <b>a</b> <i>b</i>\n</div>");
+    /// let options = CompactOptions { quote: QuoteStyle::Single, collapse_whitespace: true };
+    /// assert_eq!(tree.render_compact(&options), "<div><p>One two</p><b>a</b> <i>b</i></div>");
+    /// ```
+    pub fn render_compact(&self, options: &CompactOptions) -> String {
+        let mut out = String::new();
+        self.write_compact(&mut out, options);
+        out
+    }
+
+    /// Renders the tree with one block-level node per line, indented by depth.
+    ///
+    /// Elements whose content is only text and inline elements stay on one
+    /// line; the content of `pre`, `textarea`, `script` and `style` is kept
+    /// verbatim.
+    ///
+    /// ```rust
+    /// use forge_rsx::node::{Node, PrettyOptions};
+    ///
+    /// let tree = Node::parse("<main><h1>Title</h1><p>Some <em>text</em></p></main>");
+    /// let options = PrettyOptions { indent: 4, ..PrettyOptions::default() };
+    /// assert_eq!(
+    ///     tree.render_pretty(&options),
+    ///     "<main>\n    <h1>Title</h1>\n    <p>Some <em>text</em></p>\n</main>"
+    /// );
+    /// ```
+    pub fn render_pretty(&self, options: &PrettyOptions) -> String {
+        let mut out = String::new();
+        self.write_pretty(&mut out, options, 0);
+        out.truncate(out.trim_end().len());
+        out
+    }
+
+    /// Renders the tree in the canonical format, whose bytes are guaranteed
+    /// not to change across versions of the crate, so rendered pages can be
+    /// committed as golden files without churn on upgrades.
+    ///
+    /// The format is [`render_pretty`](Node::render_pretty) with fixed
+    /// settings, which do not follow [`PrettyOptions::default`] or
+    /// [`INLINE_ELEMENTS`]:
+    ///
+    /// - attributes are sorted by name (duplicates keep their order) and
+    ///   values are double-quoted;
+    /// - each nesting level is indented by two spaces;
+    /// - `a`, `abbr`, `b`, `br`, `cite`, `code`, `em`, `i`, `img`, `kbd`,
+    ///   `label`, `mark`, `q`, `s`, `small`, `span`, `strong`, `sub` and
+    ///   `sup` stay inline with surrounding text;
+    /// - whitespace in text is collapsed, except inside `pre`, `textarea`,
+    ///   `script` and `style`;
+    /// - the output ends with a single line break.
+    ///
+    /// Changing any of these rules is a breaking change.
+    ///
+    /// ```rust
+    /// use forge_rsx::rsx;
+    /// use forge_rsx::node::Node;
+    ///
+    /// let page = rsx!(lined, main { id: "top", class: "page", h1 { "Title" } p { "Some " em { "text" } } });
+    /// assert_eq!(
+    ///     Node::parse(&page).render_canonical(),
+    ///     "<main class=\"page\" id=\"top\">\n  <h1>Title</h1>\n  <p>Some <em>text</em></p>\n</main>\n"
+    /// );
+    /// ```
+    pub fn render_canonical(&self) -> String {
+        let mut node = self.clone();
+        node.sort_attrs();
+        let options = PrettyOptions {
+            indent: 2,
+            use_tabs: false,
+            quote: QuoteStyle::Double,
+            inline_elements: CANONICAL_INLINE_ELEMENTS.iter().map(|s| s.to_string()).collect(),
+            hooks: Vec::new(),
+        };
+        let mut out = node.render_pretty(&options);
+        out.push('\n');
+        out
+    }
+
+    fn sort_attrs(&mut self) {
+        match self {
+            Node::Element(el) => {
+                el.attrs.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+                el.children.iter_mut().for_each(Node::sort_attrs);
+            }
+            Node::Fragment(nodes) => nodes.iter_mut().for_each(Node::sort_attrs),
+            _ => {}
+        }
+    }
+
+    fn write_compact(&self, out: &mut String, options: &CompactOptions) {
+        // Writing to a string cannot fail.
+        let _ = self.write_events(&mut sink::Markup::new(out, options.quote), options.collapse_whitespace, false);
+    }
+
+    fn write_pretty(&self, out: &mut String, options: &PrettyOptions, depth: usize) {
+        let indent = if options.use_tabs {
+            "\t".repeat(depth)
+        } else {
+            " ".repeat(options.indent * depth)
+        };
+        match self {
+            Node::Fragment(nodes) => {
+                for node in nodes {
+                    node.write_pretty(out, options, depth);
+                }
+            }
+            Node::Text(text) => {
+                let text = collapse(text.trim());
+                if !text.is_empty() {
+                    out.push_str(&indent);
+                    out.push_str(&text);
+                    out.push('\n');
+                }
+            }
+            Node::Element(el) if options.hooks.iter().any(|hook| hook.applies_to(&el.tag)) => {
+                let mut el = el.clone();
+                let mut layout = Layout::default();
+                for hook in &options.hooks {
+                    if hook.applies_to(&el.tag) {
+                        (hook.callback)(&mut el, &mut layout);
+                    }
+                }
+                if layout.blank_line_before && !out.is_empty() && !out.ends_with("\n\n") {
+                    out.push('\n');
+                }
+                write_pretty_element(&el, out, options, &indent, depth, layout.inline);
+                if layout.blank_line_after {
+                    out.push('\n');
+                }
+            }
+            Node::Element(el) => write_pretty_element(el, out, options, &indent, depth, None),
+            _ => {
+                let compact = CompactOptions { quote: options.quote, collapse_whitespace: false };
+                out.push_str(&indent);
+                out.push_str(&self.render_compact(&compact));
+                out.push('\n');
+            }
+        }
+    }
+
+    /// Whether the node can be laid out within a line of text.
+    fn is_inline(&self, options: &PrettyOptions) -> bool {
+        match self {
+            Node::Text(_) => true,
+            Node::Element(el) => el.is_inline(options),
+            _ => false,
+        }
+    }
+}
+
+impl Element {
+    fn write_compact(&self, out: &mut String, options: &CompactOptions) {
+        // Writing to a string cannot fail.
+        let _ = self.write_events(&mut sink::Markup::new(out, options.quote), options.collapse_whitespace, false);
+    }
+
+    fn is_inline(&self, options: &PrettyOptions) -> bool {
+        options.inline_elements.iter().any(|t| t.as_str() == self.tag.as_str())
+            && self.children.iter().all(|c| c.is_inline(options))
+    }
+}
+
+/// Writes an element for [`Node::render_pretty`]; `force_inline` overrides
+/// the inline/block decision.
+fn write_pretty_element(
+    el: &Element,
+    out: &mut String,
+    options: &PrettyOptions,
+    indent: &str,
+    depth: usize,
+    force_inline: Option<bool>,
+) {
+    let one_line = el.is_void() || el.is_raw() || force_inline.unwrap_or_else(|| el.is_inline(options));
+    out.push_str(indent);
+    if one_line {
+        let compact = CompactOptions { quote: options.quote, collapse_whitespace: force_inline.is_some() };
+        el.write_compact(out, &compact);
+    } else if force_inline.is_none() && el.children.iter().all(|c| c.is_inline(options)) {
+        write_open(out, &el.tag, el.attr_pairs(), options.quote);
+        out.push_str(&inline_content(&el.children, options));
+        write_close(out, &el.tag);
+    } else {
+        write_open(out, &el.tag, el.attr_pairs(), options.quote);
+        out.push('\n');
+        for child in &el.children {
+            child.write_pretty(out, options, depth + 1);
+        }
+        out.push_str(indent);
+        write_close(out, &el.tag);
+    }
+    out.push('\n');
+}
+
+impl std::fmt::Display for Node {
+    /// Formats the node with [`Node::render_compact`] and default options.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.render_compact(&CompactOptions::default()))
+    }
+}
+
+/// Renders inline children on one line, collapsing whitespace.
+fn inline_content(children: &[Node], options: &PrettyOptions) -> String {
+    let compact = CompactOptions { quote: options.quote, collapse_whitespace: true };
+    let mut out = String::new();
+    for child in children {
+        match child {
+            Node::Text(text) => out.push_str(&collapse(text)),
+            _ => child.write_compact(&mut out, &compact),
+        }
+    }
+    out.trim().to_string()
+}
+
+/// Decodes the character references of text or attribute values stored in
+/// a tree (which keeps them as they appear in the markup): the named
+/// `&amp;`, `&lt;`, `&gt;`, `&quot;`, `&apos;` and `&nbsp;`, and numeric
+/// ones. Other sequences are left as they are.
+///
+/// ```rust
+/// use forge_rsx::node::unescape;
+///
+/// assert_eq!(unescape("Tom &amp; Jerry &#8212; &lt;3 &#x1F600;"), "Tom & Jerry \u{2014} <3 \u{1F600}");
+/// assert_eq!(unescape("&copy; 2025"), "&copy; 2025");
+/// ```
+pub fn unescape(text: &str) -> Cow<'_, str> {
+    if !text.contains('&') {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let c = match &rest[1..end] {
+                "amp" => '&',
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                "nbsp" => '\u{a0}',
+                entity => {
+                    let code = match entity.strip_prefix("#x").or_else(|| entity.strip_prefix("#X")) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => entity.strip_prefix('#')?.parse().ok()?,
+                    };
+                    char::from_u32(code)?
+                }
+            };
+            Some((c, end + 1))
+        });
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    Cow::Owned(out)
+}
+
+fn collapse(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_space = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !in_space {
+                out.push(' ');
+            }
+            in_space = true;
+        } else {
+            out.push(c);
+            in_space = false;
+        }
+    }
+    out
+}
+
+fn write_open<'s>(
+    out: &mut String,
+    tag: &str,
+    attrs: impl IntoIterator<Item = (&'s str, Option<&'s str>)>,
+    quote: QuoteStyle,
+) {
+    out.push('<');
+    out.push_str(tag);
+    for (name, value) in attrs {
+        write_attr(out, name, value, quote);
+    }
+    out.push('>');
+}
+
+/// Writes ` name="value"` (or ` name` for a boolean attribute).
+fn write_attr(out: &mut String, name: &str, value: Option<&str>, quote: QuoteStyle) {
+    out.push(' ');
+    out.push_str(name);
+    if let Some(value) = value {
+        let (q, other, entity) = match quote {
+            QuoteStyle::Double => ('"', '\'', "&quot;"),
+            QuoteStyle::Single => ('\'', '"', "&#39;"),
+        };
+        out.push('=');
+        if !value.contains(q) {
+            out.push(q);
+            out.push_str(value);
+            out.push(q);
+        } else if !value.contains(other) {
+            out.push(other);
+            out.push_str(value);
+            out.push(other);
+        } else {
+            out.push(q);
+            out.push_str(&value.replace(q, entity));
+            out.push(q);
+        }
+    }
+}
+
+fn write_close(out: &mut String, tag: &str) {
+    out.push_str("</");
+    out.push_str(tag);
+    out.push('>');
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+
+    /// Parses nodes until the closing tag of `parent` (consumed) or the end of
+    /// input. A closing tag for another open ancestor is left in place.
+    fn parse_nodes(&mut self, parent: Option<&[String]>) -> Vec<Node> {
+        let mut nodes = Vec::new();
+        while self.pos < self.src.len() {
+            let rest = self.rest();
+            if let Some(after) = rest.strip_prefix("<!--") {
+                let end = after.find("-->").unwrap_or(after.len());
+                nodes.push(Node::Comment(after[..end].into()));
+                self.pos += 4 + (end + 3).min(after.len());
+            } else if let Some(name) = rest.strip_prefix("</") {
+                let end = name.find('>').unwrap_or(name.len());
+                let tag = name[..end].trim().to_ascii_lowercase();
+                let open = parent.unwrap_or(&[]);
+                if open.last() == Some(&tag) {
+                    self.pos += 2 + (end + 1).min(name.len());
+                    return nodes;
+                }
+                if open.contains(&tag) {
+                    return nodes;
+                }
+                // Stray closing tag.
+                self.pos += 2 + (end + 1).min(name.len());
+            } else if let Some(decl) = rest.strip_prefix("<!") {
+                let end = decl.find('>').unwrap_or(decl.len());
+                nodes.push(Node::Doctype(decl[..end].into()));
+                self.pos += 2 + (end + 1).min(decl.len());
+            } else if rest.starts_with('<') && rest[1..].starts_with(|c: char| c.is_ascii_alphabetic()) {
+                nodes.push(self.parse_element(parent.unwrap_or(&[])));
+            } else {
+                // Skips the first character, which may be a stray `<`.
+                let first = rest.chars().next().map_or(1, char::len_utf8);
+                let end = rest[first..].find('<').map_or(rest.len(), |i| i + first);
+                nodes.push(Node::Text(rest[..end].into()));
+                self.pos += end;
+            }
+        }
+        nodes
+    }
+
+    fn parse_element(&mut self, ancestors: &[String]) -> Node {
+        self.pos += 1;
+        let rest = self.rest();
+        let end = rest
+            .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+            .unwrap_or(rest.len());
+        let mut el = Element::new(&rest[..end]);
+        self.pos += end;
+        let self_closing = self.parse_attrs(&mut el);
+        if self_closing || el.is_void() {
+            return el.into();
+        }
+        if el.is_raw() {
+            let close = format!("</{}", el.tag.as_str().to_ascii_lowercase());
+            let rest = self.rest();
+            let end = rest.to_ascii_lowercase().find(&close).unwrap_or(rest.len());
+            if end > 0 {
+                el.children.push(Node::Text(rest[..end].into()));
+            }
+            self.pos += end;
+            let rest = self.rest();
+            self.pos += rest.find('>').map_or(rest.len(), |i| i + 1);
+            return el.into();
+        }
+        let mut open = ancestors.to_vec();
+        open.push(el.tag.as_str().to_ascii_lowercase());
+        el.children = self.parse_nodes(Some(&open));
+        el.into()
+    }
+
+    /// Parses attributes up to the end of the start tag. Returns whether the
+    /// tag was self-closing (`/>`).
+    fn parse_attrs(&mut self, el: &mut Element) -> bool {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+            if trimmed.is_empty() {
+                return false;
+            }
+            if let Some(after) = trimmed.strip_prefix("/>") {
+                self.pos = self.src.len() - after.len();
+                return true;
+            }
+            if trimmed.starts_with('>') {
+                self.pos += 1;
+                return false;
+            }
+            if trimmed.starts_with('/') {
+                self.pos += 1;
+                continue;
+            }
+            let end = trimmed
+                .find(|c: char| c.is_whitespace() || c == '=' || c == '>' || c == '/')
+                .unwrap_or(trimmed.len());
+            let name = Name::from(&trimmed[..end]);
+            self.pos += end;
+            let rest = self.rest();
+            let Some(value) = rest.trim_start().strip_prefix('=') else {
+                el.attrs.push((name, None));
+                continue;
+            };
+            let value_start = value.trim_start();
+            self.pos = self.src.len() - value_start.len();
+            let value = match value_start.chars().next() {
+                Some(q @ ('"' | '\'')) => {
+                    let body = &value_start[1..];
+                    let end = body.find(q).unwrap_or(body.len());
+                    self.pos += 1 + (end + 1).min(body.len());
+                    Str::from(&body[..end])
+                }
+                _ => {
+                    let end = value_start
+                        .find(|c: char| c.is_whitespace() || c == '>')
+                        .unwrap_or(value_start.len());
+                    self.pos += end;
+                    Str::from(&value_start[..end])
+                }
+            };
+            el.attrs.push((name, Some(value)));
+        }
+    }
+}
@@ -0,0 +1,235 @@
+//! In-place updates of a [`Node`] tree from a list of [`Patch`]es.
+
+use super::{Name, Node, Str};
+use crate::error::Result;
+
+/// A single change to a tree.
+///
+/// Nodes are addressed by their path from the root: the child indices to
+/// follow, where an empty path is the root itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Patch {
+    /// Replaces the node at `path`.
+    Replace { path: Vec<usize>, node: Node },
+    /// Inserts `node` so that it ends up at `path`, shifting later siblings.
+    Insert { path: Vec<usize>, node: Node },
+    /// Removes the node at `path`.
+    Remove { path: Vec<usize> },
+    /// Sets (or adds) an attribute of the element at `path`; `None` makes it
+    /// a boolean attribute.
+    SetAttr { path: Vec<usize>, name: Name, value: Option<Str> },
+    /// Removes an attribute from the element at `path`.
+    RemoveAttr { path: Vec<usize>, name: Name },
+    /// Replaces the text of the text node at `path`.
+    SetText { path: Vec<usize>, text: Str },
+}
+
+impl Patch {
+    /// Returns the path the patch applies to.
+    pub fn path(&self) -> &[usize] {
+        match self {
+            Patch::Replace { path, .. }
+            | Patch::Insert { path, .. }
+            | Patch::Remove { path }
+            | Patch::SetAttr { path, .. }
+            | Patch::RemoveAttr { path, .. }
+            | Patch::SetText { path, .. } => path,
+        }
+    }
+}
+
+/// Why [`Node::apply`] failed, carried by [`Error::Patch`](crate::Error::Patch).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchError {
+    /// Index of the failing patch in the applied slice.
+    pub index: usize,
+    /// Path of the failing patch.
+    pub path: Vec<usize>,
+}
+
+impl std::fmt::Display for PatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "patch {} does not match the tree at path {:?}", self.index, self.path)
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+impl Node {
+    /// Applies `patches` in order, updating the tree in place.
+    ///
+    /// Stops at the first patch whose path doesn't exist (or targets the wrong
+    /// kind of node); the patches before it stay applied.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use forge_rsx::rsx;
+    /// use forge_rsx::node::{Node, patch::Patch};
+    ///
+    /// let mut tree = Node::parse(&rsx!(lined, ul { li { "One" } li { "Two" } }));
+    /// tree.apply(&[
+    ///     Patch::SetText { path: vec![1, 0], text: "2".into() },
+    ///     Patch::Insert { path: vec![2], node: Node::parse("<li>3</li>") },
+    ///     Patch::SetAttr { path: vec![], name: "class".into(), value: Some("list".into()) },
+    /// ])
+    /// .unwrap();
+    /// assert_eq!(tree.to_string(), r#"<ul class="list"><li>One</li><li>2</li><li>3</li></ul>"#);
+    ///
+    /// let err = tree.apply(&[Patch::Remove { path: vec![7] }]).unwrap_err();
+    /// assert_eq!(err.to_string(), "patch error: patch 0 does not match the tree at path [7]");
+    /// ```
+    pub fn apply(&mut self, patches: &[Patch]) -> Result<()> {
+        for (index, patch) in patches.iter().enumerate() {
+            if apply_one(self, patch).is_none() {
+                return Err(PatchError { index, path: patch.path().to_vec() }.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Computes the patches that turn this tree into `other`, so that
+    /// `self.apply(&self.diff(other))` leaves `self == other`: for replaying
+    /// server-side changes on a client tree, or reconciling two renders.
+    ///
+    /// Text nodes are updated in place, elements of the same tag keep their
+    /// node and get attribute patches (unless the attribute order would
+    /// differ), and children are compared after skipping the unchanged ones at
+    /// both ends, so an insertion or removal in a list yields one patch.
+    /// Anything else is replaced.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use forge_rsx::rsx;
+    /// use forge_rsx::node::{Node, patch::Patch};
+    ///
+    /// let a = Node::parse(&rsx!(lined, ul { li { "One" } li { "Two" } li { "Four" } }));
+    /// let b = Node::parse(&rsx!(lined, ul { class: "done", li { "One" } li { "Two" } li { "Three" } li { "Four" } }));
+    ///
+    /// let patches = a.diff(&b);
+    /// assert_eq!(patches, [
+    ///     Patch::SetAttr { path: vec![], name: "class".into(), value: Some("done".into()) },
+    ///     Patch::Insert { path: vec![2], node: Node::parse("<li>Three</li>") },
+    /// ]);
+    ///
+    /// let mut tree = a.clone();
+    /// tree.apply(&patches).unwrap();
+    /// assert_eq!(tree, b);
+    /// assert!(b.diff(&b).is_empty());
+    /// ```
+    pub fn diff(&self, other: &Node) -> Vec<Patch> {
+        let mut patches = Vec::new();
+        diff_node(self, other, &mut Vec::new(), &mut patches);
+        patches
+    }
+
+    fn children_mut(&mut self) -> Option<&mut Vec<Node>> {
+        match self {
+            Node::Element(el) => Some(&mut el.children),
+            Node::Fragment(nodes) => Some(nodes),
+            _ => None,
+        }
+    }
+
+    fn at_mut(&mut self, path: &[usize]) -> Option<&mut Node> {
+        match path.split_first() {
+            None => Some(self),
+            Some((&i, rest)) => self.children_mut()?.get_mut(i)?.at_mut(rest),
+        }
+    }
+}
+
+fn apply_one(root: &mut Node, patch: &Patch) -> Option<()> {
+    match patch {
+        Patch::Replace { path, node } => *root.at_mut(path)? = node.clone(),
+        Patch::Insert { path, node } => {
+            let (&i, parent) = path.split_last()?;
+            let children = root.at_mut(parent)?.children_mut()?;
+            if i > children.len() {
+                return None;
+            }
+            children.insert(i, node.clone());
+        }
+        Patch::Remove { path } => {
+            let (&i, parent) = path.split_last()?;
+            let children = root.at_mut(parent)?.children_mut()?;
+            if i >= children.len() {
+                return None;
+            }
+            children.remove(i);
+        }
+        Patch::SetAttr { path, name, value } => {
+            let Node::Element(el) = root.at_mut(path)? else { return None };
+            match el.attrs.iter_mut().find(|(k, _)| k == name) {
+                Some(attr) => attr.1 = value.clone(),
+                None => el.attrs.push((name.clone(), value.clone())),
+            }
+        }
+        Patch::RemoveAttr { path, name } => {
+            let Node::Element(el) = root.at_mut(path)? else { return None };
+            el.attrs.retain(|(k, _)| k != name);
+        }
+        Patch::SetText { path, text } => {
+            let Node::Text(current) = root.at_mut(path)? else { return None };
+            *current = text.clone();
+        }
+    }
+    Some(())
+}
+
+fn diff_node(a: &Node, b: &Node, path: &mut Vec<usize>, out: &mut Vec<Patch>) {
+    if a == b {
+        return;
+    }
+    match (a, b) {
+        (Node::Text(_), Node::Text(text)) => out.push(Patch::SetText { path: path.clone(), text: text.clone() }),
+        (Node::Element(x), Node::Element(y)) if x.tag == y.tag && attrs_patchable(&x.attrs, &y.attrs) => {
+            for (name, value) in &y.attrs {
+                if !x.attrs.iter().any(|(k, v)| k == name && v == value) {
+                    out.push(Patch::SetAttr { path: path.clone(), name: name.clone(), value: value.clone() });
+                }
+            }
+            for (name, _) in &x.attrs {
+                if !y.attrs.iter().any(|(k, _)| k == name) {
+                    out.push(Patch::RemoveAttr { path: path.clone(), name: name.clone() });
+                }
+            }
+            diff_children(&x.children, &y.children, path, out);
+        }
+        (Node::Fragment(x), Node::Fragment(y)) => diff_children(x, y, path, out),
+        _ => out.push(Patch::Replace { path: path.clone(), node: b.clone() }),
+    }
+}
+
+/// Whether attribute patches turn `from` into `to`, order included: kept
+/// attributes stay in place and new ones are appended.
+fn attrs_patchable(from: &[(Name, Option<Str>)], to: &[(Name, Option<Str>)]) -> bool {
+    let unique = |attrs: &[(Name, Option<Str>)]| attrs.iter().enumerate().all(|(i, (k, _))| !attrs[..i].iter().any(|(n, _)| n == k));
+    if !unique(from) || !unique(to) {
+        return false;
+    }
+    let kept = from.iter().map(|(k, _)| k).filter(|k| to.iter().any(|(n, _)| n == *k));
+    let added = to.iter().map(|(k, _)| k).filter(|k| !from.iter().any(|(n, _)| n == *k));
+    kept.chain(added).eq(to.iter().map(|(k, _)| k))
+}
+
+fn diff_children(a: &[Node], b: &[Node], path: &mut Vec<usize>, out: &mut Vec<Patch>) {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..].iter().rev().zip(b[prefix..].iter().rev()).take_while(|(x, y)| x == y).count();
+    let (a, b) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+    let common = a.len().min(b.len());
+    for i in 0..common {
+        path.push(prefix + i);
+        diff_node(&a[i], &b[i], path, out);
+        path.pop();
+    }
+    for (i, node) in b.iter().enumerate().skip(common) {
+        path.push(prefix + i);
+        out.push(Patch::Insert { path: path.clone(), node: node.clone() });
+        path.pop();
+    }
+    for _ in common..a.len() {
+        path.push(prefix + common);
+        out.push(Patch::Remove { path: path.clone() });
+        path.pop();
+    }
+}
@@ -0,0 +1,298 @@
+//! Streaming output of trees through the [`HtmlSink`] trait.
+
+use std::io;
+
+use super::{CompactOptions, Element, Node, QuoteStyle, RAW_TEXT_ELEMENTS, collapse, write_attr};
+use crate::error::Result;
+use crate::rules::is_void_element;
+
+/// A receiver of serialization events, for custom output backends: writing
+/// straight to a socket, collecting tokens for analysis, or emitting another
+/// syntax, without post-processing a string.
+///
+/// Events arrive in document order. An element produces
+/// [`write_start_tag`](HtmlSink::write_start_tag), one
+/// [`write_attr`](HtmlSink::write_attr) per attribute,
+/// [`close_start_tag`](HtmlSink::close_start_tag), its content, and
+/// [`write_end_tag`](HtmlSink::write_end_tag) unless it is a void element.
+/// Text, comments and doctypes are passed as they appear in the markup.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::rsx;
+/// use forge_rsx::node::{Node, sink::HtmlSink};
+/// use std::io;
+///
+/// /// Collects the tags of a page.
+/// #[derive(Default)]
+/// struct Tags(Vec<String>);
+///
+/// impl HtmlSink for Tags {
+///     fn write_start_tag(&mut self, tag: &str) -> io::Result<()> {
+///         self.0.push(tag.to_string());
+///         Ok(())
+///     }
+///     fn write_attr(&mut self, _: &str, _: Option<&str>) -> io::Result<()> { Ok(()) }
+///     fn close_start_tag(&mut self, _: &str) -> io::Result<()> { Ok(()) }
+///     fn write_text(&mut self, _: &str) -> io::Result<()> { Ok(()) }
+///     fn write_end_tag(&mut self, _: &str) -> io::Result<()> { Ok(()) }
+/// }
+///
+/// let tree = Node::parse(&rsx!(lined, ul { li { "One" } li { a { href: "/", "Two" } } }));
+/// let mut tags = Tags::default();
+/// tree.write_to(&mut tags).unwrap();
+/// assert_eq!(tags.0, ["ul", "li", "li", "a"]);
+/// ```
+pub trait HtmlSink {
+    /// Opens the start tag of `tag`.
+    fn write_start_tag(&mut self, tag: &str) -> io::Result<()>;
+
+    /// Writes an attribute of the open start tag; `None` marks a boolean
+    /// attribute.
+    fn write_attr(&mut self, name: &str, value: Option<&str>) -> io::Result<()>;
+
+    /// Ends the start tag of `tag` after its attributes.
+    fn close_start_tag(&mut self, tag: &str) -> io::Result<()>;
+
+    /// Writes text content.
+    fn write_text(&mut self, text: &str) -> io::Result<()>;
+
+    /// Writes the end tag of `tag`.
+    fn write_end_tag(&mut self, tag: &str) -> io::Result<()>;
+
+    /// Writes a comment. By default, the serialized comment is passed to
+    /// [`write_text`](HtmlSink::write_text).
+    fn write_comment(&mut self, text: &str) -> io::Result<()> {
+        self.write_text(&format!("<!--{}-->", text))
+    }
+
+    /// Writes a doctype such as `DOCTYPE html`. By default, the serialized
+    /// declaration is passed to [`write_text`](HtmlSink::write_text).
+    fn write_doctype(&mut self, text: &str) -> io::Result<()> {
+        self.write_text(&format!("<!{}>", text))
+    }
+
+    /// Sets the quote character requested for the attribute values that
+    /// follow, e.g. by [`Renderer::render_to`](crate::renderer::Renderer::render_to).
+    /// Sinks that don't format attributes can ignore it, which is the default.
+    fn set_quote(&mut self, quote: QuoteStyle) {
+        let _ = quote;
+    }
+}
+
+/// Appends markup to the string, with double-quoted attributes.
+impl HtmlSink for String {
+    fn write_start_tag(&mut self, tag: &str) -> io::Result<()> {
+        Markup::new(self, QuoteStyle::Double).write_start_tag(tag)
+    }
+
+    fn write_attr(&mut self, name: &str, value: Option<&str>) -> io::Result<()> {
+        Markup::new(self, QuoteStyle::Double).write_attr(name, value)
+    }
+
+    fn close_start_tag(&mut self, tag: &str) -> io::Result<()> {
+        Markup::new(self, QuoteStyle::Double).close_start_tag(tag)
+    }
+
+    fn write_text(&mut self, text: &str) -> io::Result<()> {
+        self.push_str(text);
+        Ok(())
+    }
+
+    fn write_end_tag(&mut self, tag: &str) -> io::Result<()> {
+        Markup::new(self, QuoteStyle::Double).write_end_tag(tag)
+    }
+}
+
+/// Appends markup to a borrowed string with a chosen quote style; the sink
+/// behind [`Node::render_compact`] and [`HtmlWriter`].
+pub(super) struct Markup<'a> {
+    out: &'a mut String,
+    quote: QuoteStyle,
+}
+
+impl<'a> Markup<'a> {
+    pub(super) fn new(out: &'a mut String, quote: QuoteStyle) -> Self {
+        Markup { out, quote }
+    }
+}
+
+impl HtmlSink for Markup<'_> {
+    fn write_start_tag(&mut self, tag: &str) -> io::Result<()> {
+        self.out.push('<');
+        self.out.push_str(tag);
+        Ok(())
+    }
+
+    fn write_attr(&mut self, name: &str, value: Option<&str>) -> io::Result<()> {
+        write_attr(self.out, name, value, self.quote);
+        Ok(())
+    }
+
+    fn close_start_tag(&mut self, _tag: &str) -> io::Result<()> {
+        self.out.push('>');
+        Ok(())
+    }
+
+    fn write_text(&mut self, text: &str) -> io::Result<()> {
+        self.out.push_str(text);
+        Ok(())
+    }
+
+    fn write_end_tag(&mut self, tag: &str) -> io::Result<()> {
+        self.out.push_str("</");
+        self.out.push_str(tag);
+        self.out.push('>');
+        Ok(())
+    }
+
+    fn set_quote(&mut self, quote: QuoteStyle) {
+        self.quote = quote;
+    }
+}
+
+/// Writes markup to an [`io::Write`] such as a socket or file, without
+/// building the page in memory first.
+///
+/// ```rust
+/// use forge_rsx::node::{Node, sink::HtmlWriter};
+///
+/// let mut writer = HtmlWriter::new(Vec::new());
+/// Node::parse("<p>Hi <b>there</b></p>").write_to(&mut writer).unwrap();
+/// assert_eq!(writer.into_inner(), b"<p>Hi <b>there</b></p>");
+/// ```
+#[derive(Debug)]
+pub struct HtmlWriter<W: io::Write> {
+    inner: W,
+    buf: String,
+    quote: QuoteStyle,
+}
+
+impl<W: io::Write> HtmlWriter<W> {
+    /// Wraps a writer. Attribute values are double-quoted unless changed
+    /// with [`set_quote`](HtmlSink::set_quote).
+    pub fn new(inner: W) -> Self {
+        HtmlWriter { inner, buf: String::new(), quote: QuoteStyle::Double }
+    }
+
+    /// Returns the wrapped writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    fn emit(&mut self, f: impl FnOnce(&mut Markup) -> io::Result<()>) -> io::Result<()> {
+        self.buf.clear();
+        f(&mut Markup::new(&mut self.buf, self.quote))?;
+        self.inner.write_all(self.buf.as_bytes())
+    }
+}
+
+impl<W: io::Write> HtmlSink for HtmlWriter<W> {
+    fn write_start_tag(&mut self, tag: &str) -> io::Result<()> {
+        self.emit(|buf| buf.write_start_tag(tag))
+    }
+
+    fn write_attr(&mut self, name: &str, value: Option<&str>) -> io::Result<()> {
+        self.emit(|buf| buf.write_attr(name, value))
+    }
+
+    fn close_start_tag(&mut self, tag: &str) -> io::Result<()> {
+        self.emit(|buf| buf.close_start_tag(tag))
+    }
+
+    fn write_text(&mut self, text: &str) -> io::Result<()> {
+        self.inner.write_all(text.as_bytes())
+    }
+
+    fn write_end_tag(&mut self, tag: &str) -> io::Result<()> {
+        self.emit(|buf| buf.write_end_tag(tag))
+    }
+
+    fn set_quote(&mut self, quote: QuoteStyle) {
+        self.quote = quote;
+    }
+}
+
+impl Node {
+    /// Streams the tree to `sink`, with the whitespace handling of
+    /// [`Node::render_compact`].
+    pub fn write_to(&self, sink: &mut impl HtmlSink) -> Result<()> {
+        Ok(self.write_events(sink, false, false)?)
+    }
+
+    /// Streams the tree to `sink` like [`Node::render_compact`] with
+    /// `options`, passing the quote style to [`HtmlSink::set_quote`].
+    ///
+    /// ```rust
+    /// use forge_rsx::node::{CompactOptions, Node, QuoteStyle, sink::HtmlWriter};
+    ///
+    /// let tree = Node::parse("<p title=\"Hi\">One   two</p>");
+    /// let options = CompactOptions { quote: QuoteStyle::Single, collapse_whitespace: true };
+    /// let mut writer = HtmlWriter::new(Vec::new());
+    /// tree.write_compact_to(&mut writer, &options).unwrap();
+    /// assert_eq!(writer.into_inner(), tree.render_compact(&options).as_bytes());
+    /// ```
+    pub fn write_compact_to(&self, sink: &mut impl HtmlSink, options: &CompactOptions) -> Result<()> {
+        sink.set_quote(options.quote);
+        Ok(self.write_events(sink, options.collapse_whitespace, false)?)
+    }
+
+    /// Emits the events of the compact serialization; `collapse_whitespace`
+    /// is [`CompactOptions::collapse_whitespace`].
+    pub(super) fn write_events(&self, sink: &mut impl HtmlSink, collapse_whitespace: bool, raw: bool) -> io::Result<()> {
+        match self {
+            Node::Text(text) => write_text(sink, text, collapse_whitespace, raw),
+            Node::Comment(text) => sink.write_comment(text),
+            Node::Doctype(text) => sink.write_doctype(text),
+            Node::Fragment(nodes) => nodes.iter().try_for_each(|node| node.write_events(sink, collapse_whitespace, raw)),
+            Node::Element(el) => el.write_events(sink, collapse_whitespace, raw),
+        }
+    }
+}
+
+impl Element {
+    pub(super) fn write_events(&self, sink: &mut impl HtmlSink, collapse_whitespace: bool, raw: bool) -> io::Result<()> {
+        write_element(sink, &self.tag, self.attr_pairs(), raw, |sink, raw| {
+            self.children.iter().try_for_each(|child| child.write_events(sink, collapse_whitespace, raw))
+        })
+    }
+}
+
+/// Emits a text node. Whitespace-only text containing a line break
+/// (indentation) is dropped, other whitespace-only text becomes one space;
+/// the content of raw text elements is kept verbatim.
+pub(super) fn write_text(sink: &mut impl HtmlSink, text: &str, collapse_whitespace: bool, raw: bool) -> io::Result<()> {
+    if raw {
+        sink.write_text(text)
+    } else if text.trim().is_empty() {
+        if !text.contains('\n') && !text.is_empty() {
+            sink.write_text(" ")?;
+        }
+        Ok(())
+    } else if collapse_whitespace {
+        sink.write_text(&collapse(text))
+    } else {
+        sink.write_text(text)
+    }
+}
+
+/// Emits an element; `children` writes its content and is told whether it
+/// is inside a raw text element.
+pub(super) fn write_element<'s, S: HtmlSink>(
+    sink: &mut S,
+    tag: &str,
+    attrs: impl IntoIterator<Item = (&'s str, Option<&'s str>)>,
+    raw: bool,
+    children: impl FnOnce(&mut S, bool) -> io::Result<()>,
+) -> io::Result<()> {
+    sink.write_start_tag(tag)?;
+    for (name, value) in attrs {
+        sink.write_attr(name, value)?;
+    }
+    sink.close_start_tag(tag)?;
+    if is_void_element(tag) {
+        return Ok(());
+    }
+    children(sink, raw || RAW_TEXT_ELEMENTS.contains(&tag))?;
+    sink.write_end_tag(tag)
+}
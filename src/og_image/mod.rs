@@ -0,0 +1,239 @@
+use std::fmt::Display;
+
+use crate::error::{ParseError, Result};
+use crate::node::{Element, Node};
+
+/// The width of an Open Graph image in CSS pixels, as recommended by the
+/// major social networks.
+pub const OG_WIDTH: u32 = 1200;
+
+/// The height of an Open Graph image in CSS pixels.
+pub const OG_HEIGHT: u32 = 630;
+
+/// A page rendered for a headless-browser screenshotter to turn into a social
+/// preview (`og:image`) picture.
+///
+/// The profile fixes the viewport to the image size ([`OG_WIDTH`] x
+/// [`OG_HEIGHT`] unless changed with [`size`](OgImage::size)), clips anything
+/// outside it, and only allows self-contained markup: styles are inlined in a
+/// single `<style>` element and images, fonts and other resources must be
+/// `data:` URIs, so the screenshot never waits on (or misses) the network.
+/// [`render`](OgImage::render) checks these constraints with
+/// [`validate`](OgImage::validate).
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::rsx;
+/// use forge_rsx::og_image::OgImage;
+///
+/// let page = OgImage::new()
+///     .style(".card { display: flex; font: 64px sans-serif; }")
+///     .body(rsx!(lined, div { class: "card", h1 { "Release 1.0" } }))
+///     .render()
+///     .unwrap();
+/// assert_eq!(
+///     page,
+///     concat!(
+///         "<!DOCTYPE html>\n",
+///         r#"<html><head><meta charset="UTF-8"><meta name="viewport" content="width=1200, height=630">"#,
+///         "<style>html, body { margin: 0; width: 1200px; height: 630px; overflow: hidden; }\n",
+///         ".card { display: flex; font: 64px sans-serif; }</style></head>",
+///         r#"<body><div class="card"><h1>Release 1.0</h1></div></body></html>"#,
+///     )
+/// );
+///
+/// let err = OgImage::new()
+///     .body(rsx!(lined, img { src: "https://cdn.example/logo.png", alt: "" }))
+///     .render()
+///     .unwrap_err();
+/// assert_eq!(
+///     err.to_string(),
+///     "invalid markup: external resource `https://cdn.example/logo.png` in <img src>; embed it as a data: URI"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct OgImage {
+    width: u32,
+    height: u32,
+    styles: Vec<String>,
+    body: String,
+}
+
+impl Default for OgImage {
+    fn default() -> Self {
+        OgImage { width: OG_WIDTH, height: OG_HEIGHT, styles: Vec::new(), body: String::new() }
+    }
+}
+
+impl OgImage {
+    /// Creates an empty profile with the standard 1200 x 630 viewport.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the viewport size in CSS pixels.
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// Adds CSS to the inline `<style>` element.
+    pub fn style(mut self, css: impl Into<String>) -> Self {
+        self.styles.push(css.into());
+        self
+    }
+
+    /// Sets the rendered component placed inside `body`.
+    pub fn body(mut self, component: impl Display) -> Self {
+        self.body = component.to_string();
+        self
+    }
+
+    /// Renders the page and checks it with [`validate`](OgImage::validate).
+    pub fn render(&self) -> Result<String> {
+        let mut css = format!(
+            "html, body {{ margin: 0; width: {}px; height: {}px; overflow: hidden; }}",
+            self.width, self.height
+        );
+        for style in &self.styles {
+            css.push('\n');
+            css.push_str(style.trim());
+        }
+        let page = format!(
+            "<!DOCTYPE html>\n<html><head><meta charset=\"UTF-8\"><meta name=\"viewport\" content=\"{}\"><style>{}</style></head><body>{}</body></html>",
+            self.viewport(),
+            css,
+            self.body
+        );
+        self.validate(&page)?;
+        Ok(page)
+    }
+
+    /// Checks that a page meets the constraints of the profile:
+    ///
+    /// - a `<meta name="viewport">` fixing the width and height of the profile;
+    /// - no linked stylesheets;
+    /// - no resource (`src`, `srcset`, `poster`, `data`, `link href`, or CSS
+    ///   `url(..)` and `@import`) other than a `data:` URI or a same-document
+    ///   `#fragment`.
+    ///
+    /// Every violation is reported in an [`Error::Validation`](crate::Error::Validation).
+    pub fn validate(&self, html: &str) -> Result<()> {
+        let tree = Node::parse(html);
+        let mut errors = Vec::new();
+        let mut viewport = None;
+        check(&tree, &mut viewport, &mut errors);
+        match viewport {
+            None => errors.insert(0, error(format!("missing viewport meta; expected `{}`", self.viewport()))),
+            Some(content) if parse_viewport(&content) != Some((self.width, self.height)) => errors.insert(
+                0,
+                error(format!("viewport `{}` does not fix the size to `{}`", content, self.viewport())),
+            ),
+            Some(_) => {}
+        }
+        if errors.is_empty() { Ok(()) } else { Err(crate::Error::Validation(errors)) }
+    }
+
+    fn viewport(&self) -> String {
+        format!("width={}, height={}", self.width, self.height)
+    }
+}
+
+fn error(message: String) -> ParseError {
+    ParseError { line: 0, message }
+}
+
+/// Reads the `width` and `height` of a viewport `content` value.
+fn parse_viewport(content: &str) -> Option<(u32, u32)> {
+    let (mut width, mut height) = (None, None);
+    for part in content.split([',', ';']) {
+        let Some((key, value)) = part.split_once('=') else { continue };
+        match key.trim() {
+            "width" => width = value.trim().parse().ok(),
+            "height" => height = value.trim().parse().ok(),
+            _ => {}
+        }
+    }
+    Some((width?, height?))
+}
+
+/// A URL that does not make the browser fetch anything.
+fn is_inline(url: &str) -> bool {
+    let url = url.trim();
+    url.is_empty() || url.starts_with('#') || url.get(..5).is_some_and(|s| s.eq_ignore_ascii_case("data:"))
+}
+
+fn check(node: &Node, viewport: &mut Option<String>, errors: &mut Vec<ParseError>) {
+    if let Node::Element(el) = node {
+        check_element(el, viewport, errors);
+    }
+    for child in node.children() {
+        check(child, viewport, errors);
+    }
+}
+
+fn check_element(el: &Element, viewport: &mut Option<String>, errors: &mut Vec<ParseError>) {
+    let tag = el.tag.as_str();
+    if tag == "meta" && el.get_attr("name").is_some_and(|n| n.eq_ignore_ascii_case("viewport")) {
+        viewport.get_or_insert_with(|| el.get_attr("content").unwrap_or("").to_string());
+    }
+    if tag == "link"
+        && let Some(href) = el.get_attr("href").filter(|href| !is_inline(href))
+    {
+        let stylesheet = el.get_attr("rel").unwrap_or("").split_ascii_whitespace().any(|r| r.eq_ignore_ascii_case("stylesheet"));
+        if stylesheet {
+            errors.push(error(format!("linked stylesheet `{}`; inline its rules with `style`", href)));
+        } else {
+            errors.push(error(format!("external resource `{}` in <link href>; embed it as a data: URI", href)));
+        }
+    }
+    for attr in ["src", "poster", "data"] {
+        if attr == "data" && tag != "object" {
+            continue;
+        }
+        if let Some(url) = el.get_attr(attr).filter(|url| !is_inline(url)) {
+            errors.push(error(format!("external resource `{}` in <{} {}>; embed it as a data: URI", url, tag, attr)));
+        }
+    }
+    if let Some(srcset) = el.get_attr("srcset") {
+        // Candidates are separated by a comma after the URL or descriptor;
+        // `data:` URLs contain commas of their own.
+        let mut expect_url = true;
+        for token in srcset.split_ascii_whitespace() {
+            let url = token.trim_end_matches(',');
+            if expect_url && !is_inline(url) {
+                errors.push(error(format!("external resource `{}` in <{} srcset>; embed it as a data: URI", url, tag)));
+            }
+            expect_url = token.ends_with(',');
+        }
+    }
+    if let Some(style) = el.get_attr("style") {
+        check_css(style, tag, errors);
+    }
+    if tag == "style" {
+        for child in &el.children {
+            if let Node::Text(css) = child {
+                check_css(css, tag, errors);
+            }
+        }
+    }
+}
+
+/// Reports the `url(..)` references and `@import` rules of a stylesheet or
+/// `style` attribute found on `tag`.
+fn check_css(css: &str, tag: &str, errors: &mut Vec<ParseError>) {
+    if css.contains("@import") {
+        errors.push(error(format!("`@import` in <{}>; inline the imported rules", tag)));
+    }
+    let mut rest = css;
+    while let Some(start) = rest.find("url(") {
+        rest = &rest[start + 4..];
+        let end = rest.find(')').unwrap_or(rest.len());
+        let url = rest[..end].trim().trim_matches(|c| c == '"' || c == '\'');
+        if !is_inline(url) {
+            errors.push(error(format!("external resource `{}` in a <{}> url(); embed it as a data: URI", url, tag)));
+        }
+        rest = &rest[end..];
+    }
+}
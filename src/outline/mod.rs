@@ -0,0 +1,225 @@
+use std::fmt;
+
+use crate::node::{Element, Node};
+
+/// Landmark roles reported by [`outline`], whether explicit (`role="..."`)
+/// or implied by the element.
+pub const LANDMARK_ROLES: [&str; 8] =
+    ["banner", "complementary", "contentinfo", "form", "main", "navigation", "region", "search"];
+
+/// Elements whose descendant `header`/`footer` are not page-level landmarks.
+const SECTIONING: [&str; 5] = ["article", "aside", "main", "nav", "section"];
+
+/// What an [`OutlineItem`] stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutlineKind {
+    /// A heading, `h1` to `h6`, with its level.
+    Heading(u8),
+    /// An ARIA landmark, with its role (one of [`LANDMARK_ROLES`]).
+    Landmark(&'static str),
+}
+
+/// A heading or landmark of an [`Outline`], with the items nested in it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutlineItem {
+    /// Heading level or landmark role.
+    pub kind: OutlineKind,
+    /// The heading text, or the landmark's `aria-label` (empty without one).
+    pub label: String,
+    /// Landmarks nested in a landmark; headings of a lower rank following a
+    /// heading in the same landmark.
+    pub children: Vec<OutlineItem>,
+}
+
+/// The heading and landmark structure of a page, as returned by [`outline`].
+///
+/// Its `Display` output lists one item per line, indented by nesting, which
+/// makes a readable snapshot for tests.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Outline {
+    /// The top-level items, in document order.
+    pub items: Vec<OutlineItem>,
+}
+
+impl Outline {
+    /// Iterates over every item, depth first in document order.
+    pub fn iter(&self) -> impl Iterator<Item = &OutlineItem> {
+        let mut stack: Vec<&OutlineItem> = self.items.iter().rev().collect();
+        std::iter::from_fn(move || {
+            let item = stack.pop()?;
+            stack.extend(item.children.iter().rev());
+            Some(item)
+        })
+    }
+
+    /// Returns the headings of `level` (1 to 6) in document order.
+    pub fn headings(&self, level: u8) -> Vec<&OutlineItem> {
+        self.iter().filter(|item| item.kind == OutlineKind::Heading(level)).collect()
+    }
+
+    /// Returns the landmarks with `role` in document order.
+    pub fn landmarks(&self, role: &str) -> Vec<&OutlineItem> {
+        self.iter().filter(|item| matches!(item.kind, OutlineKind::Landmark(r) if r == role)).collect()
+    }
+
+    /// Returns the headings that skip a level after the previous heading (an
+    /// `h4` following an `h2`), a common accessibility audit failure.
+    pub fn skipped_levels(&self) -> Vec<&OutlineItem> {
+        let mut previous = 0;
+        let mut skipped = Vec::new();
+        for item in self.iter() {
+            if let OutlineKind::Heading(level) = item.kind {
+                if level > previous + 1 {
+                    skipped.push(item);
+                }
+                previous = level;
+            }
+        }
+        skipped
+    }
+}
+
+impl fmt::Display for Outline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn write_items(f: &mut fmt::Formatter<'_>, items: &[OutlineItem], depth: usize) -> fmt::Result {
+            for item in items {
+                let kind = match item.kind {
+                    OutlineKind::Heading(level) => format!("h{}", level),
+                    OutlineKind::Landmark(role) => format!("[{}]", role),
+                };
+                if item.label.is_empty() {
+                    writeln!(f, "{}{}", "  ".repeat(depth), kind)?;
+                } else {
+                    writeln!(f, "{}{} {}", "  ".repeat(depth), kind, item.label)?;
+                }
+                write_items(f, &item.children, depth + 1)?;
+            }
+            Ok(())
+        }
+        write_items(f, &self.items, 0)
+    }
+}
+
+/// Extracts the heading (`h1`-`h6`) and ARIA landmark structure of a tree,
+/// so tests and audits can check a page's structure: exactly one `h1`, a
+/// `main` landmark, no skipped heading levels.
+///
+/// Landmarks come from an explicit landmark `role` or from the element:
+/// `main`, `nav`, `aside`, `search`, `header` and `footer` (as `banner` and
+/// `contentinfo`, unless inside `article`, `aside`, `main`, `nav` or
+/// `section`), and `section` and `form` when labelled with `aria-label`,
+/// `aria-labelledby` or `title`. Landmarks nest by containment; headings nest
+/// by level within their landmark. Elements with `hidden` or
+/// `aria-hidden="true"` are skipped.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::rsx;
+/// use forge_rsx::node::Node;
+/// use forge_rsx::outline::outline;
+///
+/// let page = rsx!(lined, body {
+///     header { nav { "aria-label": "Primary", a { href: "/", "Home" } } }
+///     main {
+///         h1 { "Release notes" }
+///         h2 { "Fixes" }
+///         h2 { "Features" }
+///         h3 { "Fragments & more" }
+///     }
+///     footer { p { "© Forge" } }
+/// });
+/// let outline = outline(&Node::parse(&page));
+///
+/// assert_eq!(outline.headings(1).len(), 1);
+/// assert_eq!(outline.landmarks("main").len(), 1);
+/// assert!(outline.skipped_levels().is_empty());
+/// assert_eq!(outline.to_string(), "\
+/// [banner]
+///   [navigation] Primary
+/// [main]
+///   h1 Release notes
+///     h2 Fixes
+///     h2 Features
+///       h3 Fragments & more
+/// [contentinfo]
+/// ");
+/// ```
+pub fn outline(node: &Node) -> Outline {
+    let mut items = Vec::new();
+    collect(node, false, &mut items);
+    Outline { items }
+}
+
+/// Adds the headings and landmarks of `node` to `items`, the children of the
+/// enclosing landmark.
+fn collect(node: &Node, in_sectioning: bool, items: &mut Vec<OutlineItem>) {
+    let Node::Element(el) = node else {
+        for child in node.children() {
+            collect(child, in_sectioning, items);
+        }
+        return;
+    };
+    if el.get_attr("hidden").is_some() || el.get_attr("aria-hidden") == Some("true") {
+        return;
+    }
+    if let Some(level) = heading_level(el) {
+        let item = OutlineItem { kind: OutlineKind::Heading(level), label: node.text_content(), children: Vec::new() };
+        insert_heading(items, item);
+        return;
+    }
+    let in_sectioning_children = in_sectioning || SECTIONING.contains(&el.tag.as_str());
+    match landmark_role(el, in_sectioning) {
+        Some(role) => {
+            let mut children = Vec::new();
+            for child in &el.children {
+                collect(child, in_sectioning_children, &mut children);
+            }
+            let label = el.get_attr("aria-label").unwrap_or("").trim().to_string();
+            items.push(OutlineItem { kind: OutlineKind::Landmark(role), label, children });
+        }
+        None => {
+            for child in &el.children {
+                collect(child, in_sectioning_children, items);
+            }
+        }
+    }
+}
+
+/// Nests a heading under the last heading of a higher rank, if any.
+fn insert_heading(items: &mut Vec<OutlineItem>, item: OutlineItem) {
+    let OutlineKind::Heading(level) = item.kind else { return };
+    match items.last_mut() {
+        Some(last) if matches!(last.kind, OutlineKind::Heading(l) if l < level) => insert_heading(&mut last.children, item),
+        _ => items.push(item),
+    }
+}
+
+fn heading_level(el: &Element) -> Option<u8> {
+    match el.tag.as_str() {
+        "h1" => Some(1),
+        "h2" => Some(2),
+        "h3" => Some(3),
+        "h4" => Some(4),
+        "h5" => Some(5),
+        "h6" => Some(6),
+        _ => None,
+    }
+}
+
+fn landmark_role(el: &Element, in_sectioning: bool) -> Option<&'static str> {
+    if let Some(role) = el.get_attr("role") {
+        return LANDMARK_ROLES.iter().copied().find(|r| *r == role.trim());
+    }
+    let labelled = ["aria-label", "aria-labelledby", "title"].iter().any(|a| el.get_attr(a).is_some_and(|v| !v.trim().is_empty()));
+    match el.tag.as_str() {
+        "main" => Some("main"),
+        "nav" => Some("navigation"),
+        "aside" => Some("complementary"),
+        "search" => Some("search"),
+        "header" if !in_sectioning => Some("banner"),
+        "footer" if !in_sectioning => Some("contentinfo"),
+        "section" if labelled => Some("region"),
+        "form" if labelled => Some("form"),
+        _ => None,
+    }
+}
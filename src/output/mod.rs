@@ -0,0 +1,221 @@
+use std::fmt;
+use std::ops::Deref;
+
+use crate::rules::PreEscaped;
+
+/// Rendered markup, as returned by `rsx!`.
+///
+/// Unlike a bare `String`, an `Html` value is known to be escaped markup, so
+/// embedding it as a braced child of another `rsx!` call inserts it as is,
+/// while strings are escaped as text. It derefs to `str` and compares equal
+/// to strings holding the same markup.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::rsx;
+/// use forge_rsx::output::Html;
+///
+/// fn badge(label: &str) -> Html {
+///     rsx!(lined, span { class: "badge", {label} })
+/// }
+///
+/// let card = rsx!(lined, div { {badge("<new>")} });
+/// assert_eq!(card, r#"<div><span class="badge">&lt;new&gt;</span></div>"#);
+/// assert!(card.starts_with("<div>"));
+///
+/// let mut list = Html::new();
+/// list.push_html(&badge("a"));
+/// list.push_html(&badge("b"));
+/// assert_eq!(list.into_string(), r#"<span class="badge">a</span><span class="badge">b</span>"#);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Html(String);
+
+impl Html {
+    /// Creates empty markup.
+    pub fn new() -> Self {
+        Html(String::new())
+    }
+
+    /// Wraps markup that is known to be safe, such as trusted HTML or the
+    /// output of another renderer. Nothing is escaped.
+    pub fn pre_escaped(markup: impl Into<String>) -> Self {
+        Html(markup.into())
+    }
+
+    /// Returns the markup as a `String`.
+    pub fn into_string(self) -> String {
+        self.0
+    }
+
+    /// Returns the markup as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Appends other markup.
+    pub fn push_html(&mut self, html: &Html) {
+        self.0.push_str(&html.0);
+    }
+
+    /// Appends text, escaped with [`escape_html`](crate::rules::escape_html).
+    pub fn push_text(&mut self, text: &str) {
+        self.0.push_str(&crate::rules::escape_html(text));
+    }
+}
+
+impl FromIterator<Html> for Html {
+    fn from_iter<I: IntoIterator<Item = Html>>(iter: I) -> Self {
+        let mut html = Html::new();
+        for item in iter {
+            html.push_html(&item);
+        }
+        html
+    }
+}
+
+impl fmt::Display for Html {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Deref for Html {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Html {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<Html> for String {
+    fn from(html: Html) -> Self {
+        html.0
+    }
+}
+
+impl<T: Into<String>> From<PreEscaped<T>> for Html {
+    fn from(markup: PreEscaped<T>) -> Self {
+        Html(markup.0.into())
+    }
+}
+
+/// Markup that renders itself into a parent `rsx!` call, at the parent's
+/// indentation.
+///
+/// Braced children implementing `Render` are rendered with
+/// [`render_to`](Render::render_to) instead of being escaped as text, so a
+/// component can return `impl Render` and be embedded directly. [`Html`] and
+/// [`PreEscaped`] values are inserted as they are, indented like any other
+/// child; `rsx!(inherit, ..)` returns a [`RenderFn`] that renders in the
+/// style and at the depth of the call it is embedded in.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::{rsx, Render};
+///
+/// fn card(title: &str) -> impl Render + '_ {
+///     rsx!(inherit, div { class: "card", h2 { {title} } })
+/// }
+///
+/// assert_eq!(
+///     rsx!(btfy2, main { {card("News")} }),
+///     "<main>\n  <div class=\"card\">\n    <h2>\n      News\n    </h2>\n  </div>\n</main>"
+/// );
+/// assert_eq!(card("News").render(), r#"<div class="card"><h2>News</h2></div>"#);
+/// ```
+pub trait Render {
+    /// Appends the markup to `out`. `indent` is the indentation mode of the
+    /// parent (`Style::mode`: 0 for `lined`, 1 for `btfy0`, otherwise the
+    /// spaces per level) and `depth` the nesting level to render at.
+    fn render_to(&self, out: &mut String, indent: usize, depth: usize);
+
+    /// Renders the markup on its own, in the `lined` style.
+    fn render(&self) -> Html {
+        let mut out = String::new();
+        self.render_to(&mut out, 0, 0);
+        Html(out)
+    }
+}
+
+/// Pushes already rendered markup, indented to `depth`; empty markup adds
+/// nothing.
+fn push_indented(out: &mut String, markup: &str, indent: usize, depth: usize) {
+    if markup.is_empty() {
+        return;
+    }
+    if indent >= 2 {
+        out.push_str(&" ".repeat(indent * depth));
+    }
+    out.push_str(markup);
+}
+
+impl Render for Html {
+    fn render_to(&self, out: &mut String, indent: usize, depth: usize) {
+        push_indented(out, &self.0, indent, depth);
+    }
+}
+
+impl<T: fmt::Display> Render for PreEscaped<T> {
+    fn render_to(&self, out: &mut String, indent: usize, depth: usize) {
+        push_indented(out, &self.0.to_string(), indent, depth);
+    }
+}
+
+impl<R: Render + ?Sized> Render for &R {
+    fn render_to(&self, out: &mut String, indent: usize, depth: usize) {
+        (**self).render_to(out, indent, depth);
+    }
+}
+
+/// A [`Render`] implementation backed by a closure taking the arguments of
+/// [`render_to`](Render::render_to), as returned by `rsx!(inherit, ..)`.
+pub struct RenderFn<F>(pub F);
+
+impl<F: Fn(&mut String, usize, usize)> Render for RenderFn<F> {
+    fn render_to(&self, out: &mut String, indent: usize, depth: usize) {
+        (self.0)(out, indent, depth);
+    }
+}
+
+impl PartialEq<str> for Html {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Html {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<String> for Html {
+    fn eq(&self, other: &String) -> bool {
+        &self.0 == other
+    }
+}
+
+impl PartialEq<Html> for str {
+    fn eq(&self, other: &Html) -> bool {
+        self == other.0
+    }
+}
+
+impl PartialEq<Html> for &str {
+    fn eq(&self, other: &Html) -> bool {
+        *self == other.0
+    }
+}
+
+impl PartialEq<Html> for String {
+    fn eq(&self, other: &Html) -> bool {
+        *self == other.0
+    }
+}
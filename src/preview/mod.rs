@@ -0,0 +1,168 @@
+use std::path::Path;
+
+use crate::context;
+use crate::embed::{srcdoc_iframe, EmbedOptions};
+use crate::files::{render_to_file, WriteMode};
+use crate::rules::{with_style, Style};
+use crate::{raw, rsx, Document, Html};
+
+struct Story {
+    component: String,
+    name: String,
+    render: Box<dyn Fn() -> String>,
+}
+
+impl Story {
+    fn id(&self) -> String {
+        format!("{}-{}", slug(&self.component), slug(&self.name))
+    }
+}
+
+/// Lowercases `text` and replaces runs of other characters than ASCII
+/// letters and digits with `-`.
+fn slug(text: &str) -> String {
+    let mut out = String::new();
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() {
+            out.push(c.to_ascii_lowercase());
+        } else if !out.is_empty() && !out.ends_with('-') {
+            out.push('-');
+        }
+    }
+    out.trim_end_matches('-').to_string()
+}
+
+/// A development harness showing components in isolation, like a minimal
+/// Storybook for server-rendered components.
+///
+/// Each story is a closure rendering a component with example props. The
+/// index page lists every story with a live preview in a sandboxed `srcdoc`
+/// frame and its markup at each of the configured [`Style`]s (components
+/// rendering with `rsx!(default, ..)` follow it). Serve
+/// [`render_index`](Stories::render_index) and
+/// [`render_story`](Stories::render_story) from dev-only routes, or write
+/// everything to a directory with [`write_static`](Stories::write_static).
+///
+/// Stories render one at a time after a [`context::reset_page`], so items
+/// they hoist to `<head>` end up in their own page, while a theme or locale
+/// set beforehand applies to every story.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::{rsx, Html, Style};
+/// use forge_rsx::preview::Stories;
+///
+/// fn button(label: &str, primary: bool) -> Html {
+///     rsx!(default, button { class: ["btn", ("btn-primary", primary)], {label} })
+/// }
+///
+/// let stories = Stories::new()
+///     .styles(&[Style::Lined, Style::Btfy2])
+///     .add("Button", "Primary", || button("Save", true))
+///     .add("Button", "Plain", || button("Cancel", false));
+///
+/// let index = stories.render_index();
+/// assert!(index.contains(r##"<a href="#button-primary">Button / Primary</a>"##));
+/// assert!(index.contains(r#"<iframe srcdoc="&lt;!DOCTYPE html&gt;"#));
+/// assert!(index.contains("&lt;button class=&quot;btn btn-primary&quot;&gt;Save&lt;/button&gt;"));
+///
+/// let page = stories.render_story("Button", "Plain").unwrap();
+/// assert!(page.contains(r#"<body><button class="btn">Cancel</button></body>"#));
+/// ```
+pub struct Stories {
+    stories: Vec<Story>,
+    styles: Vec<Style>,
+}
+
+impl Default for Stories {
+    fn default() -> Self {
+        Stories { stories: Vec::new(), styles: vec![Style::Lined, Style::Btfy2] }
+    }
+}
+
+impl Stories {
+    /// Creates an empty harness showing markup in the `lined` and `btfy2`
+    /// styles.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the styles the markup of each story is shown in.
+    pub fn styles(mut self, styles: &[Style]) -> Self {
+        self.styles = styles.to_vec();
+        self
+    }
+
+    /// Registers the story `name` of `component`.
+    pub fn add<R: Into<String>>(mut self, component: &str, name: &str, render: impl Fn() -> R + 'static) -> Self {
+        let render = Box::new(move || render().into());
+        self.stories.push(Story { component: component.to_string(), name: name.to_string(), render });
+        self
+    }
+
+    /// Renders a story as a standalone page (compact, `lined` markup).
+    pub fn render_story(&self, component: &str, name: &str) -> Option<String> {
+        let story = self.stories.iter().find(|s| s.component == component && s.name == name)?;
+        Some(self.story_page(story))
+    }
+
+    fn story_page(&self, story: &Story) -> String {
+        context::reset_page();
+        let body = with_style(Style::Lined, &story.render);
+        Document::new()
+            .title(format!("{} / {}", story.component, story.name))
+            .body(body)
+            .render(Style::Lined)
+    }
+
+    /// Renders the index page: a table of contents, then one section per
+    /// story with its preview and markup.
+    pub fn render_index(&self) -> String {
+        let sections: Html = self
+            .stories
+            .iter()
+            .map(|story| {
+                let id = story.id();
+                let preview = srcdoc_iframe(self.story_page(story), &EmbedOptions::new(format!("{} preview", story.name)).strict_sandbox().eager());
+                let sources: Html = self
+                    .styles
+                    .iter()
+                    .map(|style| {
+                        context::reset_page();
+                        let html = with_style(*style, &story.render);
+                        rsx!(lined, details { summary { {format!("{:?}", style).to_lowercase()} } pre { code { {html} } } })
+                    })
+                    .collect();
+                rsx!(lined, section {
+                    id: &id,
+                    class: "story",
+                    h2 { {&story.component} " " small { {&story.name} } }
+                    {raw!(preview)}
+                    p { a { href: format!("{}.html", id), "Open in isolation" } }
+                    {sources}
+                })
+            })
+            .collect();
+        context::reset_page();
+        Document::new()
+            .title("Component previews")
+            .body(format!("{}{}", rsx!(lined, nav {
+                ul {
+                    for story in &self.stories => {
+                        li { a { href: format!("#{}", story.id()), {format!("{} / {}", story.component, story.name)} } }
+                    }
+                }
+            }), rsx!(lined, main { {sections} })))
+            .render(Style::Lined)
+    }
+
+    /// Writes `index.html` and one `<component>-<story>.html` page per story
+    /// to `dir`, creating it if needed. Unchanged pages are not rewritten.
+    pub fn write_static(&self, dir: impl AsRef<Path>) -> crate::error::Result<()> {
+        let dir = dir.as_ref();
+        for story in &self.stories {
+            render_to_file(dir.join(format!("{}.html", story.id())), self.story_page(story), WriteMode::IfChanged)?;
+        }
+        render_to_file(dir.join("index.html"), self.render_index(), WriteMode::IfChanged).map(|_| ())
+    }
+}
@@ -0,0 +1,78 @@
+use crate::node::{Element, Node};
+use crate::renderer::append_to_head;
+
+/// The stylesheet injected by [`printable`].
+pub const PRINT_CSS: &str = "body{font:12pt/1.5 Georgia,serif;color:#000;background:#fff}a{color:inherit;text-decoration:none}.print-href{font-size:90%}img{max-width:100%}";
+
+/// Produces a printable variant of a rendered page or fragment.
+///
+/// - `nav` and `button` elements, and every element marked `data-noprint`,
+///   are removed.
+/// - Links to other pages get their target appended as visible text,
+///   `<span class="print-href"> (https://..)</span>`; in-page (`#..`) and
+///   `javascript:` links are left alone.
+/// - [`PRINT_CSS`] is added at the end of `<head>`, or before the markup when
+///   it has no `head`.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::rsx;
+/// use forge_rsx::print::{printable, PRINT_CSS};
+///
+/// let page = rsx!(lined, html {
+///     head { title { "Invoice" } }
+///     body {
+///         nav { a { href: "/", "Home" } }
+///         p { "Paid via " a { href: "https://pay.example", "Pay" } "." }
+///         button { "Print" }
+///         div { "data-noprint": "", "Ad" }
+///     }
+/// });
+/// assert_eq!(
+///     printable(&page),
+///     format!(
+///         r#"<html><head><title>Invoice</title><style>{}</style></head><body><p>Paid via <a href="https://pay.example">Pay</a><span class="print-href"> (https://pay.example)</span>.</p></body></html>"#,
+///         PRINT_CSS
+///     )
+/// );
+/// ```
+pub fn printable(html: &str) -> String {
+    let mut tree = Node::parse(html);
+    transform(&mut tree);
+    let style = Node::from(Element::new("style").child(Node::text(PRINT_CSS)));
+    if !append_to_head(&mut tree, std::slice::from_ref(&style)) {
+        tree = Node::Fragment(vec![style, tree]);
+    }
+    tree.to_string()
+}
+
+fn transform(node: &mut Node) {
+    let children = match node {
+        Node::Element(el) => &mut el.children,
+        Node::Fragment(nodes) => nodes,
+        _ => return,
+    };
+    children.retain(|child| match child {
+        Node::Element(el) => el.tag != "nav" && el.tag != "button" && el.get_attr("data-noprint").is_none(),
+        _ => true,
+    });
+    let mut i = 0;
+    while i < children.len() {
+        transform(&mut children[i]);
+        let href = children[i]
+            .as_element()
+            .filter(|el| el.tag == "a")
+            .and_then(|el| el.get_attr("href"))
+            .filter(|href| !href.is_empty() && !href.starts_with('#') && !href.starts_with("javascript:"))
+            .map(str::to_string);
+        if let Some(href) = href {
+            let note = Element::new("span")
+                .attr("class", "print-href")
+                .child(Node::text(format!(" ({})", href)));
+            children.insert(i + 1, note.into());
+            i += 1;
+        }
+        i += 1;
+    }
+}
+
@@ -0,0 +1,263 @@
+use crate::node::sink::HtmlSink;
+use crate::node::{CompactOptions, Node, PrettyOptions, RAW_TEXT_ELEMENTS, Str};
+
+/// A step of a [`Renderer`] pipeline.
+///
+/// A stage can rewrite the tree before it is serialized, the output string
+/// afterwards, or both; the default methods leave their input unchanged.
+/// Closures taking `&mut Node` are stages too.
+pub trait Stage {
+    /// Rewrites the tree before rendering.
+    fn transform_node(&self, node: &mut Node) {
+        let _ = node;
+    }
+
+    /// Rewrites the rendered markup.
+    fn transform_html(&self, html: String) -> String {
+        html
+    }
+}
+
+impl<F: Fn(&mut Node)> Stage for F {
+    fn transform_node(&self, node: &mut Node) {
+        self(node)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Format {
+    Compact(CompactOptions),
+    Pretty(PrettyOptions),
+}
+
+/// A post-render pipeline that applies cross-cutting transformations to
+/// every page, instead of repeating them in each template.
+///
+/// The tree goes through the [`transform_node`](Stage::transform_node) of
+/// every stage in the order they were added, is rendered (compact by
+/// default), then goes through every [`transform_html`](Stage::transform_html).
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::rsx;
+/// use forge_rsx::node::Node;
+/// use forge_rsx::renderer::{AddNoopener, InjectAnalytics, MinifyWhitespace, Renderer};
+///
+/// let renderer = Renderer::new()
+///     .with(AddNoopener)
+///     .with(MinifyWhitespace)
+///     .with(InjectAnalytics(r#"<script src="/a.js" defer></script>"#.to_string()));
+///
+/// let page = rsx!(btfy2, body { a { href: "https://example.com", target: "_blank", "Out" } });
+/// assert_eq!(
+///     renderer.render(Node::parse(&page)),
+///     concat!(
+///         r#"<body><a href="https://example.com" target="_blank" rel="noopener">Out</a>"#,
+///         r#"<script src="/a.js" defer></script></body>"#,
+///     )
+/// );
+/// ```
+pub struct Renderer {
+    stages: Vec<Box<dyn Stage>>,
+    format: Format,
+}
+
+impl Default for Renderer {
+    fn default() -> Self {
+        Renderer { stages: Vec::new(), format: Format::Compact(CompactOptions::default()) }
+    }
+}
+
+impl Renderer {
+    /// Creates an empty pipeline rendering with default compact options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a stage.
+    pub fn with(mut self, stage: impl Stage + 'static) -> Self {
+        self.stages.push(Box::new(stage));
+        self
+    }
+
+    /// Renders with [`Node::render_compact`] and `options`.
+    pub fn compact(mut self, options: CompactOptions) -> Self {
+        self.format = Format::Compact(options);
+        self
+    }
+
+    /// Renders with [`Node::render_pretty`] and `options`.
+    pub fn pretty(mut self, options: PrettyOptions) -> Self {
+        self.format = Format::Pretty(options);
+        self
+    }
+
+    /// Runs the pipeline on a tree.
+    pub fn render(&self, mut node: Node) -> String {
+        for stage in &self.stages {
+            stage.transform_node(&mut node);
+        }
+        let html = match &self.format {
+            Format::Compact(options) => node.render_compact(options),
+            Format::Pretty(options) => node.render_pretty(options),
+        };
+        self.stages.iter().fold(html, |html, stage| stage.transform_html(html))
+    }
+
+    /// Runs the tree stages and streams the result to `sink` instead of
+    /// building a string. [`transform_html`](Stage::transform_html) needs the
+    /// whole output, so it is not applied here.
+    ///
+    /// The output is compact: the quote style is passed to the sink with
+    /// [`HtmlSink::set_quote`] and text whitespace is collapsed as in
+    /// [`Node::render_compact`]. With a [`pretty`](Renderer::pretty) format,
+    /// only its quote style is used.
+    ///
+    /// ```rust
+    /// use forge_rsx::node::{CompactOptions, Node, QuoteStyle, sink::HtmlWriter};
+    /// use forge_rsx::renderer::{AddNoopener, Renderer};
+    ///
+    /// let mut out = HtmlWriter::new(Vec::new());
+    /// Renderer::new()
+    ///     .with(AddNoopener)
+    ///     .compact(CompactOptions { quote: QuoteStyle::Single, collapse_whitespace: true })
+    ///     .render_to(Node::parse("<a href=\"/x\" target=\"_blank\">Read   more</a>"), &mut out)
+    ///     .unwrap();
+    /// assert_eq!(out.into_inner(), b"<a href='/x' target='_blank' rel='noopener'>Read more</a>");
+    /// ```
+    pub fn render_to(&self, mut node: Node, sink: &mut impl HtmlSink) -> crate::error::Result<()> {
+        for stage in &self.stages {
+            stage.transform_node(&mut node);
+        }
+        let options = match &self.format {
+            Format::Compact(options) => options.clone(),
+            Format::Pretty(options) => CompactOptions { quote: options.quote, collapse_whitespace: false },
+        };
+        node.write_compact_to(sink, &options)
+    }
+
+    /// Parses markup (e.g. `rsx!` output) and runs the pipeline on it.
+    pub fn render_html(&self, html: &str) -> String {
+        self.render(Node::parse(html))
+    }
+}
+
+/// Adds `rel="noopener"` to links opening in a new browsing context
+/// (`target="_blank"`), keeping any existing `rel` values.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AddNoopener;
+
+impl Stage for AddNoopener {
+    fn transform_node(&self, node: &mut Node) {
+        visit_mut(node, &mut |node| {
+            let Node::Element(el) = node else { return };
+            if el.tag != "a" && el.tag != "area" || el.get_attr("target") != Some("_blank") {
+                return;
+            }
+            match el.attrs.iter_mut().find(|(k, _)| k == "rel") {
+                Some((_, value)) => {
+                    let rel = value.get_or_insert_with(Default::default);
+                    if !rel.split_whitespace().any(|r| r == "noopener") {
+                        if !rel.is_empty() {
+                            rel.push(' ');
+                        }
+                        rel.push_str("noopener");
+                    }
+                }
+                None => el.attrs.push(("rel".into(), Some("noopener".into()))),
+            }
+        });
+    }
+}
+
+/// Drops indentation between elements and collapses runs of whitespace in
+/// text, except inside `pre`, `textarea`, `script` and `style`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MinifyWhitespace;
+
+impl Stage for MinifyWhitespace {
+    fn transform_node(&self, node: &mut Node) {
+        minify(node);
+    }
+}
+
+fn minify(node: &mut Node) {
+    let children = match node {
+        Node::Element(el) if RAW_TEXT_ELEMENTS.contains(&el.tag.as_str()) => return,
+        Node::Element(el) => &mut el.children,
+        Node::Fragment(nodes) => nodes,
+        _ => return,
+    };
+    children.retain(|child| !matches!(child, Node::Text(t) if t.trim().is_empty() && t.contains('\n')));
+    for child in children.iter_mut() {
+        match child {
+            Node::Text(text) => {
+                let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                // Edge whitespace containing a line break is indentation.
+                let lead = &text[..text.len() - text.trim_start().len()];
+                let trail = &text[text.trim_end().len()..];
+                let mut out = Str::default();
+                if !lead.is_empty() && !lead.contains('\n') {
+                    out.push(' ');
+                }
+                out.push_str(&collapsed);
+                if !trail.is_empty() && !trail.contains('\n') && !collapsed.is_empty() {
+                    out.push(' ');
+                }
+                *text = out;
+            }
+            _ => minify(child),
+        }
+    }
+}
+
+/// Appends an analytics snippet (raw markup) at the end of `<body>`, or at the
+/// end of the output when there is no `body` element.
+#[derive(Debug, Clone, Default)]
+pub struct InjectAnalytics(pub String);
+
+impl Stage for InjectAnalytics {
+    fn transform_node(&self, node: &mut Node) {
+        let snippet = Node::text(self.0.as_str());
+        if !append_to_body(node, &snippet) {
+            let root = std::mem::replace(node, Node::Fragment(Vec::new()));
+            *node = Node::Fragment(vec![root, snippet]);
+        }
+    }
+}
+
+/// Appends `items` to the first `head` element of the tree, returning whether
+/// there was one.
+pub(crate) fn append_to_head(node: &mut Node, items: &[Node]) -> bool {
+    match node {
+        Node::Element(el) if el.tag == "head" => {
+            el.children.extend_from_slice(items);
+            true
+        }
+        Node::Element(el) => el.children.iter_mut().any(|child| append_to_head(child, items)),
+        Node::Fragment(nodes) => nodes.iter_mut().any(|child| append_to_head(child, items)),
+        _ => false,
+    }
+}
+
+fn append_to_body(node: &mut Node, item: &Node) -> bool {
+    match node {
+        Node::Element(el) if el.tag == "body" => {
+            el.children.push(item.clone());
+            true
+        }
+        Node::Element(el) => el.children.iter_mut().any(|child| append_to_body(child, item)),
+        Node::Fragment(nodes) => nodes.iter_mut().any(|child| append_to_body(child, item)),
+        _ => false,
+    }
+}
+
+/// Calls `f` on `node` and all its descendants, parents first.
+fn visit_mut(node: &mut Node, f: &mut impl FnMut(&mut Node)) {
+    f(node);
+    match node {
+        Node::Element(el) => el.children.iter_mut().for_each(|child| visit_mut(child, f)),
+        Node::Fragment(nodes) => nodes.iter_mut().for_each(|child| visit_mut(child, f)),
+        _ => {}
+    }
+}
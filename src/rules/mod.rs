@@ -1,14 +1,20 @@
+use std::cell::Cell;
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+use crate::output::Render;
+
 /// A macro to generate HTML-like markup with different indentation styles.
 /// 
 /// Usage:
 /// ```rust
-/// use forge_rsx::{rsx, get_char};
+/// use forge_rsx::{raw, rsx, get_char};
 /// fn main() {
 ///    let apple = "🍎 Apple";
 ///    let apple_component = rsx!(lined, span { {&apple} });
 ///    let fruits = vec!["🍇", "mango", "orange"];
 ///    let div = rsx!(btfy4, div { "..." 
-///        {"<!--  How to use attributes with hyphens, like x-show in Alpine.js -->"}
+///        {raw!("<!--  How to use attributes with hyphens, like x-show in Alpine.js -->")}
 ///        span {
 ///            id: "my-id",
 ///            class: "my-class",
@@ -27,26 +33,26 @@
 ///                 span {
 ///                     {
 ///                         if fruit == &"🍇" {
-///                             &format!("{} {}", fruit.to_string(), "Grapes")
+///                             format!("{} {}", fruit.to_string(), "Grapes")
 /// 
 ///                         } else if fruit == &"mango" {
-///                             &format!("{} {}", "🥭", fruit.to_lowercase())
+///                             format!("{} {}", "🥭", fruit.to_lowercase())
 ///                         } else {
-///                             &fruit.to_uppercase()
+///                             fruit.to_uppercase()
 ///                         }
 ///                     }
 ///                 }
 ///             }
 ///         }
 ///         li { 
-///             {"<!-- How to join RSX component -->"}
-///             {&apple_component.to_string()} 
+///             {raw!("<!-- How to join RSX component -->")}
+///             {raw!(&apple_component)} 
 ///             {
-///                 if get_char(&apple, 1).to_string() == "🍎" {
+///                 raw!(if get_char(&apple, 1).to_string() == "🍎" {
 ///                     "🍎".to_string()
 ///                 } else {
 ///                     apple_component.to_string()
-///                 }
+///                 })
 ///             }
 ///         }
 ///     } } });
@@ -101,28 +107,398 @@
 /// - `btfy2`: uses 2 spaces indentation
 /// - `btfy4`: uses 4 spaces indentation
 /// - `tabed`: uses 2 spaces indentation (tabed = btfy2)
+///
+/// The style may also be chosen at runtime by passing a parenthesized [`Style`]
+/// expression instead of an identifier, e.g. `rsx!((style), div { .. })`, or
+/// left to the application with `default`, which uses the style configured
+/// by [`set_default_style`].
+///
+/// Prefix keywords (placed after the style, before the root tag):
+/// - `doctype_html`: prepends `<!DOCTYPE html>` on its own line
+/// - `doctype_html_lower`: prepends the lowercase `<!doctype html>`
+/// - `doctype "..."`: prepends `<!DOCTYPE ...>` with an arbitrary doctype string
+/// - `trailing_newline`: ends the output with a final `\n`, as file-writing
+///   pipelines and POSIX tools expect
+///
+/// ```rust
+/// use forge_rsx::rsx;
+///
+/// let page = rsx!(lined, trailing_newline doctype_html html { body { "Hi" } });
+/// assert_eq!(page, "<!DOCTYPE html>\n<html><body>Hi</body></html>\n");
+///
+/// let lower = rsx!(lined, doctype_html_lower html {});
+/// assert_eq!(lower, "<!doctype html>\n<html></html>");
+///
+/// let legacy = rsx!(lined, doctype r#"html SYSTEM "about:legacy-compat""# html {});
+/// assert_eq!(legacy, "<!DOCTYPE html SYSTEM \"about:legacy-compat\">\n<html></html>");
+/// ```
+///
+/// A child element annotated with `#[cfg(..)]` (or `@cfg(..)`) is compiled
+/// only when the configuration predicate holds, so debug panels or
+/// feature-gated sections cost nothing in builds that leave them out.
+///
+/// ```rust
+/// use forge_rsx::rsx;
+///
+/// let page = rsx!(lined, main {
+///     h1 { "Orders" }
+///     #[cfg(debug_assertions)] pre { "debug: 3 queries" }
+///     @cfg(not(debug_assertions)) footer { "v1.0" }
+/// });
+/// if cfg!(debug_assertions) {
+///     assert_eq!(page, "<main><h1>Orders</h1><pre>debug: 3 queries</pre></main>");
+/// } else {
+///     assert_eq!(page, "<main><h1>Orders</h1><footer>v1.0</footer></main>");
+/// }
+/// ```
+///
+/// A braced child may pipe a value through Jinja-style filters, resolved by
+/// name in the [`filters`](crate::filters) registry:
+/// `{ value | filter | filter(args..) }`. The value is a variable, a field
+/// path or a parenthesized expression; wrap a bitwise OR in parentheses,
+/// `{ (a | b) }`, to keep it from being read as a filter.
+///
+/// ```rust
+/// use forge_rsx::rsx;
+///
+/// let user = ("Ann", Some("admin"));
+/// let heading = rsx!(lined, h1 { { user.0 | upper } " " { (user.1) | upper } });
+/// assert_eq!(heading, "<h1>ANN ADMIN</h1>");
+/// ```
+///
+/// Any tag may be followed by Emmet-style shorthand: `#name` for the `id` and
+/// `.name` for each class (quote names that aren't identifiers, e.g.
+/// `."mt-4"` or `# "intro-text"`, with a space after `#` under edition 2024).
+/// Classes from the shorthand and an explicit `class:` are merged into one
+/// attribute.
+///
+/// ```rust
+/// use forge_rsx::rsx;
+///
+/// let layout = rsx!(lined, div #main .container .dark {
+///     class: "wide",
+///     section ."mt-4" { p # "intro-text" { "Hi" } }
+/// });
+/// assert_eq!(
+///     layout,
+///     r#"<div id="main" class="container dark wide"><section class="mt-4"><p id="intro-text">Hi</p></section></div>"#
+/// );
+/// ```
+///
+/// `if` / `else if` / `else` blocks choose between whole subtrees; the
+/// children of the matching branch are rendered in place, at the depth of the
+/// surrounding children. Conditions may bind patterns with `let`.
+///
+/// ```rust
+/// use forge_rsx::rsx;
+///
+/// let unread = 3;
+/// let user = Some("Ann");
+/// let inbox = rsx!(btfy2, div {
+///     if unread > 0 { b { {unread} } "new" } else { "No messages" }
+///     if let Some(name) = user { span { {name} } }
+/// });
+/// assert_eq!(inbox, "<div>\n  <b>\n    3\n  </b>\n  new\n  <span>\n    Ann\n  </span>\n</div>");
+/// ```
+///
+/// `match e => { .. }` picks a subtree per enum variant. Each arm is a
+/// pattern (with an optional `if` guard) followed by a tag or by braced
+/// children, which are rendered like the children of an `if` branch.
+///
+/// ```rust
+/// use forge_rsx::rsx;
+///
+/// enum Status { Active, Suspended { reason: &'static str }, Invited(u32) }
+///
+/// let badges: Vec<_> = [Status::Active, Status::Suspended { reason: "spam" }, Status::Invited(0)]
+///     .iter()
+///     .map(|status| rsx!(lined, td {
+///         match status => {
+///             Status::Active => span { class: "ok", "Active" },
+///             Status::Suspended { reason } => { "Suspended: " em { {reason} } },
+///             Status::Invited(days) if *days > 0 => { "Invited " {days} " days ago" },
+///             Status::Invited(_) => { "Invited today" }
+///         }
+///     }))
+///     .collect();
+/// assert_eq!(badges[0], r#"<td><span class="ok">Active</span></td>"#);
+/// assert_eq!(badges[1], "<td>Suspended: <em>spam</em></td>");
+/// assert_eq!(badges[2], "<td>Invited today</td>");
+/// ```
+///
+/// A `fragment { .. }` root (or child) renders several siblings without a
+/// wrapper element, e.g. list items or a `<head>` snippet:
+///
+/// ```rust
+/// use forge_rsx::rsx;
+///
+/// let items = rsx!(btfy2, fragment { li { "One" } li { "Two" } });
+/// assert_eq!(items, "<li>\n  One\n</li>\n<li>\n  Two\n</li>");
+///
+/// let head = rsx!(lined, fragment {
+///     meta { charset: "utf-8" }
+///     title { "Inbox" }
+/// });
+/// assert_eq!(head, r#"<meta charset="utf-8"><title>Inbox</title>"#);
+/// assert_eq!(rsx!(lined, ul { fragment { li { "A" } li { "B" } } }), "<ul><li>A</li><li>B</li></ul>");
+/// ```
+///
+/// For markup the grammar cannot express, a `verbatim { .. }` block inserts
+/// its tokens as written, unescaped. Spacing follows the compiler's view of
+/// the tokens, which keeps it as typed in practice; Rust still has to be able
+/// to tokenize the content, so unbalanced brackets or lone quotes need `raw!`.
+///
+/// ```rust
+/// use forge_rsx::rsx;
+///
+/// let menu = rsx!(lined, nav {
+///     verbatim { <my-menu @toggle.window="open = !open" compact/> }
+///     a { href: "/", "Home" }
+/// });
+/// assert_eq!(menu, r#"<nav><my-menu @toggle.window="open = !open" compact/><a href="/">Home</a></nav>"#);
+/// ```
+#[cfg(feature = "macros")]
 #[macro_export]
 macro_rules! rsx {
-    ($style:ident, doctype_html $tag:ident { $($content:tt)* }) => {
-        format!(
+    ($($content:tt)*) => {{
+        // Filter errors (an unknown name) panic here; `rsx_try!` returns them.
+        #[allow(unused_macros)]
+        macro_rules! __forge_rsx_filter {
+            ($result:expr) => {
+                match $result {
+                    ::core::result::Result::Ok(value) => value,
+                    ::core::result::Result::Err(e) => panic!("{}", e),
+                }
+            };
+        }
+        forge_rsx::rsx_markup!($($content)*)
+    }};
+}
+
+/// The grammar of `rsx!`, without the handling of filter errors, which
+/// `rsx!` and `rsx_try!` each define around it.
+#[doc(hidden)]
+#[cfg(feature = "macros")]
+#[macro_export]
+macro_rules! rsx_markup {
+    ($style:tt, trailing_newline $($rest:tt)*) => {
+        forge_rsx::output::Html::pre_escaped(format!("{}\n", forge_rsx::rsx_markup!($style, $($rest)*)))
+    };
+    ($style:tt, doctype_html trailing_newline $($rest:tt)*) => {
+        forge_rsx::rsx_markup!($style, trailing_newline doctype_html $($rest)*)
+    };
+    ($style:tt, doctype_html $tag:ident { $($content:tt)* }) => {
+        forge_rsx::output::Html::pre_escaped(format!(
             "<!DOCTYPE html>\n{}", 
-            forge_rsx::rsx!($style, $tag { $($content)* })
-        )
+            forge_rsx::rsx_markup!($style, $tag { $($content)* })
+        ))
+    };
+    ($style:tt, doctype_html_lower $tag:ident { $($content:tt)* }) => {
+        forge_rsx::output::Html::pre_escaped(format!(
+            "<!doctype html>\n{}",
+            forge_rsx::rsx_markup!($style, $tag { $($content)* })
+        ))
+    };
+    ($style:tt, doctype $doctype:literal $tag:ident { $($content:tt)* }) => {
+        forge_rsx::output::Html::pre_escaped(format!(
+            "<!DOCTYPE {}>\n{}",
+            $doctype,
+            forge_rsx::rsx_markup!($style, $tag { $($content)* })
+        ))
     };
     (lined, $tag:ident { $($content:tt)* }) => {
-        forge_rsx::rsx_muncher!(0, 0, $tag, [], [], $($content)*)
+        forge_rsx::output::Html::pre_escaped(forge_rsx::rules::finish(0, forge_rsx::rsx_muncher!(0, 0, $tag, [], [], $($content)*)))
     };
     (btfy0, $tag:ident { $($content:tt)* }) => {
-        forge_rsx::rsx_muncher!(1, 0, $tag, [], [], $($content)*)
+        forge_rsx::output::Html::pre_escaped(forge_rsx::rules::finish(1, forge_rsx::rsx_muncher!(1, 0, $tag, [], [], $($content)*)))
     };
     (btfy2, $tag:ident { $($content:tt)* }) => {
-        forge_rsx::rsx_muncher!(2, 0, $tag, [], [], $($content)*)
+        forge_rsx::output::Html::pre_escaped(forge_rsx::rsx_muncher!(2, 0, $tag, [], [], $($content)*))
     };
     (btfy4, $tag:ident { $($content:tt)* }) => {
-        forge_rsx::rsx_muncher!(4, 0, $tag, [], [], $($content)*)
+        forge_rsx::output::Html::pre_escaped(forge_rsx::rsx_muncher!(4, 0, $tag, [], [], $($content)*))
     };
     (tabed, $tag:ident { $($content:tt)* }) => {
-        forge_rsx::rsx_muncher!(2, 0, $tag, [], [], $($content)*)
+        forge_rsx::output::Html::pre_escaped(forge_rsx::rsx_muncher!(2, 0, $tag, [], [], $($content)*))
+    };
+    (default, $tag:ident { $($content:tt)* }) => {
+        forge_rsx::rsx_markup!((forge_rsx::rules::default_style()), $tag { $($content)* })
+    };
+    (inherit, $tag:ident { $($content:tt)* }) => {
+        forge_rsx::output::RenderFn(move |out: &mut String, mode: usize, depth: usize| {
+            out.push_str(&forge_rsx::rsx_muncher!(mode, depth, $tag, [], [], $($content)*))
+        })
+    };
+    (($style:expr), $tag:ident { $($content:tt)* }) => {{
+        let mode = forge_rsx::rules::Style::mode($style);
+        forge_rsx::output::Html::pre_escaped(forge_rsx::rules::finish(mode, forge_rsx::rsx_muncher!(mode, 0, $tag, [], [], $($content)*)))
+    }};
+    ($style:tt, $tag:ident # $id:tt $(. $cls:tt)+ { $($content:tt)* }) => {
+        forge_rsx::rsx_markup!($style, $tag {
+            id: forge_rsx::rules::shorthand_name(stringify!($id)),
+            class: forge_rsx::rules::shorthand_classes(&[$(stringify!($cls)),+]),
+            $($content)*
+        })
+    };
+    ($style:tt, $tag:ident # $id:tt { $($content:tt)* }) => {
+        forge_rsx::rsx_markup!($style, $tag { id: forge_rsx::rules::shorthand_name(stringify!($id)), $($content)* })
+    };
+    ($style:tt, $tag:ident $(. $cls:tt)+ { $($content:tt)* }) => {
+        forge_rsx::rsx_markup!($style, $tag { class: forge_rsx::rules::shorthand_classes(&[$(stringify!($cls)),+]), $($content)* })
+    };
+}
+
+/// Output style, the runtime counterpart of the `lined`, `btfy0`, `btfy2`,
+/// `btfy4` and `tabed` identifiers accepted by `rsx!`.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::{rsx, Style};
+///
+/// let style = Style::Btfy2; // e.g. picked from configuration
+/// let html = rsx!((style), ul { li { "One" } });
+/// assert_eq!(html, rsx!(btfy2, ul { li { "One" } }));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Style {
+    /// No indentation or line breaks.
+    Lined,
+    /// Line breaks without indentation.
+    Btfy0,
+    /// 2 spaces per level.
+    Btfy2,
+    /// 4 spaces per level.
+    Btfy4,
+    /// 2 spaces per level (same as `Btfy2`).
+    Tabed,
+}
+
+impl Style {
+    /// The indentation mode `rsx_muncher!` expects for this style.
+    pub fn mode(self) -> usize {
+        match self {
+            Style::Lined => 0,
+            Style::Btfy0 => 1,
+            Style::Btfy2 | Style::Tabed => 2,
+            Style::Btfy4 => 4,
+        }
+    }
+}
+
+/// The style used by `rsx!(default, ..)`, stored as its index in `STYLES`.
+static DEFAULT_STYLE: AtomicU8 = AtomicU8::new(2);
+
+const STYLES: [Style; 5] = [Style::Lined, Style::Btfy0, Style::Btfy2, Style::Btfy4, Style::Tabed];
+
+/// Sets the style used by every `rsx!(default, ..)` call outside a
+/// [`with_style`] scope, so an application can switch all its templates
+/// between pretty and minified output in one place. The initial default is [`Style::Btfy2`].
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::{rsx, set_default_style, Style};
+///
+/// set_default_style(Style::Lined); // e.g. chosen from an environment variable
+/// assert_eq!(rsx!(default, ul { li { "One" } }), "<ul><li>One</li></ul>");
+///
+/// set_default_style(Style::Btfy2);
+/// assert_eq!(rsx!(default, ul { li { "One" } }), "<ul>\n  <li>\n    One\n  </li>\n</ul>");
+/// ```
+pub fn set_default_style(style: Style) {
+    let index = STYLES.iter().position(|s| *s == style).unwrap_or(2);
+    DEFAULT_STYLE.store(index as u8, Ordering::Relaxed);
+}
+
+/// Returns the style used by `rsx!(default, ..)`: the innermost
+/// [`with_style`] override on this thread, or the global default.
+pub fn default_style() -> Style {
+    STYLE_OVERRIDE
+        .with(|o| o.get())
+        .unwrap_or_else(|| STYLES[DEFAULT_STYLE.load(Ordering::Relaxed) as usize])
+}
+
+thread_local! {
+    static STYLE_OVERRIDE: Cell<Option<Style>> = const { Cell::new(None) };
+}
+
+/// Restores the previous override when a [`with_style`] scope ends, even by
+/// unwinding.
+struct StyleGuard(Option<Style>);
+
+impl Drop for StyleGuard {
+    fn drop(&mut self) {
+        STYLE_OVERRIDE.with(|o| o.set(self.0));
+    }
+}
+
+/// Runs `f` with `style` as the default style of the current thread, e.g. to
+/// dump components that normally render minified in a readable form. Scopes
+/// can be nested; the global default set by [`set_default_style`] is left
+/// untouched.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::{rsx, set_default_style, with_style, Style};
+///
+/// set_default_style(Style::Lined);
+/// let card = || rsx!(default, div { p { "Card" } });
+///
+/// assert_eq!(card(), "<div><p>Card</p></div>");
+/// let debug = with_style(Style::Btfy2, card);
+/// assert_eq!(debug, "<div>\n  <p>\n    Card\n  </p>\n</div>");
+/// assert_eq!(card(), "<div><p>Card</p></div>");
+/// ```
+pub fn with_style<R>(style: Style, f: impl FnOnce() -> R) -> R {
+    let _guard = StyleGuard(STYLE_OVERRIDE.with(|o| o.replace(Some(style))));
+    f()
+}
+
+/// A fallible variant of `rsx!` that evaluates to `Result<Html, E>`.
+///
+/// Braced children may use the `?` operator; the first error short-circuits the
+/// whole template and is returned as `Err`, so fallible lookups or formatting
+/// no longer need `unwrap()` inside the markup. The error type is inferred from
+/// the context the result is used in (e.g. a `let` annotation).
+///
+/// Usage:
+/// ```rust
+/// use forge_rsx::{rsx_try, Html};
+/// use std::num::ParseIntError;
+///
+/// let ok: Result<Html, ParseIntError> = rsx_try!(lined, span { { "42".parse::<u32>()? } });
+/// assert_eq!(ok.unwrap(), "<span>42</span>");
+///
+/// let err: Result<Html, ParseIntError> = rsx_try!(lined, span { { "x".parse::<u32>()? } });
+/// assert!(err.is_err());
+/// ```
+///
+/// An unknown filter in `{ value | filter }` is returned the same way, as an
+/// [`Error::Render`](crate::Error::Render) converted with `?` (so the error
+/// type must implement `From<forge_rsx::Error>`), where `rsx!` panics.
+///
+/// ```rust
+/// use forge_rsx::{rsx_try, Error, Html};
+///
+/// let name = "ann";
+/// let ok: Result<Html, Error> = rsx_try!(lined, b { { name | upper } });
+/// assert_eq!(ok.unwrap(), "<b>ANN</b>");
+///
+/// let err: Result<Html, Error> = rsx_try!(lined, b { { name | shout } });
+/// assert_eq!(err.unwrap_err().to_string(), "render error: unknown filter `shout`");
+/// ```
+#[cfg(feature = "macros")]
+#[macro_export]
+macro_rules! rsx_try {
+    ($($content:tt)*) => {
+        (|| -> ::core::result::Result<forge_rsx::output::Html, _> {
+            #[allow(unused_macros)]
+            macro_rules! __forge_rsx_filter {
+                ($result:expr) => {
+                    $result?
+                };
+            }
+            ::core::result::Result::Ok(forge_rsx::rsx_markup!($($content)*))
+        })()
     };
 }
 
@@ -135,12 +511,39 @@ macro_rules! rsx {
 /// loops, and conditional content.
 ///
 /// # Pattern Breakdown
+/// - **Leaves:** Elements without attributes whose children are all literals
+///   (`span { "text" }`, the most common case) are rendered by `rules::leaf`
+///   in a single pass, without the generic accumulator.
 /// - **Termination:** Handles empty content (end of children).
 /// - **Attributes:** Adds attributes to tags, supporting both identifier and literal patterns.
+///   A bracketed value (`class: ["card", ("active", on)]`) is built with `class_list!`.
 /// - **Nested tags:** Recursively processes inner tags with increased indentation.
-/// - **Loops:** Supports iteration over collections to generate repeated content.
+/// - **Loops:** Supports iteration over collections to generate repeated content;
+///   the binder is any pattern, e.g. `for (i, item) in iter.enumerate() => { .. }`
+///   or `for Product { name, price } in &products => { .. }`. An `else { .. }` after the
+///   loop body renders fallback children when the collection is empty.
+/// - **`if let`:** `if let pat = e => { tag { .. } }` renders a nested tag only when a pattern
+///   matches, with its bindings in scope.
+/// - **`if` / `else`:** `if cond { .. } else if cond { .. } else { .. }` renders the
+///   children of the first matching branch (as a fragment) at the depth of the
+///   surrounding children. Conditions may be `let` patterns; the first `{ .. }`
+///   after `if` ends the condition.
+/// - **`match`:** `match e => { Pattern => tag { .. }, Pattern if guard => { .. } }`
+///   renders the arm of the first matching pattern like an `if` branch; commas
+///   between arms are optional.
 /// - **Braced expressions:** Embeds static text or expressions inside tags.
-/// - **String literals:** Inserts string content directly.
+/// - **String literals:** Inserts string content.
+/// - **Escaping:** Text and attribute values are escaped with `escape_html`
+///   (except inside `script` and `style`); `raw!` opts out for trusted markup.
+/// - **Fragments:** `fragment { .. }` renders its children (tags, loops,
+///   `if`/`else` and `match` blocks, braced expressions and literals) at its own depth, without a
+///   wrapper element; `fragment` is therefore not usable as an element name.
+/// - **Verbatim blocks:** `verbatim { .. }` inserts its tokens as written
+///   (spacing as the compiler reports it), unescaped, for markup the grammar
+///   cannot express.
+/// - **Markers:** `~` suppresses the line break and indentation between its neighbours
+///   (or after the opening / before the closing tag when placed first / last);
+///   `@spaced` puts a blank line between the children that follow it.
 /// - **Cleanup:** Handles trailing commas or empty patterns.
 ///
 /// # Examples
@@ -157,6 +560,11 @@ macro_rules! rsx {
 ///
 /// // Loop generating multiple items
 /// rsx_muncher!(0, 0, ul, [], [], for item in vec!["One", "Two"] => { li { {item} } } );
+///
+/// // Conditional tag destructuring an Option
+/// let user = Some("Montasir");
+/// let html = rsx_muncher!(0, 0, div, [], [], if let Some(name) = user => { span { class: "user", {name} } } );
+/// assert_eq!(html, r#"<div><span class="user">Montasir</span></div>"#);
 /// ```
 ///
 /// # Arguments
@@ -166,50 +574,226 @@ macro_rules! rsx {
 /// - `$attrs`: list of attributes (tt tokens).
 /// - `$children`: list of child content (expressions).
 /// - Remaining patterns: inner tags, loops, expressions, etc.
+#[cfg(feature = "macros")]
 #[macro_export]
 macro_rules! rsx_muncher {
+    // 0a. FRAGMENTS - `fragment { .. }`: the children at the fragment's own depth, without a wrapper element
+    ($m:expr, $d:expr, fragment, [], [], $($content:tt)*) => {
+        forge_rsx::rsx_muncher!(@fragment $m, $d, [], $($content)*)
+    };
+    (@fragment $m:expr, $d:expr, [$($children:expr),*], ) => {
+        forge_rsx::rules::join_fragment($m, &[$($children),*])
+    };
+    (@fragment $m:expr, $d:expr, [$($children:expr),*], $inner_tag:ident { $($inner_content:tt)* } $($rest:tt)*) => {
+        forge_rsx::rsx_muncher!(@fragment $m, $d, [$($children,)* forge_rsx::rsx_muncher!($m, $d, $inner_tag, [], [], $($inner_content)*)], $($rest)*)
+    };
+    (@fragment $m:expr, $d:expr, [$($children:expr),*], for $pat:pat in $collection:expr => { $it:ident { $($ic:tt)* } } else { $($fallback:tt)* } $($rest:tt)*) => {{
+        #[allow(unused_mut)]
+        let mut s = String::new();
+        let mut empty = true;
+        let nl = if $m > 0 { "\n" } else { "" };
+        for $pat in $collection {
+            empty = false;
+            if !s.is_empty() { s.push_str(nl); }
+            s.push_str(&forge_rsx::rsx_muncher!($m, $d, $it, [], [], $($ic)*));
+        }
+        if empty {
+            s = forge_rsx::rsx_muncher!(@fragment $m, $d, [], $($fallback)*);
+        }
+        forge_rsx::rsx_muncher!(@fragment $m, $d, [$($children,)* s], $($rest)*)
+    }};
+    (@fragment $m:expr, $d:expr, [$($children:expr),*], for $pat:pat in $collection:expr => { $it:ident { $($ic:tt)* } } $($rest:tt)*) => {{
+        #[allow(unused_mut)]
+        let mut s = String::new();
+        let nl = if $m > 0 { "\n" } else { "" };
+        for $pat in $collection {
+            if !s.is_empty() { s.push_str(nl); }
+            s.push_str(&forge_rsx::rsx_muncher!($m, $d, $it, [], [], $($ic)*));
+        }
+        forge_rsx::rsx_muncher!(@fragment $m, $d, [$($children,)* s], $($rest)*)
+    }};
+    (@fragment $m:expr, $d:expr, [$($children:expr),*], match $e:expr => { $($arms:tt)* } $($rest:tt)*) => {
+        forge_rsx::rsx_muncher!(@match ($m, $d) [@fragment $m, $d,] [$($children),*] ($e) [] [$($arms)*] $($rest)*)
+    };
+    (@fragment $m:expr, $d:expr, [$($children:expr),*], if $($rest:tt)*) => {
+        forge_rsx::rsx_muncher!(@if ($m, $d) [@fragment $m, $d,] [$($children),*] [] [] $($rest)*)
+    };
+    (@fragment $m:expr, $d:expr, [$($children:expr),*], { $text:expr } $($rest:tt)*) => {
+        forge_rsx::rsx_muncher!(@fragment $m, $d, [$($children,)* {
+            #[allow(unused_imports)]
+            use forge_rsx::rules::{RawChild as _, RawIterChild as _, OptionChild as _, IterChild as _, DisplayChild as _};
+            let indent = match $m { 2 => "  ".repeat($d), 4 => "    ".repeat($d), _ => String::new() };
+            (&&&forge_rsx::rules::ChildExpr(&$text)).render_child(&indent, if $m > 0 { "\n" } else { "" }, true, $m, $d)
+        }], $($rest)*)
+    };
+    (@fragment $m:expr, $d:expr, [$($children:expr),*], $text:literal $($rest:tt)*) => {
+        forge_rsx::rsx_muncher!(@fragment $m, $d, [$($children,)* forge_rsx::rules::indent_child(
+            &match $m { 2 => "  ".repeat($d), 4 => "    ".repeat($d), _ => String::new() },
+            forge_rsx::rules::text_child("fragment", $text)
+        )], $($rest)*)
+    };
+    (@fragment $m:expr, $d:expr, [$($children:expr),*], , $($rest:tt)*) => {
+        forge_rsx::rsx_muncher!(@fragment $m, $d, [$($children),*], $($rest)*)
+    };
+
+    // 0b. IF / ELSE - collects the branches of `if cond { .. } else if cond { .. } else { .. }`
+    // (`if let pat = e => { tag { .. } }`, the older form of a single branch, is read as `if let pat = e { tag { .. } }`),
+    // then pushes the rendered branch and resumes the caller (`$k` followed by its children)
+    (@if $state:tt $k:tt $children:tt [$($branches:tt)*] [$($cond:tt)*] { $($body:tt)* } else if $($rest:tt)*) => {
+        forge_rsx::rsx_muncher!(@if $state $k $children [$($branches)* (($($cond)*) { $($body)* })] [] $($rest)*)
+    };
+    (@if $state:tt $k:tt $children:tt [] [$($cond:tt)*] => { $($body:tt)* } $($rest:tt)*) => {
+        forge_rsx::rsx_muncher!(@if $state $k $children [] [$($cond)*] { $($body)* } $($rest)*)
+    };
+    (@if ($m:expr, $cd:expr) [$($k:tt)*] [$($children:expr),*] [$($branches:tt)*] [$($cond:tt)*] { $($body:tt)* } else { $($else_body:tt)* } $($rest:tt)*) => {{
+        #[allow(irrefutable_let_patterns)]
+        let s = forge_rsx::rsx_muncher!(@if_chain $m, $cd, [$($branches)* (($($cond)*) { $($body)* })] [$($else_body)*]);
+        forge_rsx::rsx_muncher!($($k)* [$($children,)* s], $($rest)*)
+    }};
+    (@if ($m:expr, $cd:expr) [$($k:tt)*] [$($children:expr),*] [$($branches:tt)*] [$($cond:tt)*] { $($body:tt)* } $($rest:tt)*) => {{
+        #[allow(irrefutable_let_patterns)]
+        let s = forge_rsx::rsx_muncher!(@if_chain $m, $cd, [$($branches)* (($($cond)*) { $($body)* })] []);
+        forge_rsx::rsx_muncher!($($k)* [$($children,)* s], $($rest)*)
+    }};
+    (@if $state:tt $k:tt $children:tt $branches:tt [$($cond:tt)*] $next:tt $($rest:tt)*) => {
+        forge_rsx::rsx_muncher!(@if $state $k $children $branches [$($cond)* $next] $($rest)*)
+    };
+    (@if_chain $m:expr, $cd:expr, [(($($cond:tt)*) { $($body:tt)* }) $($more:tt)*] $else_body:tt) => {
+        if $($cond)* {
+            forge_rsx::rsx_muncher!(@fragment $m, $cd, [], $($body)*)
+        } else {
+            forge_rsx::rsx_muncher!(@if_chain $m, $cd, [$($more)*] $else_body)
+        }
+    };
+    (@if_chain $m:expr, $cd:expr, [] [$($else_body:tt)*]) => {
+        forge_rsx::rsx_muncher!(@fragment $m, $cd, [], $($else_body)*)
+    };
+
+    // 0c. MATCH - collects the arms of `match e => { Pattern => tag { .. }, Pattern if guard => { .. } }`,
+    // then pushes the rendered arm and resumes the caller like the `if` arms above
+    (@match $state:tt $k:tt $children:tt $e:tt [$($arms:tt)*] [, $($more:tt)*] $($rest:tt)*) => {
+        forge_rsx::rsx_muncher!(@match $state $k $children $e [$($arms)*] [$($more)*] $($rest)*)
+    };
+    (@match $state:tt $k:tt $children:tt $e:tt [$($arms:tt)*] [$p:pat if $g:expr => $it:ident { $($ic:tt)* } $($more:tt)*] $($rest:tt)*) => {
+        forge_rsx::rsx_muncher!(@match $state $k $children $e [$($arms)* ($p) [if $g] { $it { $($ic)* } }] [$($more)*] $($rest)*)
+    };
+    (@match $state:tt $k:tt $children:tt $e:tt [$($arms:tt)*] [$p:pat if $g:expr => { $($body:tt)* } $($more:tt)*] $($rest:tt)*) => {
+        forge_rsx::rsx_muncher!(@match $state $k $children $e [$($arms)* ($p) [if $g] { $($body)* }] [$($more)*] $($rest)*)
+    };
+    (@match $state:tt $k:tt $children:tt $e:tt [$($arms:tt)*] [$p:pat => $it:ident { $($ic:tt)* } $($more:tt)*] $($rest:tt)*) => {
+        forge_rsx::rsx_muncher!(@match $state $k $children $e [$($arms)* ($p) [] { $it { $($ic)* } }] [$($more)*] $($rest)*)
+    };
+    (@match $state:tt $k:tt $children:tt $e:tt [$($arms:tt)*] [$p:pat => { $($body:tt)* } $($more:tt)*] $($rest:tt)*) => {
+        forge_rsx::rsx_muncher!(@match $state $k $children $e [$($arms)* ($p) [] { $($body)* }] [$($more)*] $($rest)*)
+    };
+    (@match ($m:expr, $cd:expr) [$($k:tt)*] [$($children:expr),*] ($e:expr) [$(($p:pat) [$($g:tt)*] { $($body:tt)* })*] [] $($rest:tt)*) => {{
+        let s = match $e {
+            $($p $($g)* => forge_rsx::rsx_muncher!(@fragment $m, $cd, [], $($body)*),)*
+        };
+        forge_rsx::rsx_muncher!($($k)* [$($children,)* s], $($rest)*)
+    }};
+
+    // 0. LEAF FAST PATH - no attributes and only literal children (`span { "text" }`)
+    ($m:expr, $d:expr, $tag:ident, [], [], $($text:literal)+) => {
+        forge_rsx::rules::leaf($m, $d, stringify!($tag), &[$(concat!($text)),+])
+    };
+
     // 1. TERMINATION - Generates the final string
     ($m:expr, $d:expr, $tag:ident, [$($attrs:tt)*], [$($children:expr),*], ) => {{
         #[allow(unused_mut)]
         let mut attr_str = String::new();
+        #[allow(unused_mut)]
+        let mut attr_list: Vec<(&str, String)> = Vec::new();
         $(
-            /// Iterates through collected attributes and formats them into a single HTML attribute string.
-            /// 
-            /// This block handles three specific scenarios:
-            /// a. **Boolean Attributes**: If value is `true`, renders only the key (e.g., `defer`). 
-            ///    If `false`, the attribute is omitted entirely.
-            /// b. **Special Frameworks**: Uses single quotes `'` if the key starts with `@`, `:`, `x-`, or `hx-` 
-            ///    (common in Alpine.js and htmx) to allow JSON-like strings inside.
-            /// c. **Standard Attributes**: Renders as `key="value"` using double quotes.
+            // Iterates through collected attributes and formats them into a single HTML attribute string.
+            // 
+            // This block handles three specific scenarios:
+            // a. **Boolean Attributes**: If value is `true`, renders only the key (e.g., `defer`). 
+            //    If `false`, the attribute is omitted entirely.
+            // b. **Special Frameworks**: Uses single quotes `'` if the key starts with `@`, `:`, `x-`, or `hx-` 
+            //    (common in Alpine.js and htmx) to allow JSON-like strings inside.
+            // c. **Standard Attributes**: Renders as `key="value"` using double quotes.
             if let Some((k, v)) = forge_rsx::parse_attr!($attrs) {
-                // Delegate to helper: format_attribute
-                let formatted = forge_rsx::rules::format_attribute(k, &format!("{}", v));
-                attr_str.push_str(&formatted);
+                #[allow(unused_imports)]
+                use forge_rsx::rules::{RawAttr as _, EscapedAttr as _};
+                forge_rsx::rules::push_attr(&mut attr_list, k, (&&forge_rsx::rules::AttrValue(&v)).attr_value());
             }
         )*
+        for (k, v) in &attr_list {
+            // Delegate to helper: format_attribute
+            attr_str.push_str(&forge_rsx::rules::format_attribute(k, v));
+        }
 
         let indent = match $m { 2 => "  ".repeat($d), 4 => "    ".repeat($d), _ => String::new() };
         let nl = if $m > 0 { "\n" } else { "" };
 
+        // Markers (see 6b): `~` glues its neighbours, `@spaced` separates the following children with a blank line.
+        let child_indent = match $m { 2 => "  ".repeat($d + 1), 4 => "    ".repeat($d + 1), _ => String::new() };
         #[allow(unused_mut)]
         let mut inner_content = String::new();
+        #[allow(unused_mut)]
+        let mut trim_open = false;
+        #[allow(unused_mut)]
+        let mut trim_next = false;
+        #[allow(unused_mut)]
+        let mut sep = nl;
         $(
-            if !inner_content.is_empty() { inner_content.push_str(nl); }
-            inner_content.push_str(&format!("{}", $children));
+            let child = $children;
+            #[allow(unused_imports)]
+            use forge_rsx::rules::{IsMarker as _, NotMarker as _};
+            match (&forge_rsx::rules::ChildKind(&child)).marker() {
+                Some(forge_rsx::rules::Marker::Trim) => {
+                    if inner_content.is_empty() { trim_open = true; }
+                    trim_next = true;
+                }
+                Some(forge_rsx::rules::Marker::Spaced) => {
+                    if $m > 0 { sep = "\n\n"; }
+                }
+                None => {
+                    let child = format!("{}", child);
+                    if !child.is_empty() {
+                        if trim_next {
+                            inner_content.push_str(child.strip_prefix(child_indent.as_str()).unwrap_or(&child));
+                        } else {
+                            if !inner_content.is_empty() { inner_content.push_str(sep); }
+                            inner_content.push_str(&child);
+                        }
+                        trim_next = false;
+                    }
+                }
+            }
         )*
+        // Elements without children never read these.
+        let _ = (&child_indent, sep);
+        let open_sep = if trim_open { "" } else { nl };
+        let close_sep = if trim_next { String::new() } else { format!("{}{}", nl, indent) };
 
         let tag_name = stringify!($tag);
-        let is_void = matches!(tag_name, "area" | "base" | "br" | "col" | "embed" | "hr" | "img" | "input" | "link" | "meta" | "source" | "track" | "wbr");
+        let is_void = forge_rsx::rules::is_void_element(tag_name);
 
         if is_void {
             format!("{}<{}{}>", indent, tag_name, attr_str)
         } else if inner_content.is_empty() {
             format!("{}<{}{}></{}>", indent, tag_name, attr_str, tag_name)
         } else {
-            format!("{}<{}{}>{}{}{}{}</{}>", indent, tag_name, attr_str, nl, inner_content, nl, indent, tag_name)
+            format!("{}<{}{}>{}{}{}</{}>", indent, tag_name, attr_str, open_sep, inner_content, close_sep, tag_name)
         }
     }};
 
+    // 2. CLASS LIST VALUES - `key: [ .. ]` (see `class_list!`), with or without a trailing comma
+    ($m:expr, $d:expr, $tag:ident, [$($attrs:tt)*], [$($children:expr),*], $attr_name:ident : [ $($items:tt)* ], $($rest:tt)*) => {
+        forge_rsx::rsx_muncher!($m, $d, $tag, [$($attrs)* (stringify!($attr_name), forge_rsx::class_list!($($items)*))], [$($children),*], $($rest)*)
+    };
+    ($m:expr, $d:expr, $tag:ident, [$($attrs:tt)*], [$($children:expr),*], $attr_name:literal : [ $($items:tt)* ], $($rest:tt)*) => {
+        forge_rsx::rsx_muncher!($m, $d, $tag, [$($attrs)* (stringify!($attr_name), forge_rsx::class_list!($($items)*))], [$($children),*], $($rest)*)
+    };
+    ($m:expr, $d:expr, $tag:ident, [$($attrs:tt)*], [$($children:expr),*], $attr_name:ident : [ $($items:tt)* ]) => {
+        forge_rsx::rsx_muncher!($m, $d, $tag, [$($attrs)* (stringify!($attr_name), forge_rsx::class_list!($($items)*))], [$($children),*], )
+    };
+    ($m:expr, $d:expr, $tag:ident, [$($attrs:tt)*], [$($children:expr),*], $attr_name:literal : [ $($items:tt)* ]) => {
+        forge_rsx::rsx_muncher!($m, $d, $tag, [$($attrs)* (stringify!($attr_name), forge_rsx::class_list!($($items)*))], [$($children),*], )
+    };
+
     // 2a. ATTRIBUTE with COMMA (Identifier key)
     ($m:expr, $d:expr, $tag:ident, [$($attrs:tt)*], [$($children:expr),*], $attr_name:ident : $attr_value:expr, $($rest:tt)+) => {
         forge_rsx::rsx_muncher!($m, $d, $tag, [$($attrs)* (stringify!($attr_name), $attr_value)], [$($children),*], $($rest)*)
@@ -230,31 +814,125 @@ macro_rules! rsx_muncher {
         forge_rsx::rsx_muncher!($m, $d, $tag, [$($attrs)* (stringify!($attr_name), $attr_value)], [$($children),*], )
     };
 
+    // 2e. VERBATIM BLOCKS - `verbatim { .. }`, inserted as the tokens are written, unescaped
+    ($m:expr, $d:expr, $tag:ident, [$($attrs:tt)*], [$($children:expr),*], verbatim { $($tokens:tt)* } $($rest:tt)*) => {
+        forge_rsx::rsx_muncher!($m, $d, $tag, [$($attrs)*], [$($children,)* forge_rsx::rules::indent_child(
+            &match $m { 2 => "  ".repeat($d + 1), 4 => "    ".repeat($d + 1), _ => String::new() },
+            stringify!($($tokens)*)
+        )], $($rest)*)
+    };
+
     // 3. NESTED TAGS
     ($m:expr, $d:expr, $tag:ident, [$($attrs:tt)*], [$($children:expr),*], $inner_tag:ident { $($inner_content:tt)* } $($rest:tt)*) => {
         forge_rsx::rsx_muncher!($m, $d, $tag, [$($attrs)*], [$($children,)* forge_rsx::rsx_muncher!($m, $d + 1, $inner_tag, [], [], $($inner_content)*)], $($rest)*)
     };
 
+    // 3a. NESTED TAGS WITH ID/CLASS SHORTHAND - `div #main .card .dark { .. }`
+    ($m:expr, $d:expr, $tag:ident, [$($attrs:tt)*], [$($children:expr),*], $inner_tag:ident # $id:tt $(. $cls:tt)+ { $($inner_content:tt)* } $($rest:tt)*) => {
+        forge_rsx::rsx_muncher!($m, $d, $tag, [$($attrs)*], [$($children,)* forge_rsx::rsx_muncher!($m, $d + 1, $inner_tag, [
+            ("id", forge_rsx::rules::shorthand_name(stringify!($id)))
+            ("class", forge_rsx::rules::shorthand_classes(&[$(stringify!($cls)),+]))
+        ], [], $($inner_content)*)], $($rest)*)
+    };
+    ($m:expr, $d:expr, $tag:ident, [$($attrs:tt)*], [$($children:expr),*], $inner_tag:ident # $id:tt { $($inner_content:tt)* } $($rest:tt)*) => {
+        forge_rsx::rsx_muncher!($m, $d, $tag, [$($attrs)*], [$($children,)* forge_rsx::rsx_muncher!($m, $d + 1, $inner_tag, [
+            ("id", forge_rsx::rules::shorthand_name(stringify!($id)))
+        ], [], $($inner_content)*)], $($rest)*)
+    };
+    ($m:expr, $d:expr, $tag:ident, [$($attrs:tt)*], [$($children:expr),*], $inner_tag:ident $(. $cls:tt)+ { $($inner_content:tt)* } $($rest:tt)*) => {
+        forge_rsx::rsx_muncher!($m, $d, $tag, [$($attrs)*], [$($children,)* forge_rsx::rsx_muncher!($m, $d + 1, $inner_tag, [
+            ("class", forge_rsx::rules::shorthand_classes(&[$(stringify!($cls)),+]))
+        ], [], $($inner_content)*)], $($rest)*)
+    };
+
+    // 3b. CFG-CONDITIONAL TAGS - `#[cfg(..)] tag { .. }` or `@cfg(..) tag { .. }`, compiled out when false
+    ($m:expr, $d:expr, $tag:ident, [$($attrs:tt)*], [$($children:expr),*], # [cfg( $($cfg:tt)* )] $inner_tag:ident { $($inner_content:tt)* } $($rest:tt)*) => {{
+        #[cfg($($cfg)*)]
+        let s = forge_rsx::rsx_muncher!($m, $d + 1, $inner_tag, [], [], $($inner_content)*);
+        #[cfg(not($($cfg)*))]
+        let s = String::new();
+        forge_rsx::rsx_muncher!($m, $d, $tag, [$($attrs)*], [$($children,)* s], $($rest)*)
+    }};
+    ($m:expr, $d:expr, $tag:ident, [$($attrs:tt)*], [$($children:expr),*], @cfg( $($cfg:tt)* ) $inner_tag:ident { $($inner_content:tt)* } $($rest:tt)*) => {
+        forge_rsx::rsx_muncher!($m, $d, $tag, [$($attrs)*], [$($children),*], #[cfg($($cfg)*)] $inner_tag { $($inner_content)* } $($rest)*)
+    };
+
     // 4. FOR LOOPS
-    ($m:expr, $d:expr, $tag:ident, [$($attrs:tt)*], [$($children:expr),*], for $var:ident in $collection:expr => { $it:ident { $($ic:tt)* } } $($rest:tt)*) => {{
+    // (`for .. => { .. } else { .. }` renders the fallback children when the collection is empty)
+    ($m:expr, $d:expr, $tag:ident, [$($attrs:tt)*], [$($children:expr),*], for $pat:pat in $collection:expr => { $it:ident { $($ic:tt)* } } else { $($fallback:tt)* } $($rest:tt)*) => {{
         #[allow(unused_mut)]
         let mut s = String::new();
+        let mut empty = true;
         let nl = if $m > 0 { "\n" } else { "" };
-        for $var in $collection {
+        for $pat in $collection {
+            empty = false;
             if !s.is_empty() { s.push_str(nl); }
             s.push_str(&forge_rsx::rsx_muncher!($m, $d + 1, $it, [], [], $($ic)*));
         }
+        if empty {
+            s = forge_rsx::rsx_muncher!(@fragment $m, $d + 1, [], $($fallback)*);
+        }
         forge_rsx::rsx_muncher!($m, $d, $tag, [$($attrs)*], [$($children,)* s], $($rest)*)
     }};
+    ($m:expr, $d:expr, $tag:ident, [$($attrs:tt)*], [$($children:expr),*], for $pat:pat in $collection:expr => { $it:ident { $($ic:tt)* } } $($rest:tt)*) => {{
+        #[allow(unused_mut)]
+        let mut s = String::new();
+        let nl = if $m > 0 { "\n" } else { "" };
+        for $pat in $collection {
+            if !s.is_empty() { s.push_str(nl); }
+            s.push_str(&forge_rsx::rsx_muncher!($m, $d + 1, $it, [], [], $($ic)*));
+        }
+        forge_rsx::rsx_muncher!($m, $d, $tag, [$($attrs)*], [$($children,)* s], $($rest)*)
+    }};
+
+    // 4b. IF / IF LET / ELSE - branch children rendered one level deeper, like those of a nested tag
+    ($m:expr, $d:expr, $tag:ident, [$($attrs:tt)*], [$($children:expr),*], if $($rest:tt)*) => {
+        forge_rsx::rsx_muncher!(@if ($m, $d + 1) [$m, $d, $tag, [$($attrs)*],] [$($children),*] [] [] $($rest)*)
+    };
+
+    // 4c. MATCH - arm children rendered one level deeper, like those of a nested tag
+    ($m:expr, $d:expr, $tag:ident, [$($attrs:tt)*], [$($children:expr),*], match $e:expr => { $($arms:tt)* } $($rest:tt)*) => {
+        forge_rsx::rsx_muncher!(@match ($m, $d + 1) [$m, $d, $tag, [$($attrs)*],] [$($children),*] ($e) [] [$($arms)*] $($rest)*)
+    };
+
+    // 5a. FILTERED EXPRESSIONS - `{ value | filter | filter(args) }`
+    ($m:expr, $d:expr, $tag:ident, [$($attrs:tt)*], [$($children:expr),*], { $head:tt $(. $field:tt)* $(| $filter:ident $(( $($arg:expr),* $(,)? ))? )+ } $($rest:tt)*) => {
+        forge_rsx::rsx_muncher!($m, $d, $tag, [$($attrs)*], [$($children,)* {
+            let value = forge_rsx::filter_value!($head $(. $field)*);
+            $(let value = __forge_rsx_filter!(forge_rsx::filters::try_apply(value, stringify!($filter), &[$($(forge_rsx::filter_value!($arg)),*)?]));)+
+            let indent = match $m { 2 => "  ".repeat($d + 1), 4 => "    ".repeat($d + 1), _ => String::new() };
+            if forge_rsx::rules::escapes_text(stringify!($tag)) {
+                forge_rsx::rules::indent_child(&indent, value.to_html())
+            } else {
+                forge_rsx::rules::indent_child(&indent, value)
+            }
+        }], $($rest)*)
+    };
 
     // 5. BRACED EXPRESSIONS
     ($m:expr, $d:expr, $tag:ident, [$($attrs:tt)*], [$($children:expr),*], { $text:expr } $($rest:tt)*) => {
-        forge_rsx::rsx_muncher!($m, $d, $tag, [$($attrs)*], [$($children,)* format!("{}{}", match $m { 2 => "  ".repeat($d + 1), 4 => "    ".repeat($d + 1), _ => String::new() }, $text)], $($rest)*)
+        forge_rsx::rsx_muncher!($m, $d, $tag, [$($attrs)*], [$($children,)* {
+            #[allow(unused_imports)]
+            use forge_rsx::rules::{RawChild as _, RawIterChild as _, OptionChild as _, IterChild as _, DisplayChild as _};
+            let indent = match $m { 2 => "  ".repeat($d + 1), 4 => "    ".repeat($d + 1), _ => String::new() };
+            (&&&forge_rsx::rules::ChildExpr(&$text)).render_child(&indent, if $m > 0 { "\n" } else { "" }, forge_rsx::rules::escapes_text(stringify!($tag)), $m, $d + 1)
+        }], $($rest)*)
     };
 
-    // 6. STRING LITERALS
+    // 6. STRING LITERALS - an empty literal adds nothing, as in the leaf fast path
     ($m:expr, $d:expr, $tag:ident, [$($attrs:tt)*], [$($children:expr),*], $text:literal $($rest:tt)*) => {
-        forge_rsx::rsx_muncher!($m, $d, $tag, [$($attrs)*], [$($children,)* format!("{}{}", match $m { 2 => "  ".repeat($d + 1), 4 => "    ".repeat($d + 1), _ => String::new() }, $text)], $($rest)*)
+        forge_rsx::rsx_muncher!($m, $d, $tag, [$($attrs)*], [$($children,)* forge_rsx::rules::indent_child(
+            &match $m { 2 => "  ".repeat($d + 1), 4 => "    ".repeat($d + 1), _ => String::new() },
+            forge_rsx::rules::text_child(stringify!($tag), $text)
+        )], $($rest)*)
+    };
+
+    // 6b. MARKERS
+    ($m:expr, $d:expr, $tag:ident, [$($attrs:tt)*], [$($children:expr),*], ~ $($rest:tt)*) => {
+        forge_rsx::rsx_muncher!($m, $d, $tag, [$($attrs)*], [$($children,)* forge_rsx::rules::Marker::Trim], $($rest)*)
+    };
+    ($m:expr, $d:expr, $tag:ident, [$($attrs:tt)*], [$($children:expr),*], @spaced $($rest:tt)*) => {
+        forge_rsx::rsx_muncher!($m, $d, $tag, [$($attrs)*], [$($children,)* forge_rsx::rules::Marker::Spaced], $($rest)*)
     };
 
     // 7. CLEANUP
@@ -278,28 +956,581 @@ macro_rules! rsx_muncher {
 ///
 /// # Arguments
 /// - `$other`: the token pattern to match, typically a tuple of key-value strings.
+#[cfg(feature = "macros")]
 #[macro_export]
 macro_rules! parse_attr {
     ( ($key:expr, $val:expr) ) => { Some(($key, $val)) };
     ( $other:tt ) => { None };
 }
 
+static COLLAPSE_WHITESPACE: AtomicBool = AtomicBool::new(false);
+
+/// Enables or disables whitespace collapsing in text nodes of `lined` and
+/// `btfy0` output (process-wide, off by default).
+///
+/// When enabled, each run of whitespace in text becomes a single space (or a
+/// single line break in `btfy0` output when the run contains one). Content of
+/// `pre`, `textarea`, `script` and `style` elements, comments and attribute
+/// values are left untouched.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::rsx;
+/// use forge_rsx::rules::set_collapse_whitespace;
+///
+/// set_collapse_whitespace(true);
+/// let html = rsx!(lined, div {
+///     p { "Hello,
+///          world!" }
+///     pre { "keep   this" }
+/// });
+/// assert_eq!(html, "<div><p>Hello, world!</p><pre>keep   this</pre></div>");
+/// set_collapse_whitespace(false);
+/// ```
+pub fn set_collapse_whitespace(enabled: bool) {
+    COLLAPSE_WHITESPACE.store(enabled, Ordering::Relaxed);
+}
+
+/// Post-processes the output of a root `rsx!` call rendered with `mode`.
+///
+/// Currently applies whitespace collapsing (see [`set_collapse_whitespace`])
+/// to `lined` and `btfy0` output.
+pub fn finish(mode: usize, html: String) -> String {
+    if mode <= 1 && COLLAPSE_WHITESPACE.load(Ordering::Relaxed) {
+        collapse_whitespace(&html, mode == 1)
+    } else {
+        html
+    }
+}
+
+/// Renders an element without attributes whose children are the literals
+/// `texts`, as the general rules of `rsx_muncher!` would: the fast path for
+/// leaves such as `span { "text" }`, which make up most of a typical page.
+///
+/// ```rust
+/// use forge_rsx::rsx;
+/// use forge_rsx::rules::leaf;
+///
+/// assert_eq!(leaf(0, 0, "span", &["Fish & chips"]), "<span>Fish &amp; chips</span>");
+/// assert_eq!(leaf(2, 1, "li", &["One", "Two"]), "  <li>\n    One\n    Two\n  </li>");
+/// assert_eq!(rsx!(btfy2, ul { li { "One" "Two" } }), format!("<ul>\n{}\n</ul>", leaf(2, 1, "li", &["One", "Two"])));
+///
+/// // Empty literals add nothing on either path.
+/// assert_eq!(rsx!(btfy2, p { "" }), "<p></p>");
+/// assert_eq!(rsx!(btfy2, p { class: "a", "" }), r#"<p class="a"></p>"#);
+/// ```
+pub fn leaf(mode: usize, depth: usize, tag: &str, texts: &[&str]) -> String {
+    let unit = match mode { 2 => "  ", 4 => "    ", _ => "" };
+    let indent = unit.repeat(depth);
+    if is_void_element(tag) {
+        return format!("{}<{}>", indent, tag);
+    }
+    let nl = if mode > 0 { "\n" } else { "" };
+    let mut html = String::with_capacity(2 * (indent.len() + tag.len()) + texts.iter().map(|t| t.len() + 8).sum::<usize>() + 5);
+    html.push_str(&indent);
+    html.push('<');
+    html.push_str(tag);
+    html.push('>');
+    let mut empty = true;
+    for text in texts.iter().filter(|t| !t.is_empty()) {
+        html.push_str(nl);
+        html.push_str(&indent);
+        html.push_str(unit);
+        if escapes_text(tag) {
+            push_escaped(&mut html, text);
+        } else {
+            html.push_str(text);
+        }
+        empty = false;
+    }
+    if !empty {
+        html.push_str(nl);
+        html.push_str(&indent);
+    }
+    html.push_str("</");
+    html.push_str(tag);
+    html.push('>');
+    html
+}
+
+/// Joins the rendered children of a `fragment { .. }`, skipping empty ones
+/// and separating the rest with a line break in the beautified styles.
+///
+/// ```rust
+/// use forge_rsx::rules::join_fragment;
+///
+/// let items = [String::from("<li>One</li>"), String::new(), String::from("<li>Two</li>")];
+/// assert_eq!(join_fragment(2, &items), "<li>One</li>\n<li>Two</li>");
+/// assert_eq!(join_fragment(0, &items), "<li>One</li><li>Two</li>");
+/// ```
+pub fn join_fragment(mode: usize, children: &[String]) -> String {
+    join_children(children.iter().cloned(), if mode > 0 { "\n" } else { "" })
+}
+
+/// Elements whose text content is whitespace-sensitive or not HTML.
+const RAW_TEXT_ELEMENTS: [&str; 4] = ["pre", "textarea", "script", "style"];
+
+/// Collapses whitespace runs in text nodes, skipping tags, comments and raw-text elements.
+fn collapse_whitespace(html: &str, keep_newlines: bool) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        if rest.starts_with("<!--") {
+            let end = rest.find("-->").map_or(rest.len(), |i| i + 3);
+            out.push_str(&rest[..end]);
+            rest = &rest[end..];
+        } else if rest.starts_with('<') {
+            let end = tag_end(rest);
+            let tag = &rest[..end];
+            out.push_str(tag);
+            rest = &rest[end..];
+            let name: String = tag[1..]
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric())
+                .collect::<String>()
+                .to_ascii_lowercase();
+            if RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+                let close = format!("</{}", name);
+                let end = rest.find(&close).unwrap_or(rest.len());
+                out.push_str(&rest[..end]);
+                rest = &rest[end..];
+            }
+        } else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            let mut chars = rest[..end].chars().peekable();
+            while let Some(c) = chars.next() {
+                if c.is_whitespace() {
+                    let mut newline = c == '\n';
+                    while let Some(n) = chars.next_if(|n| n.is_whitespace()) {
+                        newline |= n == '\n';
+                    }
+                    out.push(if keep_newlines && newline { '\n' } else { ' ' });
+                } else {
+                    out.push(c);
+                }
+            }
+            rest = &rest[end..];
+        }
+    }
+    out
+}
+
+/// Byte length of the tag at the start of `s`, honouring quoted attribute values.
+fn tag_end(s: &str) -> usize {
+    let mut quote: Option<char> = None;
+    for (i, c) in s.char_indices().skip(1) {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '>' => return i + 1,
+            None => {}
+        }
+    }
+    s.len()
+}
+
+/// HTML void elements: rendered as a lone start tag, without children or end tag.
+pub const VOID_ELEMENTS: [&str; 13] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track", "wbr",
+];
+
+/// Void elements added with [`register_void_element`].
+static CUSTOM_VOID_ELEMENTS: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+/// Set once anything is registered, so the common case skips the lock.
+static HAS_CUSTOM_VOID_ELEMENTS: AtomicBool = AtomicBool::new(false);
+
+/// Registers an additional void (self-closing) element for every `rsx!` call,
+/// e.g. for legacy tags or proprietary vocabularies.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::rsx;
+/// use forge_rsx::rules::{is_void_element, register_void_element};
+///
+/// assert_eq!(rsx!(lined, spacer { width: 8 }), r#"<spacer width="8"></spacer>"#);
+/// register_void_element("spacer");
+/// assert!(is_void_element("spacer"));
+/// assert_eq!(rsx!(lined, spacer { width: 8 }), r#"<spacer width="8">"#);
+/// ```
+pub fn register_void_element(name: impl Into<String>) {
+    let name = name.into();
+    let mut custom = CUSTOM_VOID_ELEMENTS.write().unwrap_or_else(|e| e.into_inner());
+    if !custom.contains(&name) {
+        custom.push(name);
+    }
+    HAS_CUSTOM_VOID_ELEMENTS.store(true, Ordering::Release);
+}
+
+/// Returns `true` for built-in [`VOID_ELEMENTS`] and registered custom ones.
+pub fn is_void_element(name: &str) -> bool {
+    VOID_ELEMENTS.contains(&name)
+        || (HAS_CUSTOM_VOID_ELEMENTS.load(Ordering::Acquire)
+            && CUSTOM_VOID_ELEMENTS
+                .read()
+                .unwrap_or_else(|e| e.into_inner())
+                .iter()
+                .any(|n| n == name))
+}
+
+/// How attribute values are quoted by [`format_attribute`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuoteMode {
+    /// Values are emitted inside double quotes as `rsx!` escaped them (the
+    /// default). An escaped `\"` in a [`PreEscaped`] value is unescaped to `"`.
+    #[default]
+    Auto,
+    /// `&` and `"` left in [`PreEscaped`] values are escaped as well (`&`
+    /// only when it does not start a character reference), as strict
+    /// serializers and validators expect.
+    Strict,
+}
+
+static QUOTE_MODE: AtomicU8 = AtomicU8::new(0);
+
+/// Sets the process-wide [`QuoteMode`] used by every `rsx!` call.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::rsx;
+/// use forge_rsx::rules::{set_quote_mode, QuoteMode};
+///
+/// set_quote_mode(QuoteMode::Strict);
+/// let button = rsx!(lined, button { "hx-on:click": r#"alert("Hi & bye")"#, "Go" });
+/// assert_eq!(button, r#"<button hx-on:click="alert(&quot;Hi &amp; bye&quot;)">Go</button>"#);
+/// set_quote_mode(QuoteMode::Auto);
+/// ```
+pub fn set_quote_mode(mode: QuoteMode) {
+    QUOTE_MODE.store(mode as u8, Ordering::Relaxed);
+}
+
+/// Returns the current process-wide [`QuoteMode`].
+pub fn quote_mode() -> QuoteMode {
+    match QUOTE_MODE.load(Ordering::Relaxed) {
+        1 => QuoteMode::Strict,
+        _ => QuoteMode::Auto,
+    }
+}
+
+/// Normalization of the tokens inside rendered `class` attributes, set with
+/// [`set_class_normalization`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClassNormalization {
+    /// Class values are emitted as written (the default).
+    #[default]
+    Off,
+    /// Repeated classes are dropped, keeping the first occurrence.
+    Dedupe,
+    /// Repeated classes are dropped and the rest sorted, so output doesn't
+    /// depend on the order conditional sources were combined in.
+    DedupeSorted,
+}
+
+static CLASS_NORMALIZATION: AtomicU8 = AtomicU8::new(0);
+
+/// Sets the process-wide [`ClassNormalization`] used by every `rsx!` call.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::rsx;
+/// use forge_rsx::rules::{set_class_normalization, ClassNormalization};
+///
+/// let active = true;
+/// set_class_normalization(ClassNormalization::DedupeSorted);
+/// let tab = rsx!(lined, a { class: ["tab", ("active", active), "tab"], "Home" });
+/// assert_eq!(tab, r#"<a class="active tab">Home</a>"#);
+///
+/// set_class_normalization(ClassNormalization::Dedupe);
+/// assert_eq!(rsx!(lined, a { class: "tab  b tab a" }), r#"<a class="tab b a"></a>"#);
+/// set_class_normalization(ClassNormalization::Off);
+/// ```
+pub fn set_class_normalization(mode: ClassNormalization) {
+    CLASS_NORMALIZATION.store(mode as u8, Ordering::Relaxed);
+}
+
+/// Returns the current process-wide [`ClassNormalization`].
+pub fn class_normalization() -> ClassNormalization {
+    match CLASS_NORMALIZATION.load(Ordering::Relaxed) {
+        1 => ClassNormalization::Dedupe,
+        2 => ClassNormalization::DedupeSorted,
+        _ => ClassNormalization::Off,
+    }
+}
+
+/// Applies the configured [`ClassNormalization`] to a `class` value.
+fn normalize_classes(value: String) -> String {
+    let mode = class_normalization();
+    if mode == ClassNormalization::Off {
+        return value;
+    }
+    let mut tokens: Vec<&str> = Vec::new();
+    for token in value.split_whitespace() {
+        if !tokens.contains(&token) {
+            tokens.push(token);
+        }
+    }
+    if mode == ClassNormalization::DedupeSorted {
+        tokens.sort_unstable();
+    }
+    tokens.join(" ")
+}
+
+/// Whether [`testid!`](crate::testid!) injects `data-testid` attributes.
+/// Unset (2) means "follow `debug_assertions`".
+static TEST_IDS: AtomicU8 = AtomicU8::new(2);
+
+/// Enables or disables `data-testid` injection by
+/// [`testid!`](crate::testid!). By default ids are injected in debug builds
+/// and left out of release builds.
+pub fn set_test_ids(enabled: bool) {
+    TEST_IDS.store(enabled as u8, Ordering::Relaxed);
+}
+
+/// Returns whether `data-testid` injection is enabled.
+pub fn test_ids_enabled() -> bool {
+    match TEST_IDS.load(Ordering::Relaxed) {
+        2 => cfg!(debug_assertions),
+        v => v == 1,
+    }
+}
+
+/// Derives a test id from a component name and optional key:
+/// `UserCard` with key `42` gives `user-card-42`.
+///
+/// ```rust
+/// use forge_rsx::rules::test_id;
+///
+/// assert_eq!(test_id("UserCard", Some(&42)), "user-card-42");
+/// assert_eq!(test_id("NavBar", None), "nav-bar");
+/// ```
+pub fn test_id(component: &str, key: Option<&dyn std::fmt::Display>) -> String {
+    let mut id = String::with_capacity(component.len() + 4);
+    for (i, c) in component.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                id.push('-');
+            }
+            id.extend(c.to_lowercase());
+        } else if c == '_' {
+            id.push('-');
+        } else {
+            id.push(c);
+        }
+    }
+    if let Some(key) = key {
+        id.push('-');
+        id.push_str(&key.to_string());
+    }
+    id
+}
+
+/// Adds `data-testid="id"` to the root element of `html` when test ids are
+/// enabled (see [`set_test_ids`]); otherwise returns `html` unchanged.
+///
+/// The root element is the first start tag: leading comments, doctypes and
+/// processing instructions are skipped. `id` is escaped.
+///
+/// ```rust,standalone_crate
+/// use forge_rsx::rules::{inject_test_id, set_test_ids};
+///
+/// set_test_ids(true);
+/// assert_eq!(
+///     inject_test_id("<!-- card --><div>Hi</div>", "card-\"1\""),
+///     r#"<!-- card --><div data-testid="card-&quot;1&quot;">Hi</div>"#
+/// );
+/// ```
+pub fn inject_test_id(html: impl Into<String>, id: &str) -> String {
+    let html = html.into();
+    if !test_ids_enabled() {
+        return html;
+    }
+    let mut from = 0;
+    let open = loop {
+        let Some(i) = html[from..].find('<').map(|i| from + i) else { return html };
+        let rest = &html[i..];
+        if rest[1..].starts_with(|c: char| c.is_ascii_alphabetic()) {
+            break i;
+        }
+        // Skip comments, doctypes, processing instructions and stray end tags.
+        let end = if rest.starts_with("<!--") { rest.find("-->").map(|e| e + 3) } else { rest.find('>').map(|e| e + 1) };
+        let Some(end) = end else { return html };
+        from = i + end;
+    };
+    let name_end = html[open + 1..]
+        .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+        .map_or(html.len(), |i| open + 1 + i);
+    let mut out = html;
+    out.insert_str(name_end, &format!(" data-testid=\"{}\"", escape_html(id)));
+    out
+}
+
+/// Marks the root element of a rendered component with a `data-testid`
+/// derived from the component name (and an optional key), giving end-to-end
+/// tests stable selectors. Nothing is injected in release builds unless
+/// enabled with [`rules::set_test_ids`](crate::rules::set_test_ids).
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::{rsx, testid, Html};
+/// use forge_rsx::rules::set_test_ids;
+///
+/// fn user_card(id: u32, name: &str) -> Html {
+///     testid!(UserCard[id], rsx!(lined, div { class: "card", {name} }))
+/// }
+///
+/// set_test_ids(true);
+/// assert_eq!(user_card(7, "Ann"), r#"<div data-testid="user-card-7" class="card">Ann</div>"#);
+/// assert_eq!(testid!(NavBar, rsx!(lined, nav {})), r#"<nav data-testid="nav-bar"></nav>"#);
+///
+/// set_test_ids(false); // what release builds do by default
+/// assert_eq!(user_card(7, "Ann"), r#"<div class="card">Ann</div>"#);
+/// ```
+#[cfg(feature = "macros")]
+#[macro_export]
+macro_rules! testid {
+    ($component:ident [ $key:expr ], $html:expr $(,)?) => {
+        forge_rsx::output::Html::pre_escaped(forge_rsx::rules::inject_test_id(
+            $html,
+            &forge_rsx::rules::test_id(stringify!($component), Some(&$key)),
+        ))
+    };
+    ($component:ident, $html:expr $(,)?) => {
+        forge_rsx::output::Html::pre_escaped(forge_rsx::rules::inject_test_id(
+            $html,
+            &forge_rsx::rules::test_id(stringify!($component), None),
+        ))
+    };
+}
+
+/// Opt-in attribute minification, applied by [`format_attribute`] once set
+/// with [`set_attr_minify`]. Meant for compact (`lined`) production output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttrMinify {
+    /// Drop attributes whose value is empty.
+    pub drop_empty: bool,
+    /// Attributes kept even when empty (e.g. `alt=""` marks decorative images).
+    pub keep_empty: Vec<String>,
+    /// Collapse runs of whitespace inside `class` values.
+    pub collapse_class: bool,
+    /// Drop redundant `type="text/javascript"` and `type="text/css"`.
+    pub drop_default_types: bool,
+}
+
+impl Default for AttrMinify {
+    /// Everything disabled, with `alt` on the keep-empty allowlist.
+    fn default() -> Self {
+        AttrMinify {
+            drop_empty: false,
+            keep_empty: vec!["alt".to_string()],
+            collapse_class: false,
+            drop_default_types: false,
+        }
+    }
+}
+
+impl AttrMinify {
+    /// Every minification enabled, keeping empty `alt` and `value` attributes.
+    pub fn all() -> Self {
+        AttrMinify {
+            drop_empty: true,
+            keep_empty: vec!["alt".to_string(), "value".to_string()],
+            collapse_class: true,
+            drop_default_types: true,
+        }
+    }
+}
+
+static ATTR_MINIFY: RwLock<Option<AttrMinify>> = RwLock::new(None);
+
+/// Set once minification is configured, so the common case skips the lock.
+static HAS_ATTR_MINIFY: AtomicBool = AtomicBool::new(false);
+
+/// Sets the process-wide attribute minification options.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::rsx;
+/// use forge_rsx::rules::{set_attr_minify, AttrMinify};
+///
+/// set_attr_minify(AttrMinify::all());
+/// let html = rsx!(lined, div {
+///     class: "  card   shadow ",
+///     title: "",
+///     script { type: "text/javascript", src: "/app.js" }
+///     img { src: "/divider.png", alt: "" }
+/// });
+/// assert_eq!(html, r#"<div class="card shadow"><script src="/app.js"></script><img src="/divider.png" alt=""></div>"#);
+/// set_attr_minify(AttrMinify::default());
+/// ```
+pub fn set_attr_minify(options: AttrMinify) {
+    *ATTR_MINIFY.write().unwrap_or_else(|e| e.into_inner()) = Some(options);
+    HAS_ATTR_MINIFY.store(true, Ordering::Release);
+}
+
+/// Applies the configured [`AttrMinify`] options, returning `None` when the
+/// attribute should be dropped.
+fn minify_attribute(key: &str, value: String) -> Option<String> {
+    if !HAS_ATTR_MINIFY.load(Ordering::Acquire) {
+        return Some(value);
+    }
+    let options = ATTR_MINIFY.read().unwrap_or_else(|e| e.into_inner());
+    let Some(options) = options.as_ref() else { return Some(value) };
+
+    if options.drop_default_types && key == "type" && matches!(value.as_str(), "text/javascript" | "text/css") {
+        return None;
+    }
+    if options.drop_empty && value.is_empty() && !options.keep_empty.iter().any(|k| k == key) {
+        return None;
+    }
+    if options.collapse_class && key == "class" {
+        return Some(value.split_whitespace().collect::<Vec<_>>().join(" "));
+    }
+    Some(value)
+}
+
 /// Formats a single attribute pair into its HTML string representation.
 /// 
 /// Rules applied:
-/// 1. Boolean logic: `true` -> " key", `false` -> "" (omitted).
+/// 1. Boolean logic: `true` -> " key", `false` -> "" (omitted), except for `aria-*`,
+///    `draggable`, `spellcheck` and `contenteditable`, which keep `"true"`/`"false"` values.
 /// 2. Special frameworks (@, :, x-, hx-): Uses double quotes but handles 
 ///    internal escaping for JSON-like strings.
 /// 3. Default: Standard `key="value"` formatting.
+///
+/// `v` is written as given: `rsx!` has already escaped it unless it was
+/// wrapped in [`raw!`](crate::raw). In [`QuoteMode::Strict`], rules 2 and 3 are
+/// replaced by entity escaping of the `"` and bare `&` left in the value.
+/// [`AttrMinify`] options, when set, are applied first, then [`ClassNormalization`].
+///
+/// ```rust
+/// use forge_rsx::rules::format_attribute;
+///
+/// assert_eq!(format_attribute("defer", "true"), " defer");
+/// assert_eq!(format_attribute("hidden", "false"), "");
+/// assert_eq!(format_attribute("aria-expanded", "false"), r#" aria-expanded="false""#);
+/// ```
 pub fn format_attribute(k: &str, v: &str) -> String {
     let key = k.trim_matches('"');
-    let val_str = format!("{}", v);
+    let Some(mut val_str) = minify_attribute(key, v.to_string()) else { return String::new() };
+    if key == "class" {
+        val_str = normalize_classes(val_str);
+    }
+
+    // ARIA states and enumerated attributes take "true"/"false" as literal values
+    let literal_bool = key.starts_with("aria-") || matches!(key, "draggable" | "spellcheck" | "contenteditable");
 
     // Case A: Boolean Attributes
-    if val_str == "true" { return format!(" {}", key); }
+    if val_str == "true" && !literal_bool { return format!(" {}", key); }
     
     // Case B: Omitted Attributes
-    if val_str == "false" { return String::new(); }
+    if val_str == "false" && !literal_bool { return String::new(); }
+
+    // Strict mode: always double quotes with entity escaping
+    if quote_mode() == QuoteMode::Strict {
+        return format!(" {}=\"{}\"", key, escape_bare_attribute(&val_str));
+    }
 
     // Case C: Special Frameworks or value contains quotes
     // (Alpine.js, htmx, or manual JSON strings)
@@ -311,4 +1542,363 @@ pub fn format_attribute(k: &str, v: &str) -> String {
 
     // Case D: Standard Attribute
     format!(" {}=\"{}\"", key, val_str)
-}
\ No newline at end of file
+}
+
+/// Escapes `"` and the `&` that do not start a character reference, leaving
+/// already escaped text unchanged.
+fn escape_bare_attribute(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for (i, c) in value.char_indices() {
+        match c {
+            '"' => out.push_str("&quot;"),
+            '&' if !starts_reference(&value[i + 1..]) => out.push_str("&amp;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Whether the text after an `&` completes a character reference (`amp;`,
+/// `#39;`, `#x27;`).
+fn starts_reference(rest: &str) -> bool {
+    let name = rest.strip_prefix('#').unwrap_or(rest);
+    let len = name.bytes().take_while(u8::is_ascii_alphanumeric).count();
+    len > 0 && name[len..].starts_with(';')
+}
+
+/// Adds a collected attribute to `attrs`, appending to an earlier `class`
+/// value instead of repeating the attribute, so the `.class` shorthand and
+/// explicit `class:` entries combine.
+pub fn push_attr<'a>(attrs: &mut Vec<(&'a str, String)>, key: &'a str, value: String) {
+    if key.trim_matches('"') == "class"
+        && let Some((_, existing)) = attrs.iter_mut().find(|(k, _)| k.trim_matches('"') == "class")
+    {
+        if !value.is_empty() {
+            if !existing.is_empty() {
+                existing.push(' ');
+            }
+            existing.push_str(&value);
+        }
+        return;
+    }
+    attrs.push((key, value));
+}
+
+/// Turns a stringified shorthand token (`main` or `"main-nav"`) into the
+/// name it stands for.
+pub fn shorthand_name(token: &str) -> &str {
+    token.trim_matches('"')
+}
+
+/// Joins stringified `.class` shorthand tokens into a `class` value.
+pub fn shorthand_classes(tokens: &[&str]) -> String {
+    tokens.iter().map(|t| shorthand_name(t)).collect::<Vec<_>>().join(" ")
+}
+
+/// Markup inserted by `rsx!` as is, without the escaping applied to other
+/// text and attribute values. Create it with [`raw!`](crate::raw).
+///
+/// Only wrap trusted markup, such as the output of another `rsx!` call.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::{raw, rsx};
+///
+/// let name = "<b>Tom & Jerry</b>";
+/// let badge = rsx!(lined, span { class: "badge", "new" });
+/// assert_eq!(
+///     rsx!(lined, p { {name} " " {raw!(badge)} }),
+///     r#"<p>&lt;b&gt;Tom &amp; Jerry&lt;/b&gt; <span class="badge">new</span></p>"#
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct PreEscaped<T>(pub T);
+
+impl<T: std::fmt::Display> std::fmt::Display for PreEscaped<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Marks trusted markup (a child, an attribute value, the items of an
+/// iterator child or the value in an `Option` child) to be inserted by
+/// `rsx!` without escaping. Expands to [`PreEscaped`].
+///
+/// ```rust
+/// use forge_rsx::{raw, rsx};
+///
+/// let items = ["One", "Two"];
+/// let list = rsx!(lined, ul {
+///     {raw!("<!-- generated -->")}
+///     { items.iter().map(|i| raw!(rsx!(lined, li { {i} }))) }
+/// });
+/// assert_eq!(list, "<ul><!-- generated --><li>One</li><li>Two</li></ul>");
+/// ```
+#[cfg(feature = "macros")]
+#[macro_export]
+macro_rules! raw {
+    ($e:expr) => {
+        forge_rsx::rules::PreEscaped($e)
+    };
+}
+
+/// Escapes `&`, `<`, `>`, `"` and `'` as character references, as `rsx!`
+/// does for text and attribute values.
+///
+/// ```rust
+/// use forge_rsx::rules::escape_html;
+///
+/// assert_eq!(escape_html(r#"<a href="?a=1&b=2">It's</a>"#), "&lt;a href=&quot;?a=1&amp;b=2&quot;&gt;It&#39;s&lt;/a&gt;");
+/// ```
+pub fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    push_escaped(&mut out, text);
+    out
+}
+
+/// Appends `text` to `out`, escaped like [`escape_html`].
+fn push_escaped(out: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+}
+
+/// Whether text inside `tag` is escaped. The contents of `script` and
+/// `style` are raw text, where character references are not decoded, so
+/// they are inserted as written.
+pub fn escapes_text(tag: &str) -> bool {
+    !matches!(tag, "script" | "style")
+}
+
+/// Renders a string literal child of `tag`, escaped unless `tag` holds raw
+/// text (see [`escapes_text`]).
+pub fn text_child(tag: &str, text: impl std::fmt::Display) -> String {
+    if escapes_text(tag) { escape_html(&text.to_string()) } else { text.to_string() }
+}
+
+/// Wraps a reference to an attribute value so the muncher can leave
+/// [`PreEscaped`] values as they are and escape the rest.
+pub struct AttrValue<'a, T: ?Sized>(pub &'a T);
+
+/// Passes [`PreEscaped`] attribute values through. Highest priority.
+pub trait RawAttr {
+    fn attr_value(&self) -> String;
+}
+
+impl<T: std::fmt::Display> RawAttr for &AttrValue<'_, PreEscaped<T>> {
+    fn attr_value(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+/// Escapes any other `Display` attribute value with [`escape_html`].
+pub trait EscapedAttr {
+    fn attr_value(&self) -> String;
+}
+
+impl<T: std::fmt::Display + ?Sized> EscapedAttr for AttrValue<'_, T> {
+    fn attr_value(&self) -> String {
+        escape_html(&self.0.to_string())
+    }
+}
+
+/// Wraps a reference to a braced child expression (`{ ... }`) so the muncher can
+/// choose how to render it based on its type.
+///
+/// Rendering is resolved (in priority order) through the [`RawChild`],
+/// [`RawIterChild`], [`OptionChild`], [`IterChild`] and [`DisplayChild`] traits:
+/// 1. [`Render`] values (such as [`PreEscaped`] and [`Html`](crate::output::Html)), `Option`s of
+///    them and iterators yielding them: rendered with
+///    [`render_to`](Render::render_to) at the child's depth, unescaped.
+/// 2. `Option<T: Display>`: `Some(v)` renders `v`, `None` renders nothing.
+/// 3. `I: Iterator + Clone` with `Display` items: each item becomes a sibling,
+///    indented and separated like any other child.
+/// 4. Any other `T: Display`: rendered with `to_string()`.
+///
+/// Text from cases 2 to 4 is escaped with [`escape_html`], except inside
+/// `script` and `style` (see [`escapes_text`]). Empty results are skipped,
+/// so they add neither indentation nor a line break.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::{raw, rsx};
+///
+/// let badge: Option<&str> = None;
+/// let name = Some("Montasir");
+/// assert_eq!(rsx!(lined, p { {badge} {name} }), "<p>Montasir</p>");
+/// assert_eq!(rsx!(btfy2, p { {badge} }), "<p></p>");
+///
+/// let items = ["One", "Two"];
+/// let list = rsx!(btfy2, ul { { items.iter().map(|i| raw!(rsx!(lined, li { {i} }))) } });
+/// assert_eq!(list, "<ul>\n  <li>One</li>\n  <li>Two</li>\n</ul>");
+/// ```
+pub struct ChildExpr<'a, T: ?Sized>(pub &'a T);
+
+/// Renders [`Render`] children, and `Option`s of them, without escaping.
+/// Highest priority.
+pub trait RawChild {
+    fn render_child(&self, indent: &str, nl: &str, escape: bool, mode: usize, depth: usize) -> String;
+}
+
+impl<R: Render> RawChild for &&ChildExpr<'_, R> {
+    fn render_child(&self, _indent: &str, _nl: &str, _escape: bool, mode: usize, depth: usize) -> String {
+        render_markup(self.0, mode, depth)
+    }
+}
+
+impl<R: Render> RawChild for &&ChildExpr<'_, Option<R>> {
+    fn render_child(&self, _indent: &str, _nl: &str, _escape: bool, mode: usize, depth: usize) -> String {
+        self.0.as_ref().map(|v| render_markup(v, mode, depth)).unwrap_or_default()
+    }
+}
+
+/// Renders iterators of [`Render`] items without escaping, one sibling per
+/// item.
+pub trait RawIterChild {
+    fn render_child(&self, indent: &str, nl: &str, escape: bool, mode: usize, depth: usize) -> String;
+}
+
+impl<I> RawIterChild for &&ChildExpr<'_, I>
+where
+    I: Iterator + Clone,
+    I::Item: Render,
+{
+    fn render_child(&self, _indent: &str, nl: &str, _escape: bool, mode: usize, depth: usize) -> String {
+        join_children(self.0.clone().map(|v| render_markup(&v, mode, depth)), nl)
+    }
+}
+
+/// Renders `Option<T>` children, emitting nothing for `None`.
+pub trait OptionChild {
+    fn render_child(&self, indent: &str, nl: &str, escape: bool, mode: usize, depth: usize) -> String;
+}
+
+impl<T: std::fmt::Display> OptionChild for &&&ChildExpr<'_, Option<T>> {
+    fn render_child(&self, indent: &str, _nl: &str, escape: bool, _mode: usize, _depth: usize) -> String {
+        self.0.as_ref().map(|v| escaped_child(indent, v, escape)).unwrap_or_default()
+    }
+}
+
+impl<T: std::fmt::Display> OptionChild for &&&ChildExpr<'_, &Option<T>> {
+    fn render_child(&self, indent: &str, _nl: &str, escape: bool, _mode: usize, _depth: usize) -> String {
+        self.0.as_ref().map(|v| escaped_child(indent, v, escape)).unwrap_or_default()
+    }
+}
+
+/// Renders iterator children, one sibling per yielded item.
+///
+/// The iterator is cloned because the muncher only holds a reference to the
+/// braced expression; standard adaptors such as `iter().map(..)` are `Clone`.
+pub trait IterChild {
+    fn render_child(&self, indent: &str, nl: &str, escape: bool, mode: usize, depth: usize) -> String;
+}
+
+impl<I> IterChild for &ChildExpr<'_, I>
+where
+    I: Iterator + Clone,
+    I::Item: std::fmt::Display,
+{
+    fn render_child(&self, indent: &str, nl: &str, escape: bool, _mode: usize, _depth: usize) -> String {
+        join_children(self.0.clone().map(|v| escaped_child(indent, v, escape)), nl)
+    }
+}
+
+/// Renders any `Display` child. Lowest priority.
+pub trait DisplayChild {
+    fn render_child(&self, indent: &str, nl: &str, escape: bool, mode: usize, depth: usize) -> String;
+}
+
+impl<T: std::fmt::Display + ?Sized> DisplayChild for ChildExpr<'_, T> {
+    fn render_child(&self, indent: &str, _nl: &str, escape: bool, _mode: usize, _depth: usize) -> String {
+        escaped_child(indent, self.0, escape)
+    }
+}
+
+/// Prefixes a rendered child with `indent`, returning an empty string for empty content.
+pub fn indent_child(indent: &str, v: impl std::fmt::Display) -> String {
+    let s = v.to_string();
+    if s.is_empty() { s } else { format!("{}{}", indent, s) }
+}
+
+/// Renders a [`Render`] child on its own.
+fn render_markup(markup: &impl Render, mode: usize, depth: usize) -> String {
+    let mut out = String::new();
+    markup.render_to(&mut out, mode, depth);
+    out
+}
+
+/// Like [`indent_child`], escaping the child first when `escape` is set.
+fn escaped_child(indent: &str, v: impl std::fmt::Display, escape: bool) -> String {
+    if escape { indent_child(indent, escape_html(&v.to_string())) } else { indent_child(indent, v) }
+}
+
+/// Joins the non-empty rendered items of an iterator child with `nl`.
+fn join_children(items: impl Iterator<Item = String>, nl: &str) -> String {
+    items.filter(|s| !s.is_empty()).collect::<Vec<_>>().join(nl)
+}
+
+/// Whitespace-control markers pushed into the children list.
+///
+/// They only affect beautified output; in `lined` output both are no-ops.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::rsx;
+///
+/// // `~` joins its neighbours without a line break or indentation; a leading or
+/// // trailing `~` does the same against the opening or closing tag.
+/// let p = rsx!(btfy2, p { "Hello, " ~ b { "world" } ~ "!" });
+/// assert_eq!(p, "<p>\n  Hello, <b>\n    world\n  </b>!\n</p>");
+///
+/// let a = rsx!(btfy2, div { a { ~ "Home" ~ } });
+/// assert_eq!(a, "<div>\n  <a>Home</a>\n</div>");
+///
+/// // `@spaced` separates the children that follow it with a blank line.
+/// let body = rsx!(btfy2, body { @spaced section { "One" } section { "Two" } });
+/// assert_eq!(body, "<body>\n  <section>\n    One\n  </section>\n\n  <section>\n    Two\n  </section>\n</body>");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Marker {
+    /// `~`: no line break or indentation at this position.
+    Trim,
+    /// `@spaced`: blank line between the following children.
+    Spaced,
+}
+
+impl std::fmt::Display for Marker {
+    fn fmt(&self, _f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Ok(())
+    }
+}
+
+/// Wraps a collected child so the termination arm can tell [`Marker`]s apart.
+pub struct ChildKind<'a, T: ?Sized>(pub &'a T);
+
+/// Identifies [`Marker`] children.
+pub trait IsMarker {
+    fn marker(&self) -> Option<Marker>;
+}
+
+impl IsMarker for ChildKind<'_, Marker> {
+    fn marker(&self) -> Option<Marker> {
+        Some(*self.0)
+    }
+}
+
+/// Fallback for every other child.
+pub trait NotMarker {
+    fn marker(&self) -> Option<Marker>;
+}
+
+impl<T: ?Sized> NotMarker for &ChildKind<'_, T> {
+    fn marker(&self) -> Option<Marker> {
+        None
+    }
+}
@@ -0,0 +1,189 @@
+use crate::context;
+use crate::node::Node;
+use crate::rsx;
+
+/// Renders `<script type="application/json" id="..">` around a JSON payload
+/// serialized from `value`, the usual way to hand initial state to client
+/// code (`JSON.parse(document.getElementById(id).textContent)`).
+///
+/// The payload is escaped with [`script_safe_json`], so string values cannot
+/// close the element or open a comment.
+///
+/// Requires the `json` feature.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::scripts::json_script;
+///
+/// let state = serde_json::json!({ "user": "</script><!--" });
+/// assert_eq!(
+///     json_script("state", &state).unwrap(),
+///     r#"<script type="application/json" id="state">{"user":"\u003c/script\u003e\u003c!--"}</script>"#
+/// );
+/// ```
+#[cfg(feature = "json")]
+pub fn json_script<T: serde::Serialize + ?Sized>(id: &str, value: &T) -> crate::error::Result<String> {
+    Ok(json_script_raw(id, &serde_json::to_string(value)?))
+}
+
+/// Renders a JSON script element from an already serialized payload.
+///
+/// ```rust
+/// use forge_rsx::scripts::json_script_raw;
+///
+/// assert_eq!(
+///     json_script_raw("data", r#"{"a":1}"#),
+///     r#"<script type="application/json" id="data">{"a":1}</script>"#
+/// );
+/// ```
+pub fn json_script_raw(id: &str, json: &str) -> String {
+    rsx!(lined, script { type: "application/json", id: id, {script_safe_json(json)} }).into()
+}
+
+/// Escapes a JSON document for embedding inside a `script` element.
+///
+/// `<`, `>` and `&` become `\u003c`, `\u003e` and `\u0026`, and the line
+/// separators U+2028/U+2029 become `\u2028`/`\u2029`. These characters can
+/// only occur inside JSON strings, where the escapes decode to the same value.
+///
+/// ```rust
+/// use forge_rsx::scripts::script_safe_json;
+///
+/// assert_eq!(script_safe_json(r#""</script>""#), r#""\u003c/script\u003e""#);
+/// ```
+pub fn script_safe_json(json: &str) -> String {
+    let mut out = String::with_capacity(json.len());
+    for c in json.chars() {
+        match c {
+            '<' => out.push_str("\\u003c"),
+            '>' => out.push_str("\\u003e"),
+            '&' => out.push_str("\\u0026"),
+            '\u{2028}' => out.push_str("\\u2028"),
+            '\u{2029}' => out.push_str("\\u2029"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Emits `enhanced` markup followed by a `<noscript>` element holding a
+/// static alternative, for progressive enhancement.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::{rsx, scripts::with_noscript};
+///
+/// let html = with_noscript(
+///     rsx!(lined, div { id: "map" }),
+///     rsx!(lined, a { href: "/map.png", "View the map" }),
+/// );
+/// assert_eq!(html, r#"<div id="map"></div><noscript><a href="/map.png">View the map</a></noscript>"#);
+/// ```
+pub fn with_noscript(enhanced: impl std::fmt::Display, fallback: impl std::fmt::Display) -> String {
+    format!("{}<noscript>{}</noscript>", enhanced, fallback)
+}
+
+/// Like [`with_noscript`], deriving the fallback from `enhanced` itself: a
+/// copy without `data-*` and event handler (`on*`) attributes and without
+/// `script` elements.
+///
+/// ```rust
+/// use forge_rsx::{rsx, scripts::with_noscript_auto};
+///
+/// let form = rsx!(lined, form {
+///     action: "/search",
+///     "data-on:submit": "search",
+///     input { name: "q", onkeyup: "suggest()" }
+///     details { open: true, ontoggle: "track()", summary { "Filters" } }
+/// });
+/// assert_eq!(
+///     with_noscript_auto(&form),
+///     concat!(
+///         r#"<form action="/search" data-on:submit="search"><input name="q" onkeyup="suggest()">"#,
+///         r#"<details open ontoggle="track()"><summary>Filters</summary></details></form>"#,
+///         r#"<noscript><form action="/search"><input name="q">"#,
+///         r#"<details open><summary>Filters</summary></details></form></noscript>"#,
+///     )
+/// );
+/// ```
+pub fn with_noscript_auto(enhanced: &str) -> String {
+    let mut fallback = Node::parse(enhanced);
+    strip_scripting(&mut fallback);
+    with_noscript(enhanced, fallback)
+}
+
+/// Removes `script` elements and `data-*`/`on*` attributes from a tree.
+fn strip_scripting(node: &mut Node) {
+    let children = match node {
+        Node::Element(el) => {
+            el.attrs.retain(|(name, _)| !name.starts_with("data-") && !is_event_handler(name));
+            &mut el.children
+        }
+        Node::Fragment(nodes) => nodes,
+        _ => return,
+    };
+    children.retain(|child| !matches!(child, Node::Element(el) if el.tag == "script"));
+    children.iter_mut().for_each(strip_scripting);
+}
+
+/// Whether `name` is an event handler attribute (`onclick`, `onkeyup`, ..):
+/// `on` followed by an event name, which excludes `open`.
+fn is_event_handler(name: &str) -> bool {
+    name.strip_prefix("on")
+        .is_some_and(|event| !event.is_empty() && event.bytes().all(|b| b.is_ascii_alphabetic()) && name != "open")
+}
+
+/// Renders `script` (markup containing one or more `script` elements) as is
+/// when consent for `category` was granted with
+/// [`context::grant_consent`](crate::context::grant_consent), and as inert
+/// placeholders otherwise.
+///
+/// Placeholders get `type="text/plain"`, which browsers neither fetch nor
+/// run, and `data-consent="<category>"`, so a consent manager can activate
+/// them client-side once the visitor agrees. A non-JavaScript type (e.g.
+/// `module`) is kept in `data-type`.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::{rsx, context, scripts::consent_gate};
+///
+/// let analytics = || rsx!(lined, script { src: "/ga.js", async: "true" });
+///
+/// context::clear();
+/// assert_eq!(
+///     consent_gate("analytics", analytics()),
+///     r#"<script type="text/plain" data-consent="analytics" src="/ga.js" async></script>"#
+/// );
+///
+/// context::grant_consent("analytics");
+/// assert_eq!(consent_gate("analytics", analytics()), r#"<script src="/ga.js" async></script>"#);
+/// ```
+pub fn consent_gate(category: &str, script: impl std::fmt::Display) -> String {
+    let script = script.to_string();
+    if context::has_consent(category) {
+        return script;
+    }
+    let mut node = Node::parse(&script);
+    make_inert(&mut node, category);
+    node.to_string()
+}
+
+/// Turns every `script` element of a tree into a consent placeholder.
+fn make_inert(node: &mut Node, category: &str) {
+    let children = match node {
+        Node::Element(el) if el.tag == "script" => {
+            let kind = el.get_attr("type").map(str::to_string);
+            el.attrs.retain(|(name, _)| name != "type" && name != "data-consent");
+            el.attrs.insert(0, ("type".into(), Some("text/plain".into())));
+            el.attrs.insert(1, ("data-consent".into(), Some(category.into())));
+            if let Some(kind) = kind.filter(|k| !k.is_empty() && k != "text/javascript") {
+                el.attrs.push(("data-type".into(), Some(kind.as_str().into())));
+            }
+            return;
+        }
+        Node::Element(el) => &mut el.children,
+        Node::Fragment(nodes) => nodes,
+        _ => return,
+    };
+    children.iter_mut().for_each(|child| make_inert(child, category));
+}
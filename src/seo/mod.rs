@@ -0,0 +1,64 @@
+use crate::{rsx, Html};
+
+/// Renders `<link rel="canonical" href="..">`.
+///
+/// Like every helper in this module, the result is ready for
+/// [`context::add_head`](crate::context::add_head), so the component that
+/// knows the page's URL can hoist it into the document head.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::{rsx, context, seo, Document, Html, Style};
+///
+/// fn article(slug: &str) -> Html {
+///     context::add_head(seo::canonical(&format!("https://example.com/blog/{}", slug)));
+///     context::add_head(seo::robots(&["noarchive"]));
+///     rsx!(lined, article { "..." })
+/// }
+///
+/// context::clear();
+/// let page = Document::new().body(article("hello")).render(Style::Lined);
+/// assert!(page.contains(concat!(
+///     r#"<link rel="canonical" href="https://example.com/blog/hello">"#,
+///     r#"<meta name="robots" content="noarchive"></head>"#,
+/// )));
+/// ```
+pub fn canonical(url: &str) -> String {
+    rsx!(lined, link { rel: "canonical", href: url }).into()
+}
+
+/// Renders `<meta name="robots" content="..">` from a list of directives
+/// such as `noindex`, `nofollow` or `max-snippet:50`.
+///
+/// ```rust
+/// use forge_rsx::seo::robots;
+///
+/// assert_eq!(robots(&["noindex", "nofollow"]), r#"<meta name="robots" content="noindex, nofollow">"#);
+/// ```
+pub fn robots(directives: &[&str]) -> String {
+    let content = directives.join(", ");
+    rsx!(lined, meta { name: "robots", content: &content }).into()
+}
+
+/// Renders one `<link rel="alternate" hreflang=".." href="..">` per
+/// `(language, url)` pair, for pages translated into several languages. Use
+/// `x-default` as the language of the fallback URL.
+///
+/// ```rust
+/// use forge_rsx::seo::hreflang_alternates;
+///
+/// assert_eq!(
+///     hreflang_alternates(&[("en", "https://example.com/"), ("x-default", "https://example.com/")]),
+///     concat!(
+///         r#"<link rel="alternate" hreflang="en" href="https://example.com/">"#,
+///         r#"<link rel="alternate" hreflang="x-default" href="https://example.com/">"#,
+///     )
+/// );
+/// ```
+pub fn hreflang_alternates(alternates: &[(&str, &str)]) -> String {
+    alternates
+        .iter()
+        .map(|(lang, url)| rsx!(lined, link { rel: "alternate", hreflang: lang, href: url }))
+        .collect::<Html>()
+        .into()
+}
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use crate::context::{self, Recorded};
+use crate::css;
+use crate::document::{Document, Encoding};
+use crate::output::Html;
+use crate::rules::Style;
+
+/// Counters of a [`RenderSession`], to check that caching pays off.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionStats {
+    /// Pages rendered with [`RenderSession::render`].
+    pub pages: usize,
+    /// [`memo`](RenderSession::memo) calls answered from the cache.
+    pub memo_hits: usize,
+    /// [`memo`](RenderSession::memo) calls that rendered.
+    pub memo_misses: usize,
+    /// Pages whose merged `<style>` block came from the cache.
+    pub css_hits: usize,
+}
+
+/// Shared caches for rendering many pages in one process, such as a static
+/// site or a batch of emails.
+///
+/// - [`memo`](RenderSession::memo) renders a component once per key (a
+///   header, a footer, a navigation tree) and reuses it on every page,
+///   replaying the head items and styles it hoisted.
+/// - [`render`](RenderSession::render) merges each distinct set of
+///   stylesheets once, instead of deduplicating the same `css!` rules on
+///   every page, and starts each page with a clean per-page [`context`]
+///   (the theme and locale stay set).
+/// - [`asset`](RenderSession::asset) resolves paths through an asset
+///   manifest (e.g. fingerprinted file names from a bundler) loaded once.
+///
+/// `css!` blocks are compiled once per call site and element names are
+/// interned process-wide (see [`node::intern`](crate::node::intern)), so
+/// both are shared by every page already.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::{css, rsx, Document, Html, Style};
+/// use forge_rsx::session::RenderSession;
+///
+/// fn nav() -> Html {
+///     let styles = css! { .nav { display: flex; } };
+///     rsx!(lined, nav { class: styles.class("nav"), a { href: "/", "Home" } })
+/// }
+///
+/// let mut session = RenderSession::new().assets([("app.js", "app.3f2a91.js")]);
+/// let pages: Vec<String> = ["One", "Two", "Three"]
+///     .iter()
+///     .map(|title| {
+///         let nav = session.memo("nav", nav);
+///         let page = Document::new()
+///             .title(*title)
+///             .script(session.asset("app.js"))
+///             .body(format!("{}{}", nav, rsx!(lined, h1 { {title} })));
+///         session.render(&page, Style::Lined)
+///     })
+///     .collect();
+///
+/// for page in &pages {
+///     assert!(page.contains("display: flex;")); // hoisted again from the cache
+///     assert!(page.contains(r#"<script src="app.3f2a91.js"></script>"#));
+/// }
+/// let stats = session.stats();
+/// assert_eq!((stats.pages, stats.memo_hits, stats.memo_misses, stats.css_hits), (3, 2, 1, 2));
+/// ```
+#[derive(Debug, Default)]
+pub struct RenderSession {
+    memo: HashMap<String, (String, Recorded)>,
+    css: HashMap<String, String>,
+    assets: HashMap<String, String>,
+    stats: SessionStats,
+}
+
+impl RenderSession {
+    /// Creates a session with empty caches.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads an asset manifest mapping source paths to the paths to link.
+    pub fn assets<K: Into<String>, V: Into<String>>(mut self, manifest: impl IntoIterator<Item = (K, V)>) -> Self {
+        self.assets.extend(manifest.into_iter().map(|(k, v)| (k.into(), v.into())));
+        self
+    }
+
+    /// Returns the manifest entry for `path`, or `path` itself when it has
+    /// none.
+    pub fn asset(&self, path: &str) -> String {
+        self.assets.get(path).cloned().unwrap_or_else(|| path.to_string())
+    }
+
+    /// Returns the output of `render` for `key`, rendering it on the first
+    /// call only. Head items and styles it registers in the [`context`] are
+    /// registered again on every call.
+    pub fn memo<R: Into<String>>(&mut self, key: &str, render: impl FnOnce() -> R) -> Html {
+        if let Some((html, recorded)) = self.memo.get(key) {
+            self.stats.memo_hits += 1;
+            context::replay(recorded);
+            return Html::pre_escaped(html.clone());
+        }
+        self.stats.memo_misses += 1;
+        let (html, recorded) = context::record(|| render().into());
+        self.memo.insert(key.to_string(), (html.clone(), recorded));
+        Html::pre_escaped(html)
+    }
+
+    /// Forgets the memoized output of `key`, e.g. after its data changed.
+    pub fn invalidate(&mut self, key: &str) {
+        self.memo.remove(key);
+    }
+
+    /// Renders `document` like [`Document::render`], reusing merged
+    /// stylesheets, then resets the per-page [`context`] with
+    /// [`context::reset_page`] for the next one. The theme and locale stay.
+    ///
+    /// ```rust,standalone_crate
+    /// use forge_rsx::{context, rsx, Document, Style};
+    /// use forge_rsx::session::RenderSession;
+    /// use forge_rsx::theme::{theme, Theme};
+    ///
+    /// context::set_theme(Theme::new().class("btn", "btn-x"));
+    /// let mut session = RenderSession::new();
+    /// for _ in 0..2 {
+    ///     let page = Document::new().body(rsx!(lined, p { class: theme("btn"), "Hi" }));
+    ///     assert!(session.render(&page, Style::Lined).contains(r#"<p class="btn-x">Hi</p>"#));
+    /// }
+    /// ```
+    pub fn render(&mut self, document: &Document, style: Style) -> String {
+        self.stats.pages += 1;
+        let (cache, stats) = (&mut self.css, &mut self.stats);
+        let page = document.render_with(style, Encoding::Utf8, &mut |sheets| {
+            let key = sheets.join("\0");
+            if let Some(merged) = cache.get(&key) {
+                stats.css_hits += 1;
+                return merged.clone();
+            }
+            let merged = css::consolidate(sheets);
+            cache.insert(key, merged.clone());
+            merged
+        });
+        context::reset_page();
+        page
+    }
+
+    /// Returns the session counters.
+    pub fn stats(&self) -> SessionStats {
+        self.stats
+    }
+}
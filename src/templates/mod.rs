@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+use crate::{raw, rsx};
+
+/// Process-wide registry of template fragments, keyed by id.
+static TEMPLATES: OnceLock<RwLock<HashMap<String, String>>> = OnceLock::new();
+
+fn templates() -> &'static RwLock<HashMap<String, String>> {
+    TEMPLATES.get_or_init(Default::default)
+}
+
+/// Renders a `<template id="..">` element around `content`.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::{raw, rsx, templates::{template, slot}};
+///
+/// let card = template("user-card", rsx!(lined, div { class: "card", {raw!(slot("name"))} }));
+/// assert_eq!(card, r#"<template id="user-card"><div class="card"><slot name="name"></slot></div></template>"#);
+/// ```
+pub fn template(id: &str, content: impl std::fmt::Display) -> String {
+    rsx!(lined, template { id: id, {raw!(content)} }).into()
+}
+
+/// Renders a named `<slot name="..">` placeholder.
+pub fn slot(name: &str) -> String {
+    rsx!(lined, slot { name: name }).into()
+}
+
+/// Renders a named slot with fallback content, shown when nothing is slotted.
+pub fn slot_with_fallback(name: &str, fallback: impl std::fmt::Display) -> String {
+    rsx!(lined, slot { name: name, {raw!(fallback)} }).into()
+}
+
+/// Renders the unnamed (default) `<slot>`.
+pub fn default_slot() -> String {
+    rsx!(lined, slot {}).into()
+}
+
+/// Registers the inner markup of a template under `id`, replacing any previous
+/// registration.
+pub fn register_template(id: impl Into<String>, content: impl std::fmt::Display) {
+    templates()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(id.into(), content.to_string());
+}
+
+/// Renders the `<template>` element of a registered template, or `None`
+/// when `id` is not registered.
+pub fn registered_template(id: &str) -> Option<String> {
+    let registry = templates().read().unwrap_or_else(|e| e.into_inner());
+    registry.get(id).map(|content| template(id, content))
+}
+
+/// Instantiates a registered template on the server by filling its slots,
+/// producing the markup a no-JS client would see once the component upgraded.
+///
+/// `slots` maps slot names to content; the empty name fills the default slot.
+/// Slots without content keep their fallback. Returns `None` when `id` is not
+/// registered.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::{raw, rsx, templates::*};
+///
+/// register_template("greeting", rsx!(lined, p {
+///     "Hello, "
+///     {raw!(slot_with_fallback("name", "stranger"))}
+///     "! "
+///     {raw!(default_slot())}
+/// }));
+///
+/// assert_eq!(
+///     instantiate("greeting", &[("name", "<b>Montasir</b>"), ("", "Welcome back.")]).unwrap(),
+///     "<p>Hello, <b>Montasir</b>! Welcome back.</p>"
+/// );
+/// assert_eq!(instantiate("greeting", &[]).unwrap(), "<p>Hello, stranger! </p>");
+/// assert!(instantiate("missing", &[]).is_none());
+/// ```
+pub fn instantiate(id: &str, slots: &[(&str, &str)]) -> Option<String> {
+    let registry = templates().read().unwrap_or_else(|e| e.into_inner());
+    let content = registry.get(id)?;
+    Some(fill_slots(content, slots))
+}
+
+/// Replaces every `<slot>` element in `markup` with its provided content or fallback.
+fn fill_slots(markup: &str, slots: &[(&str, &str)]) -> String {
+    const CLOSE: &str = "</slot>";
+    let mut out = String::with_capacity(markup.len());
+    let mut rest = markup;
+
+    while let Some(start) = find_slot_open(rest) {
+        let Some(open_len) = rest[start..].find('>') else { break };
+        let open_end = start + open_len + 1;
+        let Some(close) = rest[open_end..].find(CLOSE) else { break };
+        let name = attr_value(&rest[start..open_end], "name").unwrap_or("");
+        let fallback = &rest[open_end..open_end + close];
+
+        out.push_str(&rest[..start]);
+        match slots.iter().find(|(n, _)| *n == name) {
+            Some((_, content)) => out.push_str(content),
+            None => out.push_str(fallback),
+        }
+        rest = &rest[open_end + close + CLOSE.len()..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Finds the next `<slot` opening tag (not e.g. `<slotted-thing`).
+fn find_slot_open(s: &str) -> Option<usize> {
+    let mut from = 0;
+    while let Some(i) = s[from..].find("<slot") {
+        let at = from + i;
+        match s[at + "<slot".len()..].chars().next() {
+            Some('>') | Some(' ') | Some('\n') | Some('\t') => return Some(at),
+            _ => from = at + 1,
+        }
+    }
+    None
+}
+
+/// Reads a double-quoted attribute value from an opening tag.
+fn attr_value<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let needle = format!(" {}=\"", name);
+    let start = tag.find(&needle)? + needle.len();
+    let len = tag[start..].find('"')?;
+    Some(&tag[start..start + len])
+}
@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::context;
+
+/// A registry of design-system tokens: logical names mapped to bundles of
+/// attributes, usually a class list.
+///
+/// Token names are dotted paths. A token inherits the bundle of each of its
+/// prefixes, so `button.primary` gets the classes of `button` followed by its
+/// own, and its other attributes override the parent's.
+///
+/// Set the theme of the current render with
+/// [`context::set_theme`](crate::context::set_theme) and look tokens up with
+/// [`theme`] and [`theme_attr`].
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::{rsx, context};
+/// use forge_rsx::theme::{theme, theme_attr, Theme};
+///
+/// let tokens = Theme::new()
+///     .class("button", "btn rounded")
+///     .class("button.primary", "bg-blue-600 text-white")
+///     .attr("button", "type", "button")
+///     .class("input", "field");
+///
+/// context::clear();
+/// context::set_theme(tokens);
+/// let html = rsx!(lined, form {
+///     input { class: theme("input"), name: "q" }
+///     button { class: theme("button.primary"), type: theme_attr("button.primary", "type"), "Search" }
+/// });
+/// assert_eq!(html, concat!(
+///     r#"<form><input class="field" name="q">"#,
+///     r#"<button class="btn rounded bg-blue-600 text-white" type="button">Search</button></form>"#,
+/// ));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Theme {
+    tokens: Arc<HashMap<String, Vec<(String, String)>>>,
+}
+
+impl Theme {
+    /// Creates an empty theme.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds classes to the bundle of `token`.
+    pub fn class(self, token: &str, classes: &str) -> Self {
+        self.attr(token, "class", classes)
+    }
+
+    /// Sets an attribute of the bundle of `token`. For `class`, the value is
+    /// appended to the classes already set.
+    pub fn attr(mut self, token: &str, name: &str, value: &str) -> Self {
+        let bundle = Arc::make_mut(&mut self.tokens).entry(token.to_string()).or_default();
+        match bundle.iter_mut().find(|(k, _)| k == name) {
+            Some((_, existing)) if name == "class" => {
+                existing.push(' ');
+                existing.push_str(value);
+            }
+            Some((_, existing)) => *existing = value.to_string(),
+            None => bundle.push((name.to_string(), value.to_string())),
+        }
+        self
+    }
+
+    /// Returns the attributes of `token`, including the inherited ones, in
+    /// the order they were first set. Empty for unknown tokens.
+    pub fn resolve(&self, token: &str) -> Vec<(String, String)> {
+        let mut attrs: Vec<(String, String)> = Vec::new();
+        let prefixes = token.match_indices('.').map(|(i, _)| &token[..i]).chain([token]);
+        for bundle in prefixes.filter_map(|prefix| self.tokens.get(prefix)) {
+            for (name, value) in bundle {
+                match attrs.iter_mut().find(|(k, _)| k == name) {
+                    Some((_, existing)) if name == "class" => {
+                        existing.push(' ');
+                        existing.push_str(value);
+                    }
+                    Some((_, existing)) => existing.clone_from(value),
+                    None => attrs.push((name.clone(), value.clone())),
+                }
+            }
+        }
+        attrs
+    }
+
+    /// Whether `token` (not counting its prefixes) is defined.
+    pub fn contains(&self, token: &str) -> bool {
+        self.tokens.contains_key(token)
+    }
+}
+
+/// Returns the class list of `token` in the current theme (see
+/// [`Theme`]), or an empty string if there is no theme or no such token.
+pub fn theme(token: &str) -> String {
+    theme_attr(token, "class")
+}
+
+/// Returns the value of the attribute `name` of `token` in the current
+/// theme, or an empty string if it is not set.
+pub fn theme_attr(token: &str, name: &str) -> String {
+    context::with_theme(|theme| {
+        theme
+            .map(|theme| theme.resolve(token))
+            .and_then(|attrs| attrs.into_iter().find(|(k, _)| k == name))
+            .map(|(_, value)| value)
+            .unwrap_or_default()
+    })
+}
+
+/// How [`ColorTheme`] marks the active theme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ThemeMarker {
+    /// A `data-theme="<name>"` attribute.
+    #[default]
+    Data,
+    /// A `theme-<name>` class.
+    Class,
+}
+
+/// The element [`ColorTheme`] marks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ThemeTarget {
+    /// The `html` element.
+    #[default]
+    Html,
+    /// The `body` element.
+    Body,
+}
+
+/// A color theme (e.g. `dark`) that [`Document`](crate::Document) marks on
+/// its `html` or `body` element, so stylesheets can switch on
+/// `[data-theme="dark"]` or `.theme-dark` without every layout doing it.
+///
+/// A name set with [`context::set_color_theme`](crate::context::set_color_theme)
+/// during the render (e.g. from a user preference) replaces the document's.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::{rsx, context, Document, Style};
+/// use forge_rsx::theme::ColorTheme;
+///
+/// context::clear();
+/// let page = Document::new().color_theme(ColorTheme::new("light")).render(Style::Lined);
+/// assert!(page.contains(r#"<html lang="en" data-theme="light">"#));
+///
+/// context::set_color_theme("dark");
+/// let page = Document::new()
+///     .color_theme(ColorTheme::new("light").as_class().on_body())
+///     .body(rsx!(lined, p { "Hi" }))
+///     .render(Style::Lined);
+/// assert!(page.contains(r#"<body class="theme-dark"><p>Hi</p></body>"#));
+///
+/// // The override ended with that page.
+/// let page = Document::new().color_theme(ColorTheme::new("light")).render(Style::Lined);
+/// assert!(page.contains(r#"<html lang="en" data-theme="light">"#));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColorTheme {
+    name: String,
+    marker: ThemeMarker,
+    target: ThemeTarget,
+}
+
+impl ColorTheme {
+    /// Creates a theme marked with `data-theme` on the `html` element.
+    pub fn new(name: impl Into<String>) -> Self {
+        ColorTheme { name: name.into(), marker: ThemeMarker::Data, target: ThemeTarget::Html }
+    }
+
+    /// Marks the theme with a `theme-<name>` class instead.
+    pub fn as_class(mut self) -> Self {
+        self.marker = ThemeMarker::Class;
+        self
+    }
+
+    /// Marks the `body` element instead of `html`.
+    pub fn on_body(mut self) -> Self {
+        self.target = ThemeTarget::Body;
+        self
+    }
+
+    #[cfg(feature = "macros")]
+    pub(crate) fn renamed(mut self, name: String) -> Self {
+        self.name = name;
+        self
+    }
+
+    /// Returns the theme name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns how the theme is marked.
+    pub fn marker(&self) -> ThemeMarker {
+        self.marker
+    }
+
+    /// Returns the element the theme is marked on.
+    pub fn target(&self) -> ThemeTarget {
+        self.target
+    }
+
+    /// Returns the theme class, `theme-<name>`.
+    pub fn class(&self) -> String {
+        format!("theme-{}", self.name)
+    }
+
+    /// Returns the `(attribute, value)` pair marked on the target element.
+    pub fn attribute(&self) -> (&'static str, String) {
+        match self.marker {
+            ThemeMarker::Data => ("data-theme", self.name.clone()),
+            ThemeMarker::Class => ("class", self.class()),
+        }
+    }
+}
+
+/// Returns the color theme name set with
+/// [`context::set_color_theme`](crate::context::set_color_theme), if any.
+pub fn color_theme() -> Option<String> {
+    context::color_theme()
+}
+
+/// Returns the class of the current color theme (`theme-dark`), or an empty
+/// string when none is set, for components that style themselves.
+///
+/// ```rust
+/// use forge_rsx::{rsx, context};
+/// use forge_rsx::theme::theme_class;
+///
+/// context::clear();
+/// assert_eq!(theme_class(), "");
+/// context::set_color_theme("dark");
+/// assert_eq!(rsx!(lined, div { class: theme_class() }), r#"<div class="theme-dark"></div>"#);
+/// ```
+pub fn theme_class() -> String {
+    color_theme().map(|name| format!("theme-{}", name)).unwrap_or_default()
+}
@@ -0,0 +1,160 @@
+use std::borrow::Cow;
+use std::cell::{Cell, Ref, RefCell};
+
+use html5ever::interface::{ElementFlags, NodeOrText, QuirksMode, TreeSink};
+use html5ever::tendril::{StrTendril, TendrilSink};
+use html5ever::tokenizer::TokenizerOpts;
+use html5ever::tree_builder::TreeBuilderOpts;
+use html5ever::{Attribute, ParseOpts, QualName, local_name, ns};
+
+pub use crate::error::ParseError;
+
+/// Runs markup through the html5ever parser (the HTML5 parsing algorithm
+/// browsers implement) and returns the errors it reports, such as stray end
+/// tags, misnested elements or bad character references. An empty list means
+/// browsers will build the intended tree.
+///
+/// `rsx!` always produces well-formed markup, but braced expressions can
+/// splice in raw fragments; asserting on this in tests catches the broken
+/// ones. Input starting with a doctype is parsed as a document, anything else
+/// as the content of a `<body>`.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::rsx;
+/// use forge_rsx::validate::validate_html;
+///
+/// let good = rsx!(lined, ul { li { "One" } li { "Two" } });
+/// assert!(validate_html(&good).is_empty());
+///
+/// let broken = "<ul><li>One</b></li></ul>";
+/// let errors = validate_html(broken);
+/// assert_eq!(errors.len(), 1);
+/// assert_eq!(errors[0].line, 1);
+///
+/// let page = rsx!(btfy2, doctype_html html { head { title { "Hi" } } body { p { "Hello" } } });
+/// assert!(validate_html(&page).is_empty());
+/// ```
+pub fn validate_html(html: &str) -> Vec<ParseError> {
+    parse_errors(html)
+}
+
+/// Like [`validate_html`], as a `Result`: the parse errors are returned as
+/// [`Error::Validation`](crate::Error::Validation).
+///
+/// ```rust
+/// use forge_rsx::Error;
+/// use forge_rsx::validate::check_html;
+///
+/// assert!(check_html("<p>ok</p>").is_ok());
+/// let Err(Error::Validation(errors)) = check_html("<p>1</div>") else { panic!() };
+/// assert_eq!(errors.len(), 1);
+/// ```
+pub fn check_html(html: &str) -> crate::error::Result<()> {
+    let errors = parse_errors(html);
+    if errors.is_empty() { Ok(()) } else { Err(crate::Error::Validation(errors)) }
+}
+
+fn parse_errors(html: &str) -> Vec<ParseError> {
+    let opts = ParseOpts {
+        tokenizer: TokenizerOpts { exact_errors: true, ..TokenizerOpts::default() },
+        tree_builder: TreeBuilderOpts { exact_errors: true, ..TreeBuilderOpts::default() },
+    };
+    let is_document = html.trim_start().get(..9).is_some_and(|s| s.eq_ignore_ascii_case("<!doctype"));
+    let sink = ErrorSink::default();
+    if is_document {
+        html5ever::parse_document(sink, opts).one(html)
+    } else {
+        let context = QualName::new(None, ns!(html), local_name!("body"));
+        html5ever::parse_fragment(sink, opts, context, Vec::new(), false).one(html)
+    }
+}
+
+/// A tree sink that only keeps element names (which the tree builder needs
+/// to look up) and collects the reported errors.
+struct ErrorSink {
+    names: RefCell<Vec<QualName>>,
+    line: Cell<u64>,
+    errors: RefCell<Vec<ParseError>>,
+}
+
+impl Default for ErrorSink {
+    fn default() -> Self {
+        ErrorSink {
+            // Handle 0 is the document.
+            names: RefCell::new(vec![QualName::new(None, ns!(), local_name!(""))]),
+            line: Cell::new(1),
+            errors: RefCell::new(Vec::new()),
+        }
+    }
+}
+
+impl ErrorSink {
+    fn new_handle(&self, name: QualName) -> usize {
+        let mut names = self.names.borrow_mut();
+        names.push(name);
+        names.len() - 1
+    }
+}
+
+impl TreeSink for ErrorSink {
+    type Handle = usize;
+    type Output = Vec<ParseError>;
+    type ElemName<'a> = Ref<'a, QualName>;
+
+    fn finish(self) -> Vec<ParseError> {
+        self.errors.into_inner()
+    }
+
+    fn parse_error(&self, msg: Cow<'static, str>) {
+        self.errors.borrow_mut().push(ParseError { line: self.line.get(), message: msg.into_owned() });
+    }
+
+    fn set_current_line(&self, line_number: u64) {
+        self.line.set(line_number);
+    }
+
+    fn get_document(&self) -> usize {
+        0
+    }
+
+    fn elem_name<'a>(&'a self, target: &'a usize) -> Ref<'a, QualName> {
+        Ref::map(self.names.borrow(), |names| &names[*target])
+    }
+
+    fn create_element(&self, name: QualName, _attrs: Vec<Attribute>, _flags: ElementFlags) -> usize {
+        self.new_handle(name)
+    }
+
+    fn create_comment(&self, _text: StrTendril) -> usize {
+        self.new_handle(QualName::new(None, ns!(), local_name!("")))
+    }
+
+    fn create_pi(&self, _target: StrTendril, _data: StrTendril) -> usize {
+        self.new_handle(QualName::new(None, ns!(), local_name!("")))
+    }
+
+    fn append(&self, _parent: &usize, _child: NodeOrText<usize>) {}
+
+    fn append_based_on_parent_node(&self, _element: &usize, _prev_element: &usize, _child: NodeOrText<usize>) {}
+
+    fn append_doctype_to_document(&self, _name: StrTendril, _public_id: StrTendril, _system_id: StrTendril) {}
+
+    fn get_template_contents(&self, _target: &usize) -> usize {
+        self.new_handle(QualName::new(None, ns!(), local_name!("")))
+    }
+
+    fn same_node(&self, x: &usize, y: &usize) -> bool {
+        x == y
+    }
+
+    fn set_quirks_mode(&self, _mode: QuirksMode) {}
+
+    fn append_before_sibling(&self, _sibling: &usize, _new_node: NodeOrText<usize>) {}
+
+    fn add_attrs_if_missing(&self, _target: &usize, _attrs: Vec<Attribute>) {}
+
+    fn remove_from_parent(&self, _target: &usize) {}
+
+    fn reparent_children(&self, _node: &usize, _new_parent: &usize) {}
+}
@@ -0,0 +1,108 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use notify::{EventKind, RecursiveMode, Watcher};
+
+use crate::error::{Error, Result};
+
+/// How long the file system must stay quiet before the callback runs, so an
+/// editor saving several files (or writing one in steps) triggers a single
+/// rebuild.
+pub const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Keeps a [`watch`] running; dropping it stops watching.
+pub struct WatchHandle {
+    _watcher: notify::RecommendedWatcher,
+}
+
+/// Watches `paths` (files, or directories recursively) and calls `callback`
+/// with the changed paths after each burst of changes, e.g. to re-render the
+/// pages built from edited data or template files.
+///
+/// The callback runs on a background thread, once per burst (see
+/// [`DEBOUNCE`]), with each path listed once. Temporary files of
+/// [`files::write_atomic`](crate::files::write_atomic) are ignored, and when
+/// pages are written with [`WriteMode::IfChanged`](crate::files::WriteMode),
+/// writing them inside a watched directory settles after one rebuild.
+///
+/// Requires the `notify` feature. Watcher failures are returned as
+/// [`Error::Io`].
+///
+/// # Examples
+/// ```rust,no_run
+/// use forge_rsx::{rsx, Style, Document};
+/// use forge_rsx::files::WriteMode;
+/// use forge_rsx::watch::watch;
+///
+/// fn build() {
+///     let posts = std::fs::read_to_string("content/posts.txt").unwrap_or_default();
+///     let page = Document::new().body(rsx!(lined, ul { for post in posts.lines() => { li { {post} } } }));
+///     page.render_to_file("public/index.html", Style::Lined, WriteMode::IfChanged).unwrap();
+/// }
+///
+/// build();
+/// let _watching = watch(&["content"], |changed| {
+///     println!("rebuilding after changes to {:?}", changed);
+///     build();
+/// })
+/// .unwrap();
+/// std::thread::park(); // serve or wait
+/// ```
+pub fn watch<P: AsRef<Path>>(
+    paths: &[P],
+    mut callback: impl FnMut(&[PathBuf]) + Send + 'static,
+) -> Result<WatchHandle> {
+    let (tx, rx) = mpsc::channel::<notify::Result<notify::Event>>();
+    let mut watcher = notify::recommended_watcher(tx).map_err(watch_error)?;
+    for path in paths {
+        watcher.watch(path.as_ref(), RecursiveMode::Recursive).map_err(watch_error)?;
+    }
+
+    thread::spawn(move || {
+        let mut changed: Vec<PathBuf> = Vec::new();
+        loop {
+            // Wait for a change, then until the burst is over.
+            let event = if changed.is_empty() { rx.recv().map_err(|_| ()) } else { rx.recv_timeout(DEBOUNCE).map_err(|_| ()) };
+            match event {
+                Ok(Ok(event)) => {
+                    if matches!(event.kind, EventKind::Access(_)) {
+                        continue;
+                    }
+                    for path in event.paths {
+                        if !is_temp_file(&path) && !changed.contains(&path) {
+                            changed.push(path);
+                        }
+                    }
+                }
+                Ok(Err(_)) => {}
+                // The watcher was dropped: stop.
+                Err(()) if changed.is_empty() => break,
+                Err(()) => {
+                    callback(&changed);
+                    changed.clear();
+                }
+            }
+        }
+    });
+    Ok(WatchHandle { _watcher: watcher })
+}
+
+/// Reports a watcher failure (a missing path, a lack of watch handles) as
+/// [`Error::Io`].
+fn watch_error(e: notify::Error) -> Error {
+    match e.kind {
+        notify::ErrorKind::Io(io) => Error::Io(io),
+        notify::ErrorKind::PathNotFound => Error::Io(std::io::Error::new(std::io::ErrorKind::NotFound, e.to_string())),
+        _ => Error::Io(std::io::Error::other(e.to_string())),
+    }
+}
+
+/// Whether `path` is a temporary file of `files::write_atomic`
+/// (`.<name>.<pid>-<n>.tmp`).
+fn is_temp_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with('.') && name.ends_with(".tmp"))
+}
@@ -0,0 +1,496 @@
+use std::fmt;
+
+use crate::context;
+use crate::error::{Error, Result};
+use crate::node::{Element, Node};
+use crate::output::Html;
+use crate::rules::PreEscaped;
+use crate::{raw, rsx};
+
+const MONTHS: [&str; 12] = [
+    "January", "February", "March", "April", "May", "June",
+    "July", "August", "September", "October", "November", "December",
+];
+
+const WEEKDAYS: [&str; 7] = ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday"];
+
+/// A day of the proleptic Gregorian calendar, as passed to the cell closure
+/// of [`calendar`]. Displays as `YYYY-MM-DD`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    /// The year.
+    pub year: i32,
+    /// The month, 1 to 12.
+    pub month: u32,
+    /// The day of the month, starting at 1.
+    pub day: u32,
+}
+
+impl Date {
+    /// Creates a date, or returns [`Error::Render`](crate::Error::Render) if
+    /// `month` is not between 1 and 12 or `day` is not a day of that month.
+    ///
+    /// ```rust
+    /// use forge_rsx::widgets::Date;
+    ///
+    /// assert_eq!(Date::new(2024, 2, 29).unwrap().to_string(), "2024-02-29");
+    /// assert!(Date::new(2023, 2, 29).is_err());
+    /// assert!(Date::new(2024, 0, 1).is_err());
+    /// ```
+    pub fn new(year: i32, month: u32, day: u32) -> Result<Self> {
+        if !(1..=12).contains(&month) {
+            return Err(Error::Render(format!("month must be between 1 and 12, got {}", month)));
+        }
+        if !(1..=days_in_month(year, month)).contains(&day) {
+            return Err(Error::Render(format!("{} is not a day of {}-{:02}", day, year, month)));
+        }
+        Ok(Date { year, month, day })
+    }
+
+    /// Returns the day of the week, 0 for Monday to 6 for Sunday.
+    ///
+    /// The fields are public, so a month outside 1 to 12 counts on from the
+    /// year (month 13 is January of the next year) instead of panicking.
+    ///
+    /// ```rust
+    /// use forge_rsx::widgets::Date;
+    ///
+    /// assert_eq!(Date::new(2024, 2, 29).unwrap().weekday(), 3); // a Thursday
+    /// assert_eq!(Date::new(2000, 1, 1).unwrap().weekday(), 5); // a Saturday
+    /// assert_eq!(Date { year: 1999, month: 13, day: 1 }.weekday(), 5);
+    /// ```
+    pub fn weekday(&self) -> u32 {
+        // Sakamoto's method, shifted so that Monday is 0.
+        const OFFSETS: [i32; 12] = [0, 3, 2, 5, 0, 3, 5, 1, 4, 6, 2, 4];
+        let months = self.year as i64 * 12 + self.month as i64 - 1;
+        let month = months.rem_euclid(12) as usize;
+        let year = months.div_euclid(12) - if month < 2 { 1 } else { 0 };
+        let sunday_based = (year + year.div_euclid(4) - year.div_euclid(100) + year.div_euclid(400)
+            + OFFSETS[month] as i64
+            + self.day as i64)
+            .rem_euclid(7);
+        ((sunday_based + 6) % 7) as u32
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// Returns the number of days of `month` (1 to 12) in `year`.
+pub fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        4 | 6 | 9 | 11 => 30,
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        _ => 31,
+    }
+}
+
+/// Renders an accessible month grid: a `table` captioned with the month and
+/// year, a header row of `th scope="col"` weekdays (Monday first, with the
+/// full name in `abbr`), and one row per week.
+///
+/// Each day cell holds a `time` element with the day number, followed by
+/// the markup `cell` returns for that date (which may be empty). Text must
+/// go through `rsx!` or [`Html::push_text`] to be escaped; use
+/// [`Html::pre_escaped`] only for trusted markup. Cells before the first and
+/// after the last day of the month are left empty.
+///
+/// Returns [`Error::Render`](crate::Error::Render) if `month` is not between
+/// 1 and 12.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::rsx;
+/// use forge_rsx::output::Html;
+/// use forge_rsx::widgets::calendar;
+///
+/// let html = calendar(2024, 2, |date| match date.day {
+///     14 => rsx!(lined, a { href: "/launch", "Launch" }),
+///     15 => {
+///         let mut note = Html::new();
+///         note.push_text("<script>x</script>");
+///         note
+///     }
+///     _ => Html::new(),
+/// })
+/// .unwrap();
+/// assert!(html.starts_with(concat!(
+///     r#"<table class="calendar"><caption>February 2024</caption><thead><tr>"#,
+///     r#"<th scope="col" abbr="Monday">Mon</th><th scope="col" abbr="Tuesday">Tue</th>"#,
+/// )));
+/// // February 2024 starts on a Thursday.
+/// assert!(html.contains(concat!(
+///     r#"<tbody><tr><td></td><td></td><td></td>"#,
+///     r#"<td><time datetime="2024-02-01">1</time></td>"#,
+/// )));
+/// assert!(html.contains(r#"<td><time datetime="2024-02-14">14</time><a href="/launch">Launch</a></td>"#));
+/// assert!(html.contains(r#"<time datetime="2024-02-15">15</time>&lt;script&gt;x&lt;/script&gt;</td>"#));
+/// assert!(html.ends_with(r#"<td><time datetime="2024-02-29">29</time></td><td></td><td></td><td></td></tr></tbody></table>"#));
+/// assert_eq!(html.matches("<tr>").count(), 6);
+/// assert!(calendar(2024, 13, |_| Html::new()).is_err());
+/// ```
+pub fn calendar(year: i32, month: u32, mut cell: impl FnMut(Date) -> Html) -> Result<String> {
+    let lead = Date::new(year, month, 1)?.weekday() as usize;
+    let mut days = vec![String::new(); lead];
+    for day in 1..=days_in_month(year, month) {
+        let date = Date { year, month, day };
+        days.push(format!("{}{}", rsx!(lined, time { datetime: date.to_string(), {day} }), cell(date)));
+    }
+    days.resize(days.len().div_ceil(7) * 7, String::new());
+    let caption = format!("{} {}", MONTHS[month as usize - 1], year);
+
+    Ok(String::from(rsx!(lined, table {
+        class: "calendar",
+        caption { {caption} }
+        thead {
+            tr {
+                for name in WEEKDAYS => { th { scope: "col", abbr: name, {&name[..3]} } }
+            }
+        }
+        tbody {
+            for week in days.chunks(7) => {
+                tr {
+                    for day in week => { td { {raw!(day)} } }
+                }
+            }
+        }
+    })))
+}
+
+/// Renders the markup of the WAI-ARIA tabs pattern: a `tablist` labelled
+/// `label` with one `tab` button per `(title, panel)` pair, followed by the
+/// `tabpanel`s. Tab `selected` is active and the other panels are `hidden`.
+///
+/// Ids come from [`context::unique_id`](crate::context::unique_id), so
+/// `aria-controls`/`aria-labelledby` stay unique when the widget appears
+/// several times. Switching tabs is left to client code (htmx, Alpine, ..).
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::{context, widgets::tabs};
+///
+/// context::clear();
+/// let html = tabs("Settings", &[("General", "<p>G</p>"), ("Privacy", "<p>P</p>")], 0);
+/// assert_eq!(html, concat!(
+///     r#"<div class="tabs"><div role="tablist" aria-label="Settings">"#,
+///     r#"<button type="button" role="tab" id="tab-1" aria-selected="true" aria-controls="tabpanel-1" tabindex="0">General</button>"#,
+///     r#"<button type="button" role="tab" id="tab-2" aria-selected="false" aria-controls="tabpanel-2" tabindex="-1">Privacy</button>"#,
+///     r#"</div><div role="tabpanel" id="tabpanel-1" aria-labelledby="tab-1" tabindex="0"><p>G</p></div>"#,
+///     r#"<div role="tabpanel" id="tabpanel-2" aria-labelledby="tab-2" tabindex="0" hidden><p>P</p></div></div>"#,
+/// ));
+/// ```
+pub fn tabs(label: &str, items: &[(&str, &str)], selected: usize) -> String {
+    // (tab id, panel id, selected, title, panel)
+    let items: Vec<_> = items
+        .iter()
+        .enumerate()
+        .map(|(i, (title, panel))| (context::unique_id("tab"), context::unique_id("tabpanel"), i == selected, title, panel))
+        .collect();
+
+    rsx!(lined, div {
+        class: "tabs",
+        div {
+            role: "tablist",
+            "aria-label": label,
+            for item in &items => {
+                button {
+                    type: "button",
+                    role: "tab",
+                    id: &item.0,
+                    "aria-selected": item.2.to_string(),
+                    "aria-controls": &item.1,
+                    tabindex: if item.2 { "0" } else { "-1" },
+                    {item.3}
+                }
+            }
+        }
+        for item in &items => {
+            div {
+                role: "tabpanel",
+                id: &item.1,
+                "aria-labelledby": &item.0,
+                tabindex: "0",
+                hidden: (!item.2).to_string(),
+                {raw!(item.4)}
+            }
+        }
+    }).into()
+}
+
+/// Renders the markup of the WAI-ARIA accordion pattern: for each
+/// `(title, panel)` pair, a heading of `level` (1 to 6) wrapping a button
+/// with `aria-expanded`/`aria-controls`, and a labelled `region` that is
+/// `hidden` unless its index is in `expanded`.
+///
+/// Ids come from [`context::unique_id`](crate::context::unique_id); toggling
+/// is left to client code.
+///
+/// ```rust
+/// use forge_rsx::{context, widgets::accordion};
+///
+/// context::clear();
+/// let html = accordion(2, &[("Shipping", "Free"), ("Returns", "30 days")], &[0]);
+/// assert_eq!(html, concat!(
+///     r#"<div class="accordion"><h2><button type="button" id="accordion-1" aria-expanded="true" aria-controls="accordion-panel-1">Shipping</button></h2>"#,
+///     r#"<div role="region" id="accordion-panel-1" aria-labelledby="accordion-1">Free</div>"#,
+///     r#"<h2><button type="button" id="accordion-2" aria-expanded="false" aria-controls="accordion-panel-2">Returns</button></h2>"#,
+///     r#"<div role="region" id="accordion-panel-2" aria-labelledby="accordion-2" hidden>30 days</div></div>"#,
+/// ));
+/// ```
+pub fn accordion(level: u8, items: &[(&str, &str)], expanded: &[usize]) -> String {
+    let level = level.clamp(1, 6);
+    let sections: String = items
+        .iter()
+        .enumerate()
+        .map(|(i, (title, content))| {
+            let (button, panel) = (context::unique_id("accordion"), context::unique_id("accordion-panel"));
+            let open = expanded.contains(&i);
+            let trigger = rsx!(lined, button {
+                type: "button",
+                id: &button,
+                "aria-expanded": open.to_string(),
+                "aria-controls": &panel,
+                {title}
+            });
+            let region = rsx!(lined, div {
+                role: "region",
+                id: &panel,
+                "aria-labelledby": &button,
+                hidden: (!open).to_string(),
+                {raw!(content)}
+            });
+            format!("<h{0}>{1}</h{0}>{2}", level, trigger, region)
+        })
+        .collect();
+    rsx!(lined, div { class: "accordion", {raw!(sections)} }).into()
+}
+
+/// Renders a `details` element with a `summary`, a no-JS disclosure widget.
+/// `open` sets the boolean `open` attribute (left out when `false`).
+///
+/// ```rust
+/// use forge_rsx::widgets::disclosure;
+///
+/// assert_eq!(disclosure("More", true, "<p>Details</p>"), "<details open><summary>More</summary><p>Details</p></details>");
+/// assert_eq!(disclosure("More", false, "…"), "<details><summary>More</summary>…</details>");
+/// ```
+pub fn disclosure(summary: impl fmt::Display, open: bool, body: impl fmt::Display) -> String {
+    rsx!(lined, details { open: open.to_string(), summary { {summary} } {raw!(body)} }).into()
+}
+
+/// Like [`disclosure`], with a `name` shared by the `details` elements of an
+/// exclusive accordion: browsers keep at most one of them open.
+///
+/// ```rust
+/// use forge_rsx::widgets::exclusive_disclosure;
+///
+/// let faq = format!(
+///     "{}{}",
+///     exclusive_disclosure("faq", "Shipping?", true, "Free"),
+///     exclusive_disclosure("faq", "Returns?", false, "30 days"),
+/// );
+/// assert_eq!(faq, concat!(
+///     r#"<details name="faq" open><summary>Shipping?</summary>Free</details>"#,
+///     r#"<details name="faq"><summary>Returns?</summary>30 days</details>"#,
+/// ));
+/// ```
+pub fn exclusive_disclosure(name: &str, summary: impl fmt::Display, open: bool, body: impl fmt::Display) -> String {
+    rsx!(lined, details { name: name, open: open.to_string(), summary { {summary} } {raw!(body)} }).into()
+}
+
+/// Renders a modal `dialog` scaffold: a header with the title (an `h2`
+/// labelling the dialog) and a close button, then the body and, unless
+/// empty, the footer.
+///
+/// The close button submits a `form method="dialog"`, so it works without
+/// scripts, and comes first in tab order after the title. Opened with
+/// `showModal()` (or [`modal_opener`]), the dialog is modal and traps focus
+/// natively. The ids are `<id>` and `<id>-title`.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::widgets::{modal, modal_opener};
+///
+/// let html = modal("confirm", "Delete file?", "<p>This cannot be undone.</p>", r#"<button>Delete</button>"#);
+/// assert_eq!(html, concat!(
+///     r#"<dialog id="confirm" class="modal" aria-labelledby="confirm-title">"#,
+///     r#"<header class="modal-header"><h2 id="confirm-title">Delete file?</h2>"#,
+///     r#"<form method="dialog"><button type="submit" class="modal-close" aria-label="Close">×</button></form></header>"#,
+///     r#"<div class="modal-body"><p>This cannot be undone.</p></div>"#,
+///     r#"<footer class="modal-footer"><button>Delete</button></footer></dialog>"#,
+/// ));
+/// assert!(!modal("info", "Info", "Saved.", "").contains("footer"));
+///
+/// assert_eq!(
+///     modal_opener("confirm", "Delete"),
+///     r#"<button type="button" aria-haspopup="dialog" aria-controls="confirm" commandfor="confirm" command="show-modal">Delete</button>"#
+/// );
+/// ```
+pub fn modal(id: &str, title: impl fmt::Display, body: impl fmt::Display, footer: impl fmt::Display) -> String {
+    let title_id = format!("{}-title", id);
+    let footer = footer.to_string();
+    let footer = (!footer.is_empty()).then(|| PreEscaped(rsx!(lined, footer { class: "modal-footer", {raw!(footer)} })));
+    rsx!(lined, dialog {
+        id: id,
+        class: "modal",
+        "aria-labelledby": &title_id,
+        header {
+            class: "modal-header",
+            h2 { id: &title_id, {title} }
+            form {
+                method: "dialog",
+                button { type: "submit", class: "modal-close", "aria-label": "Close", "×" }
+            }
+        }
+        div { class: "modal-body", {raw!(body)} }
+        {footer}
+    }).into()
+}
+
+/// Renders a button opening the [`modal`] `id` through the declarative
+/// `command="show-modal"` invoker, with the matching ARIA attributes.
+pub fn modal_opener(id: &str, label: impl fmt::Display) -> String {
+    rsx!(lined, button {
+        type: "button",
+        "aria-haspopup": "dialog",
+        "aria-controls": id,
+        commandfor: id,
+        command: "show-modal",
+        {label}
+    }).into()
+}
+
+/// The direction of a sorted table column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortDirection {
+    /// Smallest first.
+    Ascending,
+    /// Largest first.
+    Descending,
+}
+
+impl SortDirection {
+    /// Returns the `aria-sort` value, `ascending` or `descending`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SortDirection::Ascending => "ascending",
+            SortDirection::Descending => "descending",
+        }
+    }
+
+    /// Returns the other direction.
+    pub fn reverse(self) -> Self {
+        match self {
+            SortDirection::Ascending => SortDirection::Descending,
+            SortDirection::Descending => SortDirection::Ascending,
+        }
+    }
+}
+
+/// Options of [`sortable`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SortOptions {
+    /// The currently sorted column (by key, see [`sortable`]) and direction.
+    pub sorted: Option<(String, SortDirection)>,
+    /// An htmx endpoint: header buttons then `hx-get`
+    /// `<endpoint>?sort=<key>&dir=<direction>` and swap the whole table,
+    /// instead of relying on [`SORT_SCRIPT`].
+    pub endpoint: Option<String>,
+}
+
+/// A small client-side sorter for tables decorated by [`sortable`]: clicking
+/// a header button sorts the first `tbody` by the `data-sort-key` of the
+/// cells (or their text), numbers compared numerically, and updates
+/// `aria-sort`. Buttons with `hx-get` are left to htmx.
+pub const SORT_SCRIPT: &str = r#"document.addEventListener("click",e=>{const b=e.target.closest("th button[data-sort-column]");if(!b||b.hasAttribute("hx-get"))return;const th=b.closest("th"),t=th.closest("table"),i=[...th.parentNode.children].indexOf(th),d=th.getAttribute("aria-sort")==="ascending"?"descending":"ascending";t.querySelectorAll("th[aria-sort]").forEach(h=>h.removeAttribute("aria-sort"));th.setAttribute("aria-sort",d);const k=r=>{const c=r.cells[i];return c?c.dataset.sortKey??c.textContent.trim():""};const rows=[...t.tBodies[0].rows].sort((x,y)=>k(x).localeCompare(k(y),undefined,{numeric:true})*(d==="ascending"?1:-1));t.tBodies[0].append(...rows)})"#;
+
+/// Decorates a rendered table for sorting: the content of each `th` in
+/// `thead` is wrapped in a `button` carrying `data-sort-column`, and the
+/// sorted column gets `aria-sort`.
+///
+/// A column's key is the `data-sort-column` attribute of its `th` when set,
+/// its index otherwise. Give cells a machine-readable order with
+/// [`sort_cell`]. Headers with `data-nosort` are left alone.
+///
+/// # Examples
+/// ```rust
+/// use forge_rsx::{raw, rsx};
+/// use forge_rsx::widgets::{sort_cell, sortable, SortDirection, SortOptions};
+///
+/// let table = rsx!(lined, table {
+///     thead { tr { th { "data-sort-column": "name", "Name" } th { "Joined" } } }
+///     tbody { tr { td { "Ann" } {raw!(sort_cell("Mar 3", "2024-03-03"))} } }
+/// });
+/// let options = SortOptions {
+///     sorted: Some(("name".to_string(), SortDirection::Ascending)),
+///     endpoint: Some("/users".to_string()),
+/// };
+/// assert_eq!(sortable(&table, &options), concat!(
+///     r#"<table><thead><tr><th data-sort-column="name" aria-sort="ascending">"#,
+///     r#"<button type="button" data-sort-column="name" hx-get="/users?sort=name&dir=descending" hx-target="closest table" hx-swap="outerHTML">Name</button></th>"#,
+///     r#"<th><button type="button" data-sort-column="1" hx-get="/users?sort=1&dir=ascending" hx-target="closest table" hx-swap="outerHTML">Joined</button></th></tr></thead>"#,
+///     r#"<tbody><tr><td>Ann</td><td data-sort-key="2024-03-03">Mar 3</td></tr></tbody></table>"#,
+/// ));
+/// ```
+pub fn sortable(table: &str, options: &SortOptions) -> String {
+    let mut tree = Node::parse(table);
+    decorate_headers(&mut tree, options, false);
+    tree.to_string()
+}
+
+/// Renders a `td` showing `content` and sorting by `key`.
+///
+/// ```rust
+/// assert_eq!(forge_rsx::widgets::sort_cell("$1,200", 1200), r#"<td data-sort-key="1200">$1,200</td>"#);
+/// ```
+pub fn sort_cell(content: impl fmt::Display, key: impl fmt::Display) -> String {
+    rsx!(lined, td { "data-sort-key": key.to_string(), {raw!(content)} }).into()
+}
+
+fn decorate_headers(node: &mut Node, options: &SortOptions, in_head: bool) {
+    let Node::Element(el) = node else {
+        if let Node::Fragment(nodes) = node {
+            nodes.iter_mut().for_each(|child| decorate_headers(child, options, in_head));
+        }
+        return;
+    };
+    let in_head = in_head || el.tag == "thead";
+    if !in_head {
+        el.children.iter_mut().for_each(|child| decorate_headers(child, options, false));
+        return;
+    }
+    let mut index = 0;
+    for child in el.children.iter_mut() {
+        match child {
+            Node::Element(th) if th.tag == "th" => {
+                let key = th.get_attr("data-sort-column").map_or_else(|| index.to_string(), str::to_string);
+                index += 1;
+                if th.get_attr("data-nosort").is_some() {
+                    continue;
+                }
+                let current = options.sorted.as_ref().filter(|(k, _)| *k == key).map(|(_, dir)| *dir);
+                if let Some(dir) = current {
+                    th.attrs.retain(|(name, _)| name != "aria-sort");
+                    th.attrs.push(("aria-sort".into(), Some(dir.as_str().into())));
+                }
+                let mut button = Element::new("button").attr("type", "button").attr("data-sort-column", key.as_str());
+                if let Some(endpoint) = &options.endpoint {
+                    let next = current.map_or(SortDirection::Ascending, SortDirection::reverse);
+                    let separator = if endpoint.contains('?') { '&' } else { '?' };
+                    let url = format!("{}{}sort={}&dir={}", endpoint, separator, key, next.as_str());
+                    button = button
+                        .attr("hx-get", url.as_str())
+                        .attr("hx-target", "closest table")
+                        .attr("hx-swap", "outerHTML");
+                }
+                button.children = std::mem::take(&mut th.children);
+                th.children.push(button.into());
+            }
+            _ => decorate_headers(child, options, true),
+        }
+    }
+}